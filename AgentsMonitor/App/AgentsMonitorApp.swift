@@ -2,28 +2,48 @@ import SwiftUI
 
 @main
 struct AgentsMonitorApp: App {
+    @NSApplicationDelegateAdaptor(AppDelegate.self) private var appDelegate
     @State private var sessionStore: SessionStore
+    @State private var appLock: AppLock
     @AppStorage("appearance") private var appearance: String = "system"
     private let appEnvironment: AppEnvironment
 
     init() {
-        UserDefaults.standard.register(defaults: [
-            "activeOnly": false,
-            "showSidechains": false,
-            "refreshInterval": 5.0,
-            "appearance": "system",
-            "codexEnabled": true,
-            "claudeCodeEnabled": true
-        ])
+        AppSettings.registerDefaults()
         let environment = AppEnvironment.current
+        if !environment.isTesting && SingleInstanceGuard.handOffToExistingInstance() {
+            exit(0)
+        }
         self.appEnvironment = environment
-        _sessionStore = State(initialValue: SessionStore(environment: environment))
+        let store = SessionStore(environment: environment)
+        let lock = environment.isTesting ? AppLock(isEnabled: { false }) : AppLock()
+        _sessionStore = State(initialValue: store)
+        _appLock = State(initialValue: lock)
+        AutomationBridge.store = store
+        AutomationBridge.appLock = lock
+
+        AppDelegate.prepareForTermination = {
+            await store.prepareForTermination()
+        }
+
+        if !environment.isTesting {
+            SingleInstanceGuard.startListening { arguments in
+                Task { @MainActor in
+                    if let id = SingleInstanceGuard.requestedSessionId(in: arguments) {
+                        store.requestedSessionId = id
+                    }
+                    SingleInstanceGuard.bringToFront()
+                    await store.refreshAll()
+                }
+            }
+        }
     }
 
     var body: some Scene {
-        MenuBarExtra("Agents Monitor", systemImage: "cpu") {
+        MenuBarExtra {
             MenuBarView()
                 .environment(sessionStore)
+                .environment(appLock)
                 .environment(\.appEnvironment, appEnvironment)
                 .onChange(of: appearance) { _, newValue in
                     applyAppearance(newValue)
@@ -31,6 +51,8 @@ struct AgentsMonitorApp: App {
                 .onAppear {
                     applyAppearance(appearance)
                 }
+        } label: {
+            MenuBarStatusLabel(stats: sessionStore.quickStats)
         }
         .menuBarExtraStyle(.window)
     }
@@ -47,3 +69,19 @@ struct AgentsMonitorApp: App {
         }
     }
 }
+
+/// Holds off quitting until the store has written its caches, instead of
+/// blocking the main thread while the analytics actor flushes.
+final class AppDelegate: NSObject, NSApplicationDelegate {
+    static var prepareForTermination: (() async -> Void)?
+
+    func applicationShouldTerminate(_ sender: NSApplication) -> NSApplication.TerminateReply {
+        guard let prepare = Self.prepareForTermination else { return .terminateNow }
+        Self.prepareForTermination = nil
+        Task { @MainActor in
+            await prepare()
+            sender.reply(toApplicationShouldTerminate: true)
+        }
+        return .terminateLater
+    }
+}
@@ -0,0 +1,145 @@
+import AppIntents
+
+/// The running app's store, for App Intents. Shortcuts runs the intents
+/// in-process, launching the app in the background if it isn't running.
+@MainActor
+enum AutomationBridge {
+    static var store: SessionStore?
+    static var appLock: AppLock?
+
+    static func sessions() throws -> [Session] {
+        let store = try unlockedStore()
+        return store.sessions
+    }
+
+    /// The store, once the lock has had its chance to engage. The lock only
+    /// re-arms when asked, and Shortcuts can ask long after the popover closed.
+    static func unlockedStore(now: Date = Date()) throws -> SessionStore {
+        guard let store else { throw AutomationError.notReady }
+        appLock?.lockIfIdle(now: now)
+        if appLock?.isLocked == true { throw AutomationError.locked }
+        return store
+    }
+}
+
+enum AutomationError: LocalizedError {
+    case notReady
+    case locked
+
+    var errorDescription: String? {
+        switch self {
+        case .notReady: return "Agents Monitor is still starting"
+        case .locked: return "Unlock Agents Monitor to share sessions with Shortcuts"
+        }
+    }
+}
+
+struct SessionEntity: AppEntity {
+    static var typeDisplayRepresentation: TypeDisplayRepresentation = "Agent Session"
+    static var defaultQuery = SessionEntityQuery()
+
+    let id: UUID
+
+    @Property(title: "Name")
+    var name: String
+
+    @Property(title: "Status")
+    var status: String
+
+    @Property(title: "Agent")
+    var agent: String
+
+    @Property(title: "Project")
+    var project: String
+
+    /// The tool the session is asking permission for; empty when it isn't.
+    @Property(title: "Waiting On")
+    var waitingOn: String
+
+    init(session: Session) {
+        id = session.id
+        name = session.name
+        status = session.status.rawValue
+        agent = session.agentType.rawValue
+        project = session.projectPath ?? ""
+        waitingOn = session.pendingPermission?.toolName ?? ""
+    }
+
+    var displayRepresentation: DisplayRepresentation {
+        DisplayRepresentation(title: "\(name)", subtitle: "\(agent) · \(status)")
+    }
+}
+
+struct SessionEntityQuery: EntityQuery {
+    @MainActor
+    func entities(for identifiers: [UUID]) async throws -> [SessionEntity] {
+        try AutomationBridge.sessions()
+            .filter { identifiers.contains($0.id) }
+            .map(SessionEntity.init)
+    }
+
+    @MainActor
+    func suggestedEntities() async throws -> [SessionEntity] {
+        try AutomationBridge.sessions().map(SessionEntity.init)
+    }
+}
+
+enum SessionFilter: String, AppEnum {
+    case waiting
+    case active
+    case failed
+    case all
+
+    static var typeDisplayRepresentation: TypeDisplayRepresentation = "Session Filter"
+    static var caseDisplayRepresentations: [SessionFilter: DisplayRepresentation] = [
+        .waiting: "Waiting",
+        .active: "Active",
+        .failed: "Failed",
+        .all: "All"
+    ]
+
+    func includes(_ session: Session) -> Bool {
+        switch self {
+        case .waiting: return session.status == .waiting || session.pendingPermission != nil
+        case .active: return session.status == .running || session.status == .waiting
+        case .failed: return session.status == .failed
+        case .all: return true
+        }
+    }
+}
+
+struct GetSessionsIntent: AppIntent {
+    static var title: LocalizedStringResource = "Get Agent Sessions"
+    static var description = IntentDescription("Lists the Claude Code and Codex sessions Agents Monitor is tracking, newest first.")
+
+    @Parameter(title: "Show", default: .waiting)
+    var filter: SessionFilter
+
+    static var parameterSummary: some ParameterSummary {
+        Summary("Get \(\.$filter) sessions")
+    }
+
+    @MainActor
+    func perform() async throws -> some IntentResult & ReturnsValue<[SessionEntity]> {
+        let store = try AutomationBridge.unlockedStore()
+        await store.refreshAll()
+        let sessions = try AutomationBridge.sessions()
+            .filter(filter.includes)
+            .sorted { $0.startedAt > $1.startedAt }
+        return .result(value: sessions.map(SessionEntity.init))
+    }
+}
+
+struct AgentsMonitorShortcuts: AppShortcutsProvider {
+    static var appShortcuts: [AppShortcut] {
+        AppShortcut(
+            intent: GetSessionsIntent(),
+            phrases: [
+                "Get waiting sessions in \(.applicationName)",
+                "Which agents need me in \(.applicationName)"
+            ],
+            shortTitle: "Agent Sessions",
+            systemImageName: "hourglass"
+        )
+    }
+}
@@ -0,0 +1,75 @@
+import AppKit
+import Foundation
+
+/// Keeps a single copy of the app running. A second launch forwards its
+/// arguments to the existing instance and exits instead of starting another
+/// menu bar item that reads the same session files.
+enum SingleInstanceGuard {
+    static let handOffNotification = Notification.Name("com.agentsmonitor.app.handOff")
+    private static let argumentsKey = "arguments"
+    private static var observer: NSObjectProtocol?
+
+    static func otherInstance(bundleIdentifier: String? = Bundle.main.bundleIdentifier) -> NSRunningApplication? {
+        guard let bundleIdentifier else { return nil }
+        let currentPID = ProcessInfo.processInfo.processIdentifier
+        return NSRunningApplication.runningApplications(withBundleIdentifier: bundleIdentifier)
+            .first { $0.processIdentifier != currentPID && !$0.isTerminated }
+    }
+
+    /// Returns `true` when another instance is already running and has been
+    /// handed this launch's arguments; the caller should exit.
+    static func handOffToExistingInstance(arguments: [String] = ProcessInfo.processInfo.arguments) -> Bool {
+        guard let existing = otherInstance() else { return false }
+
+        DistributedNotificationCenter.default().postNotificationName(
+            handOffNotification,
+            object: nil,
+            userInfo: [argumentsKey: forwardedArguments(arguments)],
+            deliverImmediately: true
+        )
+        existing.activate()
+        AppLogger.logWarning("Another instance is running (pid \(existing.processIdentifier)), handing off", context: "SingleInstanceGuard")
+        return true
+    }
+
+    /// Listens for hand-offs from later launches. `handler` runs on the main queue.
+    static func startListening(_ handler: @escaping ([String]) -> Void) {
+        guard observer == nil else { return }
+        observer = DistributedNotificationCenter.default().addObserver(
+            forName: handOffNotification,
+            object: nil,
+            queue: .main
+        ) { notification in
+            let arguments = notification.userInfo?[argumentsKey] as? [String] ?? []
+            handler(arguments)
+        }
+    }
+
+    static func forwardedArguments(_ arguments: [String]) -> [String] {
+        Array(arguments.dropFirst())
+    }
+
+    /// The session a forwarded launch asks to show, from an
+    /// `agentsmonitor://session/<id>` link or `--session <id>`.
+    static func requestedSessionId(in arguments: [String]) -> UUID? {
+        for (index, argument) in arguments.enumerated() {
+            if argument == "--session", index + 1 < arguments.count {
+                return UUID(uuidString: arguments[index + 1])
+            }
+            if let url = URL(string: argument), url.scheme == "agentsmonitor", url.host == "session" {
+                return url.pathComponents.dropFirst().first.flatMap(UUID.init(uuidString:))
+            }
+        }
+        return nil
+    }
+
+    /// Activates the app and raises the menu bar window if it is open.
+    /// `MenuBarExtra` has no API to open it, so a requested session is
+    /// expanded the next time the user does.
+    @MainActor
+    static func bringToFront() {
+        guard let app = NSApp else { return }
+        app.activate(ignoringOtherApps: true)
+        app.windows.first { $0.isVisible && $0.className.contains("MenuBarExtraWindow") }?.makeKeyAndOrderFront(nil)
+    }
+}
@@ -0,0 +1,346 @@
+import Foundation
+
+/// Typed view over the preferences stored in `UserDefaults`.
+///
+/// Views keep binding to individual keys with `@AppStorage`; non-view code
+/// reads a validated snapshot through `AppSettings.load()` instead of poking
+/// at raw keys.
+struct AppSettings: Equatable {
+    enum Key {
+        static let activeOnly = "activeOnly"
+        static let showSidechains = "showSidechains"
+        static let refreshInterval = "refreshInterval"
+        static let refreshInBackground = "refreshInBackground"
+        static let appearance = "appearance"
+        static let codexEnabled = "codexEnabled"
+        static let claudeCodeEnabled = "claudeCodeEnabled"
+        static let launchAtLogin = "launchAtLogin"
+        static let notificationsEnabled = "notificationsEnabled"
+        static let claudeDirectory = "claudeDirectory"
+        static let codexDirectory = "codexDirectory"
+        static let analyticsEnabled = "analyticsEnabled"
+        static let redactionPatterns = "redactionPatterns"
+        static let dangerousCommandPatterns = "dangerousCommandPatterns"
+        static let appLockEnabled = "appLockEnabled"
+        static let viewPresets = "viewPresets"
+        static let otlpEndpoint = "otlpEndpoint"
+        static let notesFolder = "notesFolder"
+        static let writeNotesOnCompletion = "writeNotesOnCompletion"
+        static let issueTracker = "issueTracker"
+        static let jiraBaseURL = "jiraBaseURL"
+        static let jiraEmail = "jiraEmail"
+        static let postIssueCommentsOnCompletion = "postIssueCommentsOnCompletion"
+        static let postPullRequestCommentsOnCompletion = "postPullRequestCommentsOnCompletion"
+
+        static let all = [
+            activeOnly, showSidechains, refreshInterval, refreshInBackground, appearance,
+            codexEnabled, claudeCodeEnabled, launchAtLogin, notificationsEnabled,
+            claudeDirectory, codexDirectory, analyticsEnabled, redactionPatterns,
+            dangerousCommandPatterns, appLockEnabled, viewPresets, otlpEndpoint,
+            notesFolder, writeNotesOnCompletion, issueTracker, jiraBaseURL, jiraEmail,
+            postIssueCommentsOnCompletion, postPullRequestCommentsOnCompletion
+        ]
+    }
+
+    static let didChangeNotification = Notification.Name("AppSettingsDidChange")
+    static let allowedRefreshIntervals: [Double] = [0, 1, 5, 10, 30]
+    static let allowedAppearances = ["system", "light", "dark"]
+
+    var activeOnly = false
+    var showSidechains = false
+    var refreshInterval: Double = 5.0
+    var refreshInBackground = true
+    var appearance = "system"
+    var codexEnabled = true
+    var claudeCodeEnabled = true
+    var launchAtLogin = false
+    var notificationsEnabled = true
+    /// Empty means "use `CLAUDE_CONFIG_DIR`, then `~/.claude`".
+    var claudeDirectory = ""
+    /// Empty means "use `CODEX_HOME`, then `~/.codex`".
+    var codexDirectory = ""
+    /// Opt-in local record of feature use and session outcomes.
+    var analyticsEnabled = false
+    /// Regular expressions masked in addition to `SecretRedactor.builtInPatterns`.
+    var redactionPatterns: [String] = []
+    /// Regular expressions flagged in addition to `DangerousCommandDetector.builtInPatterns`.
+    var dangerousCommandPatterns: [String] = []
+    /// Require Touch ID or the account password before showing sessions.
+    var appLockEnabled = false
+    /// Saved dashboard views, in menu order. Names are unique.
+    var viewPresets: [ViewPreset] = []
+    /// OpenTelemetry collector that "Send to Collector" posts traces to; empty
+    /// until the user sets one.
+    var otlpEndpoint = ""
+    /// Notes vault folder session notes are written to; empty turns notes off.
+    var notesFolder = ""
+    /// Write a note for each session that ends while the app is watching it.
+    var writeNotesOnCompletion = false
+    /// `IssueTracker` raw value; empty when no tracker is connected.
+    var issueTracker = ""
+    /// Jira Cloud site, e.g. `https://acme.atlassian.net`.
+    var jiraBaseURL = ""
+    /// The Atlassian account the Jira API token belongs to.
+    var jiraEmail = ""
+    /// Comment on a session's linked issue when it ends while the app is watching it.
+    var postIssueCommentsOnCompletion = false
+    /// Comment on the pull request a session links to when it ends while the
+    /// app is watching it.
+    var postPullRequestCommentsOnCompletion = false
+
+    static let defaults = AppSettings()
+
+    init() {}
+
+    /// Snaps out-of-range values back to something the UI can display.
+    func validated() -> AppSettings {
+        var copy = self
+        if !Self.allowedRefreshIntervals.contains(copy.refreshInterval) {
+            copy.refreshInterval = Self.allowedRefreshIntervals
+                .min(by: { abs($0 - copy.refreshInterval) < abs($1 - copy.refreshInterval) })
+                ?? Self.defaults.refreshInterval
+        }
+        if !Self.allowedAppearances.contains(copy.appearance) {
+            copy.appearance = Self.defaults.appearance
+        }
+        copy.claudeDirectory = copy.claudeDirectory.trimmingCharacters(in: .whitespacesAndNewlines)
+        copy.codexDirectory = copy.codexDirectory.trimmingCharacters(in: .whitespacesAndNewlines)
+        copy.otlpEndpoint = copy.otlpEndpoint.trimmingCharacters(in: .whitespacesAndNewlines)
+        copy.notesFolder = copy.notesFolder.trimmingCharacters(in: .whitespacesAndNewlines)
+        if !copy.issueTracker.isEmpty && IssueTracker(rawValue: copy.issueTracker) == nil {
+            copy.issueTracker = Self.defaults.issueTracker
+        }
+        copy.jiraBaseURL = copy.jiraBaseURL.trimmingCharacters(in: .whitespacesAndNewlines)
+        copy.jiraEmail = copy.jiraEmail.trimmingCharacters(in: .whitespacesAndNewlines)
+        copy.redactionPatterns = copy.redactionPatterns.filter { !$0.trimmingCharacters(in: .whitespaces).isEmpty }
+        copy.dangerousCommandPatterns = copy.dangerousCommandPatterns.filter { !$0.trimmingCharacters(in: .whitespaces).isEmpty }
+        var seenPresetNames = Set<String>()
+        copy.viewPresets = copy.viewPresets.filter {
+            !$0.name.trimmingCharacters(in: .whitespaces).isEmpty && seenPresetNames.insert($0.name).inserted
+        }
+        return copy
+    }
+
+    // MARK: - Persistence
+
+    static func registerDefaults(in defaults: UserDefaults = .standard) {
+        defaults.register(defaults: Self.defaults.dictionaryRepresentation)
+    }
+
+    static func load(from defaults: UserDefaults = .standard) -> AppSettings {
+        AppSettings(dictionary: defaults.dictionaryRepresentation())
+    }
+
+    /// Builds settings from raw key/value pairs, falling back to defaults for
+    /// missing or mistyped entries.
+    init(dictionary: [String: Any]) {
+        let fallback = AppSettings()
+
+        func bool(_ key: String, _ defaultValue: Bool) -> Bool {
+            dictionary[key] as? Bool ?? defaultValue
+        }
+
+        func string(_ key: String, _ defaultValue: String) -> String {
+            dictionary[key] as? String ?? defaultValue
+        }
+
+        var settings = fallback
+        settings.activeOnly = bool(Key.activeOnly, fallback.activeOnly)
+        settings.showSidechains = bool(Key.showSidechains, fallback.showSidechains)
+        settings.refreshInBackground = bool(Key.refreshInBackground, fallback.refreshInBackground)
+        settings.codexEnabled = bool(Key.codexEnabled, fallback.codexEnabled)
+        settings.claudeCodeEnabled = bool(Key.claudeCodeEnabled, fallback.claudeCodeEnabled)
+        settings.launchAtLogin = bool(Key.launchAtLogin, fallback.launchAtLogin)
+        settings.notificationsEnabled = bool(Key.notificationsEnabled, fallback.notificationsEnabled)
+        settings.analyticsEnabled = bool(Key.analyticsEnabled, fallback.analyticsEnabled)
+        settings.appLockEnabled = bool(Key.appLockEnabled, fallback.appLockEnabled)
+        settings.writeNotesOnCompletion = bool(Key.writeNotesOnCompletion, fallback.writeNotesOnCompletion)
+        settings.postIssueCommentsOnCompletion = bool(Key.postIssueCommentsOnCompletion, fallback.postIssueCommentsOnCompletion)
+        settings.postPullRequestCommentsOnCompletion = bool(Key.postPullRequestCommentsOnCompletion, fallback.postPullRequestCommentsOnCompletion)
+        settings.refreshInterval = dictionary[Key.refreshInterval] as? Double ?? fallback.refreshInterval
+        settings.appearance = string(Key.appearance, fallback.appearance)
+        settings.claudeDirectory = string(Key.claudeDirectory, fallback.claudeDirectory)
+        settings.codexDirectory = string(Key.codexDirectory, fallback.codexDirectory)
+        settings.otlpEndpoint = string(Key.otlpEndpoint, fallback.otlpEndpoint)
+        settings.notesFolder = string(Key.notesFolder, fallback.notesFolder)
+        settings.issueTracker = string(Key.issueTracker, fallback.issueTracker)
+        settings.jiraBaseURL = string(Key.jiraBaseURL, fallback.jiraBaseURL)
+        settings.jiraEmail = string(Key.jiraEmail, fallback.jiraEmail)
+        settings.redactionPatterns = dictionary[Key.redactionPatterns] as? [String] ?? fallback.redactionPatterns
+        settings.dangerousCommandPatterns = dictionary[Key.dangerousCommandPatterns] as? [String] ?? fallback.dangerousCommandPatterns
+        settings.viewPresets = (dictionary[Key.viewPresets] as? [[String: Any]])?.compactMap(ViewPreset.init(dictionary:)) ?? fallback.viewPresets
+        self = settings.validated()
+    }
+
+    func save(to defaults: UserDefaults = .standard) {
+        for (key, value) in validated().dictionaryRepresentation {
+            defaults.set(value, forKey: key)
+        }
+    }
+
+    /// Applies `change` to the stored settings and writes them back.
+    /// `SettingsWatcher` picks up the write and posts `didChangeNotification`.
+    @discardableResult
+    static func update(in defaults: UserDefaults = .standard, _ change: (inout AppSettings) -> Void) -> AppSettings {
+        let current = load(from: defaults)
+        var updated = current
+        change(&updated)
+        updated = updated.validated()
+        guard updated != current else { return current }
+        updated.save(to: defaults)
+        return updated
+    }
+
+    /// Whether moving from `other` to `self` changes which sessions discovery returns.
+    func affectsSessionDiscovery(comparedTo other: AppSettings) -> Bool {
+        activeOnly != other.activeOnly
+            || showSidechains != other.showSidechains
+            || codexEnabled != other.codexEnabled
+            || claudeCodeEnabled != other.claudeCodeEnabled
+            || claudeDirectory != other.claudeDirectory
+            || codexDirectory != other.codexDirectory
+    }
+
+    // MARK: - Data Locations
+
+    /// Where Claude Code keeps its projects: the setting, then
+    /// `CLAUDE_CONFIG_DIR`, then `~/.claude`.
+    func claudeDirectoryURL(
+        environment: [String: String] = ProcessInfo.processInfo.environment,
+        home: String = FileUtilities.realHomeDirectory()
+    ) -> URL {
+        Self.resolveDirectory(claudeDirectory, environmentValue: environment["CLAUDE_CONFIG_DIR"], fallback: ".claude", home: home)
+    }
+
+    /// Where Codex keeps its sessions: the setting, then `CODEX_HOME`, then `~/.codex`.
+    func codexDirectoryURL(
+        environment: [String: String] = ProcessInfo.processInfo.environment,
+        home: String = FileUtilities.realHomeDirectory()
+    ) -> URL {
+        Self.resolveDirectory(codexDirectory, environmentValue: environment["CODEX_HOME"], fallback: ".codex", home: home)
+    }
+
+    var connectedIssueTracker: IssueTracker? {
+        IssueTracker(rawValue: issueTracker)
+    }
+
+    /// The notes folder with `~` expanded; `nil` while notes are off.
+    func notesFolderURL(home: String = FileUtilities.realHomeDirectory()) -> URL? {
+        guard !notesFolder.isEmpty else { return nil }
+        return Self.resolveDirectory(notesFolder, environmentValue: nil, fallback: "", home: home)
+    }
+
+    private static func resolveDirectory(_ configured: String, environmentValue: String?, fallback: String, home: String) -> URL {
+        let homeURL = URL(fileURLWithPath: home)
+        guard let path = [configured, environmentValue ?? ""].first(where: { !$0.isEmpty }) else {
+            return homeURL.appendingPathComponent(fallback)
+        }
+        if path == "~" { return homeURL }
+        if path.hasPrefix("~/") { return homeURL.appendingPathComponent(String(path.dropFirst(2))) }
+        return URL(fileURLWithPath: path)
+    }
+
+    var dictionaryRepresentation: [String: Any] {
+        [
+            Key.activeOnly: activeOnly,
+            Key.showSidechains: showSidechains,
+            Key.refreshInterval: refreshInterval,
+            Key.refreshInBackground: refreshInBackground,
+            Key.appearance: appearance,
+            Key.codexEnabled: codexEnabled,
+            Key.claudeCodeEnabled: claudeCodeEnabled,
+            Key.launchAtLogin: launchAtLogin,
+            Key.notificationsEnabled: notificationsEnabled,
+            Key.claudeDirectory: claudeDirectory,
+            Key.codexDirectory: codexDirectory,
+            Key.analyticsEnabled: analyticsEnabled,
+            Key.redactionPatterns: redactionPatterns,
+            Key.dangerousCommandPatterns: dangerousCommandPatterns,
+            Key.appLockEnabled: appLockEnabled,
+            Key.viewPresets: viewPresets.map(\.dictionaryRepresentation),
+            Key.otlpEndpoint: otlpEndpoint,
+            Key.notesFolder: notesFolder,
+            Key.writeNotesOnCompletion: writeNotesOnCompletion,
+            Key.issueTracker: issueTracker,
+            Key.jiraBaseURL: jiraBaseURL,
+            Key.jiraEmail: jiraEmail,
+            Key.postIssueCommentsOnCompletion: postIssueCommentsOnCompletion,
+            Key.postPullRequestCommentsOnCompletion: postPullRequestCommentsOnCompletion
+        ]
+    }
+}
+
+// MARK: - Import / Export
+
+enum SettingsTransferError: LocalizedError {
+    case invalidFormat
+    case unsupportedVersion(Int)
+
+    var errorDescription: String? {
+        switch self {
+        case .invalidFormat: return "Not an Agents Monitor settings file"
+        case .unsupportedVersion(let version): return "Settings file version \(version) is not supported"
+        }
+    }
+}
+
+extension AppSettings {
+    static let exportVersion = 1
+
+    enum ImportStrategy {
+        /// Every value in the file replaces the local one.
+        case replaceAll
+        /// Values the user already changed from the defaults are kept; the file
+        /// only fills in settings left at their defaults.
+        case keepLocalChanges
+    }
+
+    /// Pass an `anonymizer` to strip the home folder and user name from
+    /// directory settings before sharing the file.
+    func exportData(exportedAt: Date = Date(), anonymizer: PathAnonymizer? = nil) throws -> Data {
+        var exported = validated()
+        if let anonymizer {
+            exported.claudeDirectory = anonymizer.anonymize(exported.claudeDirectory)
+            exported.codexDirectory = anonymizer.anonymize(exported.codexDirectory)
+            exported.notesFolder = anonymizer.anonymize(exported.notesFolder)
+            for i in exported.viewPresets.indices {
+                exported.viewPresets[i].projectPath = exported.viewPresets[i].projectPath.map(anonymizer.anonymize)
+            }
+        }
+        let formatter = ISO8601DateFormatter()
+        let payload: [String: Any] = [
+            "version": Self.exportVersion,
+            "exportedAt": formatter.string(from: exportedAt),
+            "settings": exported.dictionaryRepresentation
+        ]
+        return try JSONSerialization.data(withJSONObject: payload, options: [.prettyPrinted, .sortedKeys])
+    }
+
+    static func importing(_ data: Data, over current: AppSettings, strategy: ImportStrategy) throws -> AppSettings {
+        guard let json = try? JSONSerialization.jsonObject(with: data) as? [String: Any],
+              let imported = json["settings"] as? [String: Any] else {
+            throw SettingsTransferError.invalidFormat
+        }
+        let version = json["version"] as? Int ?? 0
+        guard version == exportVersion else {
+            throw SettingsTransferError.unsupportedVersion(version)
+        }
+
+        let local = current.dictionaryRepresentation
+        let factory = Self.defaults.dictionaryRepresentation
+        var merged = local
+        for key in Key.all {
+            guard let value = imported[key] else { continue }
+            switch strategy {
+            case .replaceAll:
+                merged[key] = value
+            case .keepLocalChanges:
+                let isLocallyChanged = !(local[key] as AnyObject).isEqual(factory[key])
+                if !isLocallyChanged {
+                    merged[key] = value
+                }
+            }
+        }
+        return AppSettings(dictionary: merged)
+    }
+}
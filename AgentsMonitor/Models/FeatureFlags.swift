@@ -0,0 +1,60 @@
+import Foundation
+
+/// Switches for experimental features that ship dark.
+///
+/// A flag is resolved from the environment first (`AGENTS_MONITOR_FEATURE_<NAME>=1`),
+/// then from `defaults write com.agentsmonitor.app feature.<name> -bool YES`,
+/// then from its built-in default, so it can be flipped per user without a rebuild.
+enum FeatureFlag: String, CaseIterable {
+    /// The opt-in local usage insights toggle in Settings.
+    case usageInsights
+
+    var defaultValue: Bool {
+        switch self {
+        case .usageInsights: return false
+        }
+    }
+
+    var defaultsKey: String { "feature.\(rawValue)" }
+
+    var environmentKey: String {
+        let snakeCase = rawValue.reduce(into: "") { result, character in
+            if character.isUppercase { result.append("_") }
+            result.append(character)
+        }
+        return "AGENTS_MONITOR_FEATURE_\(snakeCase.uppercased())"
+    }
+}
+
+struct FeatureFlags {
+    private let environment: [String: String]
+    private let defaults: UserDefaults
+
+    static var current: FeatureFlags { FeatureFlags() }
+
+    init(
+        environment: [String: String] = ProcessInfo.processInfo.environment,
+        defaults: UserDefaults = .standard
+    ) {
+        self.environment = environment
+        self.defaults = defaults
+    }
+
+    func isEnabled(_ flag: FeatureFlag) -> Bool {
+        if let raw = environment[flag.environmentKey], let value = Self.parse(raw) {
+            return value
+        }
+        if let value = defaults.object(forKey: flag.defaultsKey) as? Bool {
+            return value
+        }
+        return flag.defaultValue
+    }
+
+    static func parse(_ raw: String) -> Bool? {
+        switch raw.trimmingCharacters(in: .whitespaces).lowercased() {
+        case "1", "true", "yes", "on": return true
+        case "0", "false", "no", "off": return false
+        default: return nil
+        }
+    }
+}
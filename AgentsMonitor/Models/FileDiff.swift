@@ -0,0 +1,175 @@
+import Foundation
+
+/// A line diff of the change an Edit, MultiEdit or Write tool call made.
+///
+/// Edit inputs only carry the replaced snippet, not the whole file, so hunk
+/// line numbers count from the start of the snippet rather than the file.
+struct FileDiff: Hashable {
+    enum Line: Hashable {
+        case context(String)
+        case removed(String)
+        case added(String)
+    }
+
+    struct Hunk: Hashable {
+        let oldStart: Int
+        let oldCount: Int
+        let newStart: Int
+        let newCount: Int
+        let lines: [Line]
+    }
+
+    let filePath: String
+    /// Write replaces the whole file, so there is no "before" to compare against.
+    let isNewFile: Bool
+    let hunks: [Hunk]
+
+    static let editTools: Set<String> = ["Edit", "MultiEdit", "Write"]
+
+    var additions: Int {
+        hunks.reduce(0) { $0 + $1.lines.filter { if case .added = $0 { return true } else { return false } }.count }
+    }
+
+    var deletions: Int {
+        hunks.reduce(0) { $0 + $1.lines.filter { if case .removed = $0 { return true } else { return false } }.count }
+    }
+
+    /// The diff in `diff -u` form. Relative paths get git's `a/` and `b/` prefixes.
+    var unified: String {
+        let isAbsolute = filePath.hasPrefix("/")
+        let oldName = isNewFile ? "/dev/null" : (isAbsolute ? filePath : "a/\(filePath)")
+        let newName = isAbsolute ? filePath : "b/\(filePath)"
+        var output = ["--- \(oldName)", "+++ \(newName)"]
+        for hunk in hunks {
+            output.append("@@ -\(hunk.oldStart),\(hunk.oldCount) +\(hunk.newStart),\(hunk.newCount) @@")
+            for line in hunk.lines {
+                switch line {
+                case .context(let text): output.append(" " + text)
+                case .removed(let text): output.append("-" + text)
+                case .added(let text): output.append("+" + text)
+                }
+            }
+        }
+        return output.joined(separator: "\n")
+    }
+
+    // MARK: - Building
+
+    /// Builds the diff from a tool call's JSON input; `nil` for tools that
+    /// don't change files or inputs missing the fields they need.
+    init?(toolName: String, input: String) {
+        guard Self.editTools.contains(toolName),
+              let data = input.data(using: .utf8),
+              let json = try? JSONSerialization.jsonObject(with: data) as? [String: Any] else {
+            return nil
+        }
+        self.init(toolName: toolName, input: json)
+    }
+
+    init?(toolName: String, input: [String: Any]) {
+        guard let path = input["file_path"] as? String else { return nil }
+        switch toolName {
+        case "Edit":
+            guard let old = input["old_string"] as? String, let new = input["new_string"] as? String else { return nil }
+            self.init(filePath: path, old: old, new: new)
+        case "MultiEdit":
+            let edits = (input["edits"] as? [[String: Any]] ?? []).compactMap { edit -> (String, String)? in
+                guard let old = edit["old_string"] as? String, let new = edit["new_string"] as? String else { return nil }
+                return (old, new)
+            }
+            guard !edits.isEmpty else { return nil }
+            // Each edit touches its own snippet; number them as if stacked.
+            var hunks: [Hunk] = []
+            var oldOffset = 0
+            var newOffset = 0
+            for (old, new) in edits {
+                let diff = FileDiff(filePath: path, old: old, new: new)
+                hunks += diff.hunks.map {
+                    Hunk(oldStart: $0.oldStart + oldOffset, oldCount: $0.oldCount,
+                         newStart: $0.newStart + newOffset, newCount: $0.newCount, lines: $0.lines)
+                }
+                oldOffset += Self.lines(of: old).count
+                newOffset += Self.lines(of: new).count
+            }
+            self.init(filePath: path, isNewFile: false, hunks: hunks)
+        case "Write":
+            guard let content = input["content"] as? String else { return nil }
+            let lines = Self.lines(of: content)
+            let hunk = Hunk(oldStart: 0, oldCount: 0, newStart: lines.isEmpty ? 0 : 1, newCount: lines.count, lines: lines.map(Line.added))
+            self.init(filePath: path, isNewFile: true, hunks: [hunk])
+        default:
+            return nil
+        }
+    }
+
+    init(filePath: String, isNewFile: Bool, hunks: [Hunk]) {
+        self.filePath = filePath
+        self.isNewFile = isNewFile
+        self.hunks = hunks
+    }
+
+    /// Diffs two texts line by line, keeping `context` unchanged lines around each change.
+    init(filePath: String, old: String, new: String, context: Int = 3) {
+        let oldLines = Self.lines(of: old)
+        let newLines = Self.lines(of: new)
+        let difference = newLines.difference(from: oldLines)
+        var removed = Set<Int>()
+        var inserted = Set<Int>()
+        for change in difference {
+            switch change {
+            case .remove(let offset, _, _): removed.insert(offset)
+            case .insert(let offset, _, _): inserted.insert(offset)
+            }
+        }
+
+        // Walk both sides in step: (line, old line number, new line number).
+        var script: [(line: Line, old: Int, new: Int)] = []
+        var i = 0
+        var j = 0
+        while i < oldLines.count || j < newLines.count {
+            if i < oldLines.count, removed.contains(i) {
+                script.append((.removed(oldLines[i]), i, j))
+                i += 1
+            } else if j < newLines.count, inserted.contains(j) {
+                script.append((.added(newLines[j]), i, j))
+                j += 1
+            } else {
+                script.append((.context(oldLines[i]), i, j))
+                i += 1
+                j += 1
+            }
+        }
+
+        var hunks: [Hunk] = []
+        let changed = script.indices.filter { if case .context = script[$0].line { return false } else { return true } }
+        var start = 0
+        while start < changed.count {
+            var end = start
+            while end + 1 < changed.count, changed[end + 1] - changed[end] <= context * 2 + 1 {
+                end += 1
+            }
+            let lower = max(changed[start] - context, 0)
+            let upper = min(changed[end] + context, script.count - 1)
+            let slice = script[lower...upper]
+            let lines = slice.map(\.line)
+            let oldCount = lines.filter { if case .added = $0 { return false } else { return true } }.count
+            let newCount = lines.filter { if case .removed = $0 { return false } else { return true } }.count
+            hunks.append(Hunk(
+                oldStart: oldCount == 0 ? slice.first!.old : slice.first!.old + 1,
+                oldCount: oldCount,
+                newStart: newCount == 0 ? slice.first!.new : slice.first!.new + 1,
+                newCount: newCount,
+                lines: lines
+            ))
+            start = end + 1
+        }
+        self.init(filePath: filePath, isNewFile: false, hunks: hunks)
+    }
+
+    private static func lines(of text: String) -> [String] {
+        guard !text.isEmpty else { return [] }
+        var lines = text.components(separatedBy: "\n")
+        if lines.last == "" { lines.removeLast() }
+        return lines
+    }
+}
@@ -7,6 +7,15 @@ struct Message: Identifiable, Hashable {
     let timestamp: Date
     var isStreaming: Bool
     var toolUseId: UUID?
+    /// The message this one replies to; set when the transcript records it, as
+    /// Claude Code does, so retries and branches can be told apart.
+    var parentId: UUID?
+    var attachments: [MessageAttachment]
+    /// Fenced code blocks from assistant replies, extracted once when the
+    /// transcript is read.
+    var codeBlocks: [CodeBlock]
+    /// `TokenEstimator` count for `content`.
+    var estimatedTokens: Int
 
     init(
         id: UUID = UUID(),
@@ -14,7 +23,11 @@ struct Message: Identifiable, Hashable {
         content: String,
         timestamp: Date = Date(),
         isStreaming: Bool = false,
-        toolUseId: UUID? = nil
+        toolUseId: UUID? = nil,
+        parentId: UUID? = nil,
+        attachments: [MessageAttachment] = [],
+        codeBlocks: [CodeBlock] = [],
+        estimatedTokens: Int? = nil
     ) {
         self.id = id
         self.role = role
@@ -22,6 +35,10 @@ struct Message: Identifiable, Hashable {
         self.timestamp = timestamp
         self.isStreaming = isStreaming
         self.toolUseId = toolUseId
+        self.parentId = parentId
+        self.attachments = attachments
+        self.codeBlocks = codeBlocks
+        self.estimatedTokens = estimatedTokens ?? TokenEstimator.estimate(content)
     }
 
     var formattedTime: String {
@@ -39,6 +56,142 @@ struct Message: Identifiable, Hashable {
     }
 }
 
+/// An image pasted into a prompt, or a file a tool call read or changed.
+struct MessageAttachment: Identifiable, Hashable {
+    enum Kind: String {
+        case image
+        case file
+    }
+
+    let id: UUID
+    let kind: Kind
+    /// MIME type for inline images, e.g. `image/png`.
+    let mediaType: String?
+    /// Inline bytes for images embedded in the transcript.
+    let data: Data?
+    /// Path on disk for file references.
+    let filePath: String?
+
+    init(id: UUID = UUID(), kind: Kind, mediaType: String? = nil, data: Data? = nil, filePath: String? = nil) {
+        self.id = id
+        self.kind = kind
+        self.mediaType = mediaType
+        self.data = data
+        self.filePath = filePath
+    }
+
+    var displayName: String {
+        if let filePath { return URL(fileURLWithPath: filePath).lastPathComponent }
+        return "Image (\(fileExtension.uppercased()))"
+    }
+
+    var fileExtension: String {
+        if let filePath { return URL(fileURLWithPath: filePath).pathExtension }
+        switch mediaType {
+        case "image/jpeg": return "jpg"
+        case "image/gif": return "gif"
+        case "image/webp": return "webp"
+        default: return "png"
+        }
+    }
+}
+
+/// A fenced code block from a message, with the file it targets when the
+/// fence or its first line names one (```swift Sources/App.swift,
+/// ```swift:Sources/App.swift, or a leading `// path/to/file` comment).
+struct CodeBlock: Hashable {
+    let language: String?
+    var content: String
+    let filePath: String?
+
+    init(language: String? = nil, content: String, filePath: String? = nil) {
+        self.language = language
+        self.content = content
+        self.filePath = filePath
+    }
+
+    static func extract(from markdown: String) -> [CodeBlock] {
+        var blocks: [CodeBlock] = []
+        var open: (fence: String, info: Substring)?
+        var lines: [Substring] = []
+
+        for line in markdown.split(separator: "\n", omittingEmptySubsequences: false) {
+            let trimmed = line.drop(while: { $0 == " " })
+            if let current = open {
+                if trimmed.hasPrefix(current.fence),
+                   trimmed.drop(while: { $0 == current.fence.first }).allSatisfy(\.isWhitespace) {
+                    blocks.append(block(info: current.info, lines: lines))
+                    open = nil
+                    lines = []
+                } else {
+                    lines.append(line)
+                }
+            } else if let fence = fence(of: trimmed) {
+                open = (fence, trimmed.dropFirst(fence.count))
+            }
+        }
+        // A reply cut off mid-block still yields what was written.
+        if let current = open, !lines.isEmpty {
+            blocks.append(block(info: current.info, lines: lines))
+        }
+        return blocks
+    }
+
+    private static func fence(of line: Substring) -> String? {
+        guard let marker = line.first, marker == "`" || marker == "~" else { return nil }
+        let run = line.prefix(while: { $0 == marker })
+        guard run.count >= 3 else { return nil }
+        // Backtick fences can't have backticks in their info string.
+        if marker == "`" && line.dropFirst(run.count).contains("`") { return nil }
+        return String(run)
+    }
+
+    private static func block(info: Substring, lines: [Substring]) -> CodeBlock {
+        let words = info.split(whereSeparator: \.isWhitespace)
+        var language = words.first.map(String.init)
+        var path: String?
+
+        if let first = language, let colon = first.firstIndex(of: ":") {
+            language = String(first[..<colon])
+            path = String(first[first.index(after: colon)...])
+        }
+        for word in words.dropFirst() where path == nil {
+            var value = word
+            for key in ["title=", "file=", "filename=", "path="] where value.hasPrefix(key) {
+                value = value.dropFirst(key.count)
+            }
+            value = value.trimmingCharacters(in: CharacterSet(charactersIn: "\"'"))[...]
+            if value.contains("/") || value.contains(".") { path = String(value) }
+        }
+        if path == nil, let first = lines.first {
+            path = commentedPath(first)
+        }
+
+        return CodeBlock(
+            language: language?.isEmpty == false ? language : nil,
+            content: lines.joined(separator: "\n"),
+            filePath: path?.isEmpty == false ? path : nil
+        )
+    }
+
+    private static let pathComment = try! NSRegularExpression(
+        pattern: #"^\s*(?://|#|--|<!--)\s*(file(?:name)?:\s*)?(\S+\.\w+)\s*(?:-->)?\s*$"#,
+        options: .caseInsensitive
+    )
+
+    /// A bare `# setup.py` is as likely a heading as a file name, so the
+    /// comment needs a `file:` label or a path with a directory in it.
+    private static func commentedPath(_ line: Substring) -> String? {
+        let text = String(line)
+        let range = NSRange(text.startIndex..., in: text)
+        guard let match = pathComment.firstMatch(in: text, range: range),
+              let pathRange = Range(match.range(at: 2), in: text) else { return nil }
+        let path = String(text[pathRange])
+        let isLabeled = match.range(at: 1).location != NSNotFound
+        return isLabeled || path.contains("/") ? path : nil
+    }
+}
+
 enum MessageRole: String, CaseIterable, Codable {
     case user = "User"
     case assistant = "Assistant"
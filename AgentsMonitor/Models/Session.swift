@@ -26,7 +26,22 @@ struct Session: Identifiable, Hashable {
     var firstPrompt: String?
     var sessionSummary: String?
     var isSidechain: Bool = false
+    /// The session whose Task call spawned this one, for sub-agent transcripts.
+    var parentSessionId: UUID?
     var fileMtime: Int64 = 0
+    var flaggedCommands: [FlaggedCommand] = []
+    var pendingPermission: PermissionRequest?
+    /// What the agent may do without asking; only read for running sessions.
+    var operatingMode: OperatingMode?
+    /// The user's own marker, kept by the app across refreshes.
+    var label: SessionLabel?
+    /// The tracker issue the session works on: linked by the user, or found
+    /// in its branch name or first prompt.
+    var issueKey: String?
+    /// MCP servers the agent's config made available in this project.
+    var configuredMCPServers: [String] = []
+    /// MCP tools the session called, by qualified name (`mcp__github__create_issue`).
+    var mcpToolsUsed: [String] = []
 
     init(
         id: UUID = UUID(),
@@ -51,7 +66,10 @@ struct Session: Identifiable, Hashable {
         firstPrompt: String? = nil,
         sessionSummary: String? = nil,
         isSidechain: Bool = false,
-        fileMtime: Int64 = 0
+        parentSessionId: UUID? = nil,
+        fileMtime: Int64 = 0,
+        flaggedCommands: [FlaggedCommand] = [],
+        pendingPermission: PermissionRequest? = nil
     ) {
         self.id = id
         self.name = name
@@ -75,7 +93,10 @@ struct Session: Identifiable, Hashable {
         self.firstPrompt = firstPrompt
         self.sessionSummary = sessionSummary
         self.isSidechain = isSidechain
+        self.parentSessionId = parentSessionId
         self.fileMtime = fileMtime
+        self.flaggedCommands = flaggedCommands
+        self.pendingPermission = pendingPermission
     }
 
     func duration(asOf date: Date) -> TimeInterval {
@@ -112,6 +133,26 @@ struct Session: Identifiable, Hashable {
         return "\(formatted) ago"
     }
 
+    /// Where a running agent has moved to, relative to its project folder;
+    /// `nil` while it is still at the top of the project.
+    var currentSubdirectory: String? {
+        guard let current = workingDirectory?.standardizedFileURL.path,
+              let project = projectPath.map({ URL(fileURLWithPath: $0).standardizedFileURL.path }),
+              current != project else {
+            return nil
+        }
+        if current.hasPrefix(project + "/") {
+            return String(current.dropFirst(project.count + 1))
+        }
+        return (current as NSString).abbreviatingWithTildeInPath
+    }
+
+    /// `path` relative to the project folder when it is inside it.
+    func relativePath(_ path: String) -> String {
+        guard let projectPath, path.hasPrefix(projectPath + "/") else { return path }
+        return String(path.dropFirst(projectPath.count + 1))
+    }
+
     var shortProjectName: String? {
         guard let path = projectPath else { return nil }
         let components = path.split(separator: "/")
@@ -121,6 +162,36 @@ struct Session: Identifiable, Hashable {
         return components.last.map(String.init)
     }
 
+    /// Branches too common to tell sessions apart.
+    static let defaultBranches: Set<String> = ["main", "master", "develop", "trunk", "HEAD"]
+
+    /// A name for a session the agent didn't title: the first line of the
+    /// first prompt, else a non-default git branch, else the project folder,
+    /// else `fallback`.
+    static func derivedName(firstPrompt: String?, gitBranch: String?, projectPath: String?, fallback: String) -> String {
+        let firstLine = firstPrompt?
+            .split(whereSeparator: \.isNewline)
+            .lazy
+            .map { $0.split(whereSeparator: \.isWhitespace).joined(separator: " ") }
+            .first { !$0.isEmpty }
+        // Slash commands and injected context start with a tag; the index writes "No prompt".
+        if let firstLine, !firstLine.hasPrefix("<"), firstLine != "No prompt" {
+            return String(firstLine.prefix(80))
+        }
+        if let gitBranch, !gitBranch.isEmpty, !defaultBranches.contains(gitBranch) {
+            return gitBranch
+        }
+        if let folder = projectPath.map({ URL(fileURLWithPath: $0).lastPathComponent }), !folder.isEmpty, folder != "/" {
+            return folder
+        }
+        return fallback
+    }
+
+    /// Servers behind `mcpToolsUsed`, in order of first use.
+    var usedMCPServers: [String] {
+        MCPServers.usedServers(toolNames: mcpToolsUsed, agentType: agentType)
+    }
+
     var duration: TimeInterval {
         duration(asOf: Date())
     }
@@ -138,6 +209,44 @@ struct Session: Identifiable, Hashable {
     }
 }
 
+/// A color and optional emoji the user puts on a session to group it by
+/// meaning ("client work", "experiments"), separate from status colors.
+struct SessionLabel: Hashable {
+    enum Color: String, CaseIterable {
+        case red, orange, yellow, green, blue, purple, gray
+    }
+
+    static let defaultsKey = "sessionLabels"
+    static let suggestedEmoji = ["⭐️", "🔥", "💼", "🧪", "🐛", "📌"]
+
+    var color: Color?
+    var emoji: String?
+
+    var isEmpty: Bool {
+        color == nil && (emoji ?? "").isEmpty
+    }
+
+    /// Labels by session id, from `UserDefaults`. Malformed entries are skipped.
+    static func loadAll(from defaults: UserDefaults = .standard) -> [UUID: SessionLabel] {
+        let stored = defaults.dictionary(forKey: defaultsKey) as? [String: [String: String]] ?? [:]
+        return stored.reduce(into: [:]) { labels, entry in
+            guard let id = UUID(uuidString: entry.key) else { return }
+            let label = SessionLabel(color: entry.value["color"].flatMap(Color.init(rawValue:)), emoji: entry.value["emoji"])
+            if !label.isEmpty { labels[id] = label }
+        }
+    }
+
+    static func saveAll(_ labels: [UUID: SessionLabel], to defaults: UserDefaults = .standard) {
+        let stored = labels.filter { !$0.value.isEmpty }.reduce(into: [String: [String: String]]()) { stored, entry in
+            var fields: [String: String] = [:]
+            fields["color"] = entry.value.color?.rawValue
+            fields["emoji"] = entry.value.emoji
+            stored[entry.key.uuidString] = fields
+        }
+        defaults.set(stored, forKey: defaultsKey)
+    }
+}
+
 enum SessionStatus: String, CaseIterable, Codable {
     case running = "Running"
     case paused = "Paused"
@@ -262,6 +371,14 @@ struct SessionMetrics: Hashable, Codable {
     var contextWindowMax: Int
     var cost: Double
     var modelName: String
+    /// The token counts are `TokenEstimator` guesses; the agent reported no usage.
+    var isEstimated: Bool
+    /// Raw ids of every model the session used, most used first; `modelName`
+    /// is the display name of the first.
+    var modelIds: [String]
+    /// Tokens in the context at the latest API call; 0 when unknown, in which
+    /// case context figures fall back to `totalTokens`.
+    var contextTokens: Int
 
     init(
         totalTokens: Int = 0,
@@ -274,7 +391,10 @@ struct SessionMetrics: Hashable, Codable {
         cacheWriteTokens: Int = 0,
         contextWindowMax: Int = defaultContextWindowMax,
         cost: Double = 0.0,
-        modelName: String = ""
+        modelName: String = "",
+        isEstimated: Bool = false,
+        modelIds: [String] = [],
+        contextTokens: Int = 0
     ) {
         self.totalTokens = totalTokens
         self.inputTokens = inputTokens
@@ -287,6 +407,9 @@ struct SessionMetrics: Hashable, Codable {
         self.contextWindowMax = contextWindowMax
         self.cost = cost
         self.modelName = modelName
+        self.isEstimated = isEstimated
+        self.modelIds = modelIds
+        self.contextTokens = contextTokens
     }
 
     init(from decoder: Decoder) throws {
@@ -303,18 +426,34 @@ struct SessionMetrics: Hashable, Codable {
         contextWindowMax = (try? container.decodeIfPresent(Int.self, forKey: .contextWindowMax)) ?? Self.defaultContextWindowMax
         cost = (try? container.decodeIfPresent(Double.self, forKey: .cost)) ?? 0.0
         modelName = (try? container.decodeIfPresent(String.self, forKey: .modelName)) ?? ""
+        isEstimated = (try? container.decodeIfPresent(Bool.self, forKey: .isEstimated)) ?? false
+        modelIds = (try? container.decodeIfPresent([String].self, forKey: .modelIds)) ?? []
+        contextTokens = (try? container.decodeIfPresent(Int.self, forKey: .contextTokens)) ?? 0
     }
 
     enum CodingKeys: String, CodingKey {
         case totalTokens, inputTokens, outputTokens
         case toolCallCount, errorCount, apiCalls
         case cacheReadTokens, cacheWriteTokens
-        case contextWindowMax, cost, modelName
+        case contextWindowMax, cost, modelName, isEstimated, modelIds, contextTokens
+    }
+
+    /// Share of the window where agents start dropping or compacting earlier turns.
+    static let contextWarningThreshold = 0.8
+
+    private var contextFill: Int {
+        contextTokens > 0 ? contextTokens : totalTokens
     }
 
     var contextWindowUsage: Double {
         guard contextWindowMax > 0 else { return 0 }
-        return min(max(Double(totalTokens) / Double(contextWindowMax), 0), 1.0)
+        return min(max(Double(contextFill) / Double(contextWindowMax), 0), 1.0)
+    }
+
+    /// Only meaningful with a measured `contextTokens`; lifetime totals pass
+    /// the window long before the context does.
+    var isNearContextLimit: Bool {
+        contextTokens > 0 && contextWindowUsage >= Self.contextWarningThreshold
     }
 
     var formattedWindowMax: String {
@@ -322,7 +461,7 @@ struct SessionMetrics: Hashable, Codable {
     }
 
     var formattedRemaining: String {
-        let remaining = max(contextWindowMax - totalTokens, 0)
+        let remaining = max(contextWindowMax - contextFill, 0)
         if remaining >= 1_000 {
             return String(format: "%.1fK", Double(remaining) / 1_000)
         }
@@ -330,11 +469,14 @@ struct SessionMetrics: Hashable, Codable {
     }
 
     var formattedContextWindow: String {
-        "\(formattedTokens) / \(formattedWindowMax)"
+        "\(Self.compactCount(contextFill)) / \(formattedWindowMax)"
     }
 
     var formattedTokens: String {
-        let total = totalTokens
+        Self.compactCount(totalTokens)
+    }
+
+    private static func compactCount(_ total: Int) -> String {
         if total >= 1_000_000 {
             return String(format: "%.1fM", Double(total) / 1_000_000)
         } else if total >= 1_000 {
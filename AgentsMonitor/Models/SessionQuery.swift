@@ -0,0 +1,343 @@
+import Foundation
+
+enum SessionQueryError: LocalizedError, Equatable {
+    case unexpectedEnd
+    case unexpectedToken(String)
+    case unknownField(String)
+    case unsupportedOperator(String, field: String)
+    case invalidValue(String, field: String)
+
+    var errorDescription: String? {
+        switch self {
+        case .unexpectedEnd: return "The filter ends too early"
+        case .unexpectedToken(let token): return "Unexpected \u{201C}\(token)\u{201D}"
+        case .unknownField(let field): return "Unknown field \u{201C}\(field)\u{201D}"
+        case .unsupportedOperator(let op, let field): return "\(field) can't be compared with \(op)"
+        case .invalidValue(let value, let field): return "\u{201C}\(value)\u{201D} isn't a valid \(field)"
+        }
+    }
+}
+
+/// A filter expression over sessions, e.g.
+/// `status=failed AND agent=codex AND tokens>100k AND started>7d`.
+///
+/// Comparisons are `field op value` with `=`, `!=`, `>`, `>=`, `<`, `<=`
+/// and `~` (contains), combined with `AND` (or just a space), `OR`, `NOT`
+/// and parentheses. Numbers take `k`/`m` suffixes and durations `s`/`m`/`h`/`d`.
+/// Dates are either ISO days or ages: `started>7d` is "started in the last
+/// seven days". Text comparisons ignore case; quote values with spaces.
+struct SessionQuery: Equatable {
+    enum Field: String, CaseIterable {
+        case status, agent, name, project, branch, prompt, issue, label, model
+        case tokens, cost, duration
+        case started, ended
+
+        fileprivate enum Kind { case text, number, date }
+
+        fileprivate var kind: Kind {
+            switch self {
+            case .tokens, .cost, .duration: return .number
+            case .started, .ended: return .date
+            default: return .text
+            }
+        }
+    }
+
+    enum Operator: String, CaseIterable {
+        case equal = "="
+        case notEqual = "!="
+        case greater = ">"
+        case greaterOrEqual = ">="
+        case less = "<"
+        case lessOrEqual = "<="
+        case contains = "~"
+    }
+
+    indirect enum Expression: Equatable {
+        case comparison(Field, Operator, String)
+        case and(Expression, Expression)
+        case or(Expression, Expression)
+        case not(Expression)
+    }
+
+    let expression: Expression
+
+    init(_ text: String) throws {
+        var parser = Parser(tokens: try Self.tokenize(text))
+        expression = try parser.parseExpression()
+        if let extra = parser.next() {
+            throw SessionQueryError.unexpectedToken(extra.text)
+        }
+        try Self.validate(expression)
+    }
+
+    func matches(_ session: Session, now: Date = Date()) -> Bool {
+        Self.evaluate(expression, session, now: now)
+    }
+
+    func filter(_ sessions: [Session], now: Date = Date()) -> [Session] {
+        sessions.filter { matches($0, now: now) }
+    }
+
+    // MARK: - Evaluation
+
+    private static func evaluate(_ expression: Expression, _ session: Session, now: Date) -> Bool {
+        switch expression {
+        case .and(let left, let right): return evaluate(left, session, now: now) && evaluate(right, session, now: now)
+        case .or(let left, let right): return evaluate(left, session, now: now) || evaluate(right, session, now: now)
+        case .not(let inner): return !evaluate(inner, session, now: now)
+        case .comparison(let field, let op, let value): return compare(field, op, value, session, now: now)
+        }
+    }
+
+    private static func compare(_ field: Field, _ op: Operator, _ value: String, _ session: Session, now: Date) -> Bool {
+        switch field.kind {
+        case .text:
+            let candidates = textValues(of: field, in: session).map { $0.lowercased() }
+            let value = value.lowercased()
+            switch op {
+            case .equal: return candidates.contains(value)
+            case .notEqual: return !candidates.contains(value)
+            case .contains: return candidates.contains { $0.contains(value) }
+            default: return false
+            }
+        case .number:
+            guard let target = number(value, field: field) else { return false }
+            let actual: Double
+            switch field {
+            case .tokens: actual = Double(session.metrics.totalTokens)
+            case .cost: actual = session.metrics.cost
+            default: actual = session.duration(asOf: now)
+            }
+            return compare(actual, op, target)
+        case .date:
+            guard let date = field == .started ? session.startedAt : session.endedAt else { return op == .notEqual }
+            if let age = age(value) {
+                return compare(date.timeIntervalSince1970, op, now.addingTimeInterval(-age).timeIntervalSince1970)
+            }
+            guard let day = day(value), let end = Calendar.current.date(byAdding: .day, value: 1, to: day) else { return false }
+            switch op {
+            case .equal: return date >= day && date < end
+            case .notEqual: return date < day || date >= end
+            case .greater: return date >= end
+            case .greaterOrEqual: return date >= day
+            case .less: return date < day
+            case .lessOrEqual: return date < end
+            case .contains: return false
+            }
+        }
+    }
+
+    private static func compare(_ actual: Double, _ op: Operator, _ target: Double) -> Bool {
+        switch op {
+        case .equal: return actual == target
+        case .notEqual: return actual != target
+        case .greater: return actual > target
+        case .greaterOrEqual: return actual >= target
+        case .less: return actual < target
+        case .lessOrEqual: return actual <= target
+        case .contains: return false
+        }
+    }
+
+    private static func textValues(of field: Field, in session: Session) -> [String] {
+        switch field {
+        case .status: return [session.status.rawValue]
+        case .agent: return [session.agentType.rawValue, session.agentType == .codex ? "codex" : "claude"]
+        case .name: return [session.name]
+        case .project:
+            guard let path = session.projectPath else { return [] }
+            return [path, URL(fileURLWithPath: path).lastPathComponent]
+        case .branch: return session.gitBranch.map { [$0] } ?? []
+        case .prompt: return session.firstPrompt.map { [$0] } ?? []
+        case .issue: return session.issueKey.map { [$0] } ?? []
+        case .label: return [session.label?.color?.rawValue, session.label?.emoji].compactMap { $0 }
+        case .model: return session.metrics.modelIds + (session.metrics.modelName.isEmpty ? [] : [session.metrics.modelName])
+        default: return []
+        }
+    }
+
+    // MARK: - Values
+
+    private static func validate(_ expression: Expression) throws {
+        switch expression {
+        case .and(let left, let right), .or(let left, let right):
+            try validate(left)
+            try validate(right)
+        case .not(let inner):
+            try validate(inner)
+        case .comparison(let field, let op, let value):
+            let supported: Bool
+            let valid: Bool
+            switch field.kind {
+            case .text:
+                supported = [.equal, .notEqual, .contains].contains(op)
+                valid = true
+            case .number:
+                supported = op != .contains
+                valid = number(value, field: field) != nil
+            case .date:
+                // An age is a moving point in time, so only ordering makes sense.
+                supported = op != .contains && (age(value) == nil || ![.equal, .notEqual].contains(op))
+                valid = age(value) != nil || day(value) != nil
+            }
+            guard supported else { throw SessionQueryError.unsupportedOperator(op.rawValue, field: field.rawValue) }
+            guard valid else { throw SessionQueryError.invalidValue(value, field: field.rawValue) }
+        }
+    }
+
+    /// `100k`, `1.5m`, `$2` for cost, `90s`, `30m`, `2h` for duration.
+    private static func number(_ text: String, field: Field) -> Double? {
+        var text = text.lowercased().replacingOccurrences(of: ",", with: "").replacingOccurrences(of: "_", with: "")
+        if field == .cost, text.hasPrefix("$") { text.removeFirst() }
+        let multipliers: [Character: Double] = field == .duration
+            ? ["s": 1, "m": 60, "h": 3600, "d": 86_400]
+            : ["k": 1_000, "m": 1_000_000]
+        var multiplier = 1.0
+        if let last = text.last, let scale = multipliers[last] {
+            multiplier = scale
+            text.removeLast()
+        }
+        return Double(text).map { $0 * multiplier }
+    }
+
+    /// `30m`, `12h`, `7d`, `2w`: how far back from now.
+    private static func age(_ text: String) -> TimeInterval? {
+        let units: [Character: TimeInterval] = ["m": 60, "h": 3600, "d": 86_400, "w": 604_800]
+        guard let unit = text.lowercased().last, let scale = units[unit],
+              let amount = Double(text.dropLast()), amount >= 0 else { return nil }
+        return amount * scale
+    }
+
+    private static let dayFormatter: DateFormatter = {
+        let formatter = DateFormatter()
+        formatter.locale = Locale(identifier: "en_US_POSIX")
+        formatter.dateFormat = "yyyy-MM-dd"
+        return formatter
+    }()
+
+    private static func day(_ text: String) -> Date? {
+        dayFormatter.date(from: text)
+    }
+
+    // MARK: - Parsing
+
+    private enum Token: Equatable {
+        case open
+        case close
+        case op(Operator)
+        case word(String)
+        case quoted(String)
+
+        var text: String {
+            switch self {
+            case .open: return "("
+            case .close: return ")"
+            case .op(let op): return op.rawValue
+            case .word(let word), .quoted(let word): return word
+            }
+        }
+
+        func isKeyword(_ keyword: String) -> Bool {
+            if case .word(let word) = self { return word.uppercased() == keyword }
+            return false
+        }
+    }
+
+    private static func tokenize(_ text: String) throws -> [Token] {
+        var tokens: [Token] = []
+        var index = text.startIndex
+        let operatorStarts = Set("=!<>~")
+        while index < text.endIndex {
+            let character = text[index]
+            if character.isWhitespace {
+                index = text.index(after: index)
+            } else if character == "(" || character == ")" {
+                tokens.append(character == "(" ? .open : .close)
+                index = text.index(after: index)
+            } else if character == "\"" || character == "'" {
+                let start = text.index(after: index)
+                guard let end = text[start...].firstIndex(of: character) else { throw SessionQueryError.unexpectedEnd }
+                tokens.append(.quoted(String(text[start..<end])))
+                index = text.index(after: end)
+            } else if operatorStarts.contains(character) {
+                let next = text.index(after: index)
+                if next < text.endIndex, let op = Operator(rawValue: String(text[index...next])) {
+                    tokens.append(.op(op))
+                    index = text.index(after: next)
+                } else if let op = Operator(rawValue: String(character)) {
+                    tokens.append(.op(op))
+                    index = next
+                } else {
+                    throw SessionQueryError.unexpectedToken(String(character))
+                }
+            } else {
+                let end = text[index...].firstIndex { $0.isWhitespace || $0 == "(" || $0 == ")" || operatorStarts.contains($0) } ?? text.endIndex
+                tokens.append(.word(String(text[index..<end])))
+                index = end
+            }
+        }
+        return tokens
+    }
+
+    /// `OR` binds loosest, then `AND` (explicit or implied by a space), then `NOT`.
+    private struct Parser {
+        let tokens: [Token]
+        var index = 0
+
+        init(tokens: [Token]) {
+            self.tokens = tokens
+        }
+
+        var peek: Token? { index < tokens.count ? tokens[index] : nil }
+
+        mutating func next() -> Token? {
+            defer { index += 1 }
+            return peek
+        }
+
+        mutating func parseExpression() throws -> Expression {
+            var left = try parseTerm()
+            while peek?.isKeyword("OR") == true {
+                index += 1
+                left = .or(left, try parseTerm())
+            }
+            return left
+        }
+
+        mutating func parseTerm() throws -> Expression {
+            var left = try parseFactor()
+            while let token = peek, token != .close, !token.isKeyword("OR") {
+                if token.isKeyword("AND") { index += 1 }
+                left = .and(left, try parseFactor())
+            }
+            return left
+        }
+
+        mutating func parseFactor() throws -> Expression {
+            guard let token = next() else { throw SessionQueryError.unexpectedEnd }
+            switch token {
+            case .word where token.isKeyword("NOT"):
+                return .not(try parseFactor())
+            case .open:
+                let inner = try parseExpression()
+                guard let close = next() else { throw SessionQueryError.unexpectedEnd }
+                guard close == .close else { throw SessionQueryError.unexpectedToken(close.text) }
+                return inner
+            case .word(let name):
+                guard let field = Field(rawValue: name.lowercased()) else { throw SessionQueryError.unknownField(name) }
+                guard let opToken = next() else { throw SessionQueryError.unexpectedEnd }
+                guard case .op(let op) = opToken else { throw SessionQueryError.unexpectedToken(opToken.text) }
+                guard let valueToken = next() else { throw SessionQueryError.unexpectedEnd }
+                switch valueToken {
+                case .word(let value), .quoted(let value):
+                    return .comparison(field, op, value)
+                default:
+                    throw SessionQueryError.unexpectedToken(valueToken.text)
+                }
+            default:
+                throw SessionQueryError.unexpectedToken(token.text)
+            }
+        }
+    }
+}
@@ -3,12 +3,14 @@ import Foundation
 struct ToolCall: Identifiable, Hashable {
     let id: UUID
     let name: String
-    let input: String
+    var input: String
     var output: String?
     let startedAt: Date
     var completedAt: Date?
     var status: ToolCallStatus
     var error: String?
+    /// What the call changed, for Edit/MultiEdit/Write.
+    var diff: FileDiff?
 
     init(
         id: UUID = UUID(),
@@ -18,7 +20,8 @@ struct ToolCall: Identifiable, Hashable {
         startedAt: Date = Date(),
         completedAt: Date? = nil,
         status: ToolCallStatus = .running,
-        error: String? = nil
+        error: String? = nil,
+        diff: FileDiff? = nil
     ) {
         self.id = id
         self.name = name
@@ -28,6 +31,7 @@ struct ToolCall: Identifiable, Hashable {
         self.completedAt = completedAt
         self.status = status
         self.error = error
+        self.diff = diff
     }
 
     var duration: TimeInterval? {
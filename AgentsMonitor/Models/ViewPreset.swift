@@ -0,0 +1,117 @@
+import Foundation
+
+/// A named dashboard view, like "Failed this week in api-server": which
+/// sessions to show and in what order. Stored with the other settings, so
+/// presets survive restarts and travel with a settings export.
+struct ViewPreset: Equatable, Identifiable {
+    enum Sort: String, CaseIterable {
+        case newest
+        case oldest
+        case cost
+        case tokens
+    }
+
+    var name: String
+    var source: SessionSourceTab = .all
+    /// Empty shows every status.
+    var statuses: Set<SessionStatus> = []
+    /// Only sessions in this project folder or below it.
+    var projectPath: String?
+    /// Only sessions started in the last this-many days.
+    var withinDays: Int?
+    var sort: Sort = .newest
+    /// Sessions from the same project stay together, projects in name order.
+    var groupByProject = false
+    /// A `SessionQuery` filter such as `tokens>100k AND started>7d`; one that
+    /// no longer parses matches nothing rather than everything.
+    var query: String?
+
+    var id: String { name }
+
+    init(
+        name: String,
+        source: SessionSourceTab = .all,
+        statuses: Set<SessionStatus> = [],
+        projectPath: String? = nil,
+        withinDays: Int? = nil,
+        sort: Sort = .newest,
+        groupByProject: Bool = false,
+        query: String? = nil
+    ) {
+        self.name = name
+        self.source = source
+        self.statuses = statuses
+        self.projectPath = projectPath
+        self.withinDays = withinDays
+        self.sort = sort
+        self.groupByProject = groupByProject
+        self.query = query
+    }
+
+    func apply(to sessions: [Session], now: Date = Date()) -> [Session] {
+        let cutoff = withinDays.flatMap { Calendar.current.date(byAdding: .day, value: -$0, to: now) }
+        let folder = projectPath.map { $0.hasSuffix("/") ? String($0.dropLast()) : $0 }
+        let filter = query.map { try? SessionQuery($0) }
+        let matching = sessions.filter { session in
+            switch source {
+            case .all: break
+            case .codex: if session.agentType != .codex { return false }
+            case .claudeCode: if session.agentType != .claudeCode { return false }
+            }
+            if !statuses.isEmpty && !statuses.contains(session.status) { return false }
+            if let cutoff, session.startedAt < cutoff { return false }
+            if let folder {
+                guard let path = session.projectPath, path == folder || path.hasPrefix(folder + "/") else { return false }
+            }
+            if let filter { return filter?.matches(session, now: now) ?? false }
+            return true
+        }
+        return matching.sorted { a, b in
+            if groupByProject, a.projectPath != b.projectPath {
+                return (a.projectPath ?? "") < (b.projectPath ?? "")
+            }
+            switch sort {
+            case .newest: return a.startedAt > b.startedAt
+            case .oldest: return a.startedAt < b.startedAt
+            case .cost: return a.metrics.cost > b.metrics.cost
+            case .tokens: return a.metrics.totalTokens > b.metrics.totalTokens
+            }
+        }
+    }
+
+    // MARK: - Persistence
+
+    /// Plain property-list values, so presets can live in `UserDefaults` and
+    /// in a JSON settings export alike.
+    var dictionaryRepresentation: [String: Any] {
+        var dictionary: [String: Any] = [
+            "name": name,
+            "source": source.rawValue,
+            "statuses": statuses.map(\.rawValue).sorted(),
+            "sort": sort.rawValue,
+            "groupByProject": groupByProject
+        ]
+        if let projectPath { dictionary["projectPath"] = projectPath }
+        if let withinDays { dictionary["withinDays"] = withinDays }
+        if let query { dictionary["query"] = query }
+        return dictionary
+    }
+
+    /// `nil` for entries without a name; unknown values fall back to defaults.
+    init?(dictionary: [String: Any]) {
+        guard let name = dictionary["name"] as? String,
+              !name.trimmingCharacters(in: .whitespaces).isEmpty else {
+            return nil
+        }
+        self.init(
+            name: name,
+            source: (dictionary["source"] as? String).flatMap(SessionSourceTab.init(rawValue:)) ?? .all,
+            statuses: Set((dictionary["statuses"] as? [String] ?? []).compactMap(SessionStatus.init(rawValue:))),
+            projectPath: dictionary["projectPath"] as? String,
+            withinDays: (dictionary["withinDays"] as? Int).flatMap { $0 > 0 ? $0 : nil },
+            sort: (dictionary["sort"] as? String).flatMap(Sort.init(rawValue:)) ?? .newest,
+            groupByProject: dictionary["groupByProject"] as? Bool ?? false,
+            query: (dictionary["query"] as? String).flatMap { $0.trimmingCharacters(in: .whitespaces).isEmpty ? nil : $0 }
+        )
+    }
+}
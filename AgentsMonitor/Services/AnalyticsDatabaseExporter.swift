@@ -0,0 +1,220 @@
+import Foundation
+import SQLite3
+
+enum AnalyticsDatabaseError: LocalizedError {
+    case sqlite(String)
+
+    var errorDescription: String? {
+        switch self {
+        case .sqlite(let message): return "Database error: \(message)"
+        }
+    }
+}
+
+/// Writes sessions into a standalone SQLite file for ad-hoc SQL: one row per
+/// session, its metrics, every message and every tool call. The app's own
+/// caches stay JSON; this file is a snapshot and is replaced on each export.
+///
+/// Times are ISO 8601 text in UTC, which SQLite's date functions read as-is.
+/// Messages and tool calls are keyed by session and position, since ids read
+/// from transcripts are not guaranteed unique.
+enum AnalyticsDatabaseExporter {
+    static let schemaVersion = 1
+
+    static let schema = """
+        CREATE TABLE sessions (
+            id TEXT PRIMARY KEY,
+            name TEXT NOT NULL,
+            agent_type TEXT NOT NULL,
+            status TEXT NOT NULL,
+            started_at TEXT NOT NULL,
+            ended_at TEXT,
+            project_path TEXT,
+            git_branch TEXT,
+            first_prompt TEXT,
+            transcript_path TEXT
+        );
+        CREATE TABLE metrics (
+            session_id TEXT PRIMARY KEY REFERENCES sessions(id),
+            model TEXT,
+            input_tokens INTEGER NOT NULL,
+            output_tokens INTEGER NOT NULL,
+            cache_read_tokens INTEGER NOT NULL,
+            cache_write_tokens INTEGER NOT NULL,
+            total_tokens INTEGER NOT NULL,
+            cost REAL NOT NULL,
+            api_calls INTEGER NOT NULL,
+            tool_call_count INTEGER NOT NULL,
+            error_count INTEGER NOT NULL,
+            is_estimated INTEGER NOT NULL,
+            files_changed INTEGER NOT NULL,
+            lines_changed INTEGER NOT NULL,
+            tokens_per_file REAL,
+            cost_per_line REAL,
+            retries INTEGER NOT NULL
+        );
+        CREATE TABLE messages (
+            session_id TEXT NOT NULL REFERENCES sessions(id),
+            position INTEGER NOT NULL,
+            id TEXT NOT NULL,
+            parent_id TEXT,
+            role TEXT NOT NULL,
+            content TEXT NOT NULL,
+            timestamp TEXT NOT NULL,
+            estimated_tokens INTEGER NOT NULL,
+            PRIMARY KEY (session_id, position)
+        );
+        CREATE TABLE tool_calls (
+            session_id TEXT NOT NULL REFERENCES sessions(id),
+            position INTEGER NOT NULL,
+            id TEXT NOT NULL,
+            parent_tool_call_id TEXT,
+            name TEXT NOT NULL,
+            status TEXT NOT NULL,
+            input TEXT NOT NULL,
+            output TEXT,
+            error TEXT,
+            started_at TEXT NOT NULL,
+            completed_at TEXT,
+            duration REAL,
+            PRIMARY KEY (session_id, position)
+        );
+        CREATE INDEX tool_calls_name ON tool_calls(name);
+        CREATE INDEX sessions_started_at ON sessions(started_at);
+        """
+
+    static func export(_ sessions: [(session: Session, transcript: Transcript)], to url: URL) throws {
+        // Build next to the destination so a failed export leaves the old file alone.
+        let staging = url.deletingLastPathComponent()
+            .appendingPathComponent(".\(url.lastPathComponent).\(UUID().uuidString)")
+        defer { try? FileManager.default.removeItem(at: staging) }
+
+        let database = try Database(path: staging.path)
+        try database.execute("PRAGMA user_version = \(schemaVersion)")
+        try database.execute(schema)
+        try database.execute("BEGIN")
+        for (session, transcript) in sessions {
+            try insert(session, transcript: transcript, into: database)
+        }
+        try database.execute("COMMIT")
+        database.close()
+
+        if FileManager.default.fileExists(atPath: url.path) {
+            _ = try FileManager.default.replaceItemAt(url, withItemAt: staging)
+        } else {
+            try FileManager.default.moveItem(at: staging, to: url)
+        }
+    }
+
+    private static func insert(_ session: Session, transcript: Transcript, into database: Database) throws {
+        let sessionId = session.id.uuidString.lowercased()
+        try database.run(
+            "INSERT INTO sessions VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+            sessionId, session.name, session.agentType.rawValue, session.status.rawValue,
+            timestamp(session.startedAt), session.endedAt.map(timestamp),
+            session.projectPath, session.gitBranch, session.firstPrompt, session.jsonlPath
+        )
+
+        let metrics = session.metrics
+        let efficiency = EfficiencyMetrics(session: session, transcript: transcript)
+        try database.run(
+            "INSERT INTO metrics VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+            sessionId, metrics.modelIds.first ?? (metrics.modelName.isEmpty ? nil : metrics.modelName),
+            metrics.inputTokens, metrics.outputTokens, metrics.cacheReadTokens, metrics.cacheWriteTokens,
+            metrics.totalTokens, metrics.cost, metrics.apiCalls,
+            max(metrics.toolCallCount, transcript.toolCalls.count), metrics.errorCount, metrics.isEstimated,
+            efficiency.filesChanged, efficiency.linesChanged, efficiency.tokensPerFile, efficiency.costPerChangedLine,
+            efficiency.retries
+        )
+
+        for (position, message) in transcript.messages.enumerated() {
+            try database.run(
+                "INSERT INTO messages VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+                sessionId, position, message.id.uuidString.lowercased(), message.parentId.map { $0.uuidString.lowercased() },
+                message.role.rawValue, message.content, timestamp(message.timestamp), message.estimatedTokens
+            )
+        }
+
+        var parents: [UUID: UUID] = [:]
+        for (taskId, callIds) in transcript.subagentToolCallIds {
+            for callId in callIds { parents[callId] = taskId }
+        }
+        for (position, call) in transcript.toolCalls.enumerated() {
+            try database.run(
+                "INSERT INTO tool_calls VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+                sessionId, position, call.id.uuidString.lowercased(), parents[call.id].map { $0.uuidString.lowercased() },
+                call.name, call.status.rawValue, call.input, call.output, call.error,
+                timestamp(call.startedAt), call.completedAt.map(timestamp), call.duration
+            )
+        }
+    }
+
+    private static let formatter = ISO8601DateFormatter()
+
+    private static func timestamp(_ date: Date) -> String {
+        formatter.string(from: date)
+    }
+
+    // MARK: - SQLite
+
+    /// Just enough of the C API for a one-shot write.
+    private final class Database {
+        private var handle: OpaquePointer?
+        private var statements: [String: OpaquePointer] = [:]
+
+        init(path: String) throws {
+            guard sqlite3_open(path, &handle) == SQLITE_OK else {
+                let message = handle.map { String(cString: sqlite3_errmsg($0)) } ?? "cannot open \(path)"
+                sqlite3_close(handle)
+                handle = nil
+                throw AnalyticsDatabaseError.sqlite(message)
+            }
+        }
+
+        deinit { close() }
+
+        func close() {
+            for statement in statements.values { sqlite3_finalize(statement) }
+            statements.removeAll()
+            sqlite3_close(handle)
+            handle = nil
+        }
+
+        func execute(_ sql: String) throws {
+            guard sqlite3_exec(handle, sql, nil, nil, nil) == SQLITE_OK else { throw error() }
+        }
+
+        func run(_ sql: String, _ values: Any?...) throws {
+            let statement: OpaquePointer
+            if let cached = statements[sql] {
+                statement = cached
+            } else {
+                var prepared: OpaquePointer?
+                guard sqlite3_prepare_v2(handle, sql, -1, &prepared, nil) == SQLITE_OK, let prepared else { throw error() }
+                statements[sql] = prepared
+                statement = prepared
+            }
+            sqlite3_reset(statement)
+            sqlite3_clear_bindings(statement)
+
+            // SQLITE_TRANSIENT: SQLite copies the text before the call returns.
+            let transient = unsafeBitCast(-1, to: sqlite3_destructor_type.self)
+            for (offset, value) in values.enumerated() {
+                let index = Int32(offset + 1)
+                switch value {
+                case nil: sqlite3_bind_null(statement, index)
+                case let bool as Bool: sqlite3_bind_int(statement, index, bool ? 1 : 0)
+                case let int as Int: sqlite3_bind_int64(statement, index, Int64(int))
+                case let double as Double: sqlite3_bind_double(statement, index, double)
+                case let string as String: sqlite3_bind_text(statement, index, string, -1, transient)
+                case let other?: sqlite3_bind_text(statement, index, "\(other)", -1, transient)
+                }
+            }
+            guard sqlite3_step(statement) == SQLITE_DONE else { throw error() }
+        }
+
+        private func error() -> AnalyticsDatabaseError {
+            .sqlite(handle.map { String(cString: sqlite3_errmsg($0)) } ?? "database closed")
+        }
+    }
+}
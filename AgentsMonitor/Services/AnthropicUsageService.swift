@@ -127,8 +127,7 @@ actor AnthropicUsageService: UsageServiceProviding {
     }
 
     private func loadFromFile() -> OAuthCredentials? {
-        let home = FileUtilities.realHomeDirectory()
-        let path = (home as NSString).appendingPathComponent(".claude/.credentials.json")
+        let path = AppSettings.load().claudeDirectoryURL().appendingPathComponent(".credentials.json").path
         guard let data = FileManager.default.contents(atPath: path) else { return nil }
         return parseCredentialData(data)
     }
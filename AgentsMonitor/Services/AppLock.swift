@@ -0,0 +1,63 @@
+import Foundation
+import LocalAuthentication
+import Observation
+
+/// Hides session content behind Touch ID or the account password when the
+/// `appLockEnabled` setting is on. The app starts locked and locks again once
+/// the popover has been closed for `idleInterval`.
+@Observable
+final class AppLock {
+    static let idleInterval: TimeInterval = 5 * 60
+
+    private(set) var isLocked: Bool
+    var lastError: String?
+    @ObservationIgnored private var lastActivity: Date?
+    @ObservationIgnored private let isEnabled: () -> Bool
+
+    init(isEnabled: @escaping () -> Bool = { AppSettings.load().appLockEnabled }) {
+        self.isEnabled = isEnabled
+        self.isLocked = isEnabled()
+    }
+
+    /// Call when the popover closes; the idle clock starts from here.
+    func noteActivity(at date: Date = Date()) {
+        lastActivity = date
+    }
+
+    /// Call when the popover opens.
+    func lockIfIdle(now: Date = Date()) {
+        guard isEnabled() else {
+            isLocked = false
+            return
+        }
+        guard let lastActivity else { return }
+        if now.timeIntervalSince(lastActivity) >= Self.idleInterval {
+            isLocked = true
+        }
+    }
+
+    func lock() {
+        guard isEnabled() else { return }
+        isLocked = true
+    }
+
+    @MainActor
+    func unlock() async {
+        let context = LAContext()
+        var policyError: NSError?
+        guard context.canEvaluatePolicy(.deviceOwnerAuthentication, error: &policyError) else {
+            lastError = policyError?.localizedDescription ?? "Authentication is not available"
+            return
+        }
+        do {
+            try await context.evaluatePolicy(.deviceOwnerAuthentication, localizedReason: "unlock your agent sessions")
+            isLocked = false
+            lastError = nil
+        } catch let error as LAError where error.code == .userCancel || error.code == .appCancel {
+            lastError = nil
+        } catch {
+            lastError = error.localizedDescription
+            AppLogger.logWarning("Unlock failed: \(error.localizedDescription)", context: "AppLock")
+        }
+    }
+}
@@ -0,0 +1,46 @@
+import CryptoKit
+import Foundation
+
+/// Per-session folder under Caches where inline images from transcripts are
+/// written so they can be opened in other apps. File references point at the
+/// original path and are never copied.
+enum AttachmentStore {
+    static var baseDirectory: URL {
+        FileManager.default.urls(for: .cachesDirectory, in: .userDomainMask)[0]
+            .appendingPathComponent("com.agentsmonitor.app")
+            .appendingPathComponent("attachments")
+    }
+
+    static func folder(for sessionId: UUID, base: URL = baseDirectory) -> URL {
+        base.appendingPathComponent(sessionId.uuidString)
+    }
+
+    /// Returns a file URL for `attachment`, writing inline image data into the
+    /// session's folder the first time it is requested.
+    static func fileURL(for attachment: MessageAttachment, sessionId: UUID, base: URL = baseDirectory) throws -> URL {
+        if let filePath = attachment.filePath {
+            return URL(fileURLWithPath: filePath)
+        }
+        guard let data = attachment.data else {
+            throw CocoaError(.fileNoSuchFile)
+        }
+
+        let folder = folder(for: sessionId, base: base)
+        // Named by content so re-reading the transcript reuses the same file.
+        let name = SHA256.hash(data: data).prefix(16).map { String(format: "%02x", $0) }.joined()
+        let url = folder.appendingPathComponent(name).appendingPathExtension(attachment.fileExtension)
+        if !FileManager.default.fileExists(atPath: url.path) {
+            try FileManager.default.createDirectory(at: folder, withIntermediateDirectories: true)
+            try data.write(to: url, options: .atomic)
+        }
+        return url
+    }
+
+    static func removeAll(base: URL = baseDirectory) {
+        do {
+            try FileManager.default.removeItem(at: base)
+        } catch let error as NSError where !(error.domain == NSCocoaErrorDomain && error.code == NSFileNoSuchFileError) {
+            AppLogger.logWarning("Failed to clear attachments: \(error.localizedDescription)", context: "AttachmentStore")
+        } catch {}
+    }
+}
@@ -17,6 +17,8 @@ struct ClaudeSessionEntry: Decodable {
     let gitBranch: String?
     let projectPath: String?
     let isSidechain: Bool
+    /// For a sub-agent's own transcript file, the session whose Task call spawned it.
+    var parentSessionId: String?
 
     var startDate: Date? {
         Self.parseISO8601(created)
@@ -30,11 +32,12 @@ struct ClaudeSessionEntry: Decodable {
         if let summary, !summary.isEmpty {
             return summary
         }
-        if let firstPrompt, !firstPrompt.isEmpty {
-            return String(firstPrompt.prefix(80))
-        }
-        let shortId = String(sessionId.prefix(8))
-        return "Session \(shortId)"
+        return Session.derivedName(
+            firstPrompt: firstPrompt,
+            gitBranch: gitBranch,
+            projectPath: projectPath,
+            fallback: "Session \(sessionId.prefix(8))"
+        )
     }
 
     init(
@@ -48,7 +51,8 @@ struct ClaudeSessionEntry: Decodable {
         modified: String,
         gitBranch: String?,
         projectPath: String?,
-        isSidechain: Bool
+        isSidechain: Bool,
+        parentSessionId: String? = nil
     ) {
         self.sessionId = sessionId
         self.fullPath = fullPath
@@ -61,6 +65,7 @@ struct ClaudeSessionEntry: Decodable {
         self.gitBranch = gitBranch
         self.projectPath = projectPath
         self.isSidechain = isSidechain
+        self.parentSessionId = parentSessionId
     }
 
     private static func parseISO8601(_ string: String) -> Date? {
@@ -76,11 +81,26 @@ struct ClaudeSessionEntry: Decodable {
 
 actor ClaudeSessionService {
     private let fileManager = FileManager.default
-    private let claudeDir: URL
+    private var claudeDir: URL
+    // Parsed sessions-index.json files and JSONL headers, keyed by path and
+    // invalidated by mtime so refreshes only re-read what changed.
+    private var indexCache: [String: (mtime: Date, entries: [ClaudeSessionEntry])] = [:]
+    private var metadataCache: [String: (mtime: Date, entry: ClaudeSessionEntry?)] = [:]
 
-    init() {
+    init(claudeDir: URL? = nil) {
         let home = FileUtilities.realHomeDirectory()
-        self.claudeDir = URL(fileURLWithPath: home).appendingPathComponent(".claude")
+        self.claudeDir = claudeDir ?? URL(fileURLWithPath: home).appendingPathComponent(".claude")
+    }
+
+    var cachedEntryCount: Int {
+        indexCache.count + metadataCache.count
+    }
+
+    func setDirectory(_ url: URL) {
+        guard url != claudeDir else { return }
+        claudeDir = url
+        indexCache.removeAll()
+        metadataCache.removeAll()
     }
 
     func discoverSessions(showAll: Bool, showSidechains: Bool) async -> [Session] {
@@ -99,16 +119,10 @@ actor ClaudeSessionService {
 
             for dir in projectDirs {
                 let indexFile = dir.appendingPathComponent("sessions-index.json")
-                if fileManager.fileExists(atPath: indexFile.path) {
-                    do {
-                        let data = try Data(contentsOf: indexFile)
-                        let index = try JSONDecoder().decode(ClaudeSessionIndex.self, from: data)
-                        allEntries.append(contentsOf: index.entries)
-                        for entry in index.entries {
-                            indexedIds.insert(entry.sessionId)
-                        }
-                    } catch {
-                        AppLogger.logWarning("Failed to parse \(indexFile.path): \(error.localizedDescription)", context: "ClaudeSessionService")
+                if let entries = indexEntries(at: indexFile) {
+                    allEntries.append(contentsOf: entries)
+                    for entry in entries {
+                        indexedIds.insert(entry.sessionId)
                     }
                 }
 
@@ -142,9 +156,13 @@ actor ClaudeSessionService {
             }
 
             let isRecent = isRecentlyModified(entry: entry)
-            let status: SessionStatus = isRecent ? .running : .completed
+            var status: SessionStatus = isRecent ? .running : .completed
+            let pendingPermission = status == .running ? pendingPermissionRequest(atPath: entry.fullPath) : nil
+            if pendingPermission != nil {
+                status = .waiting
+            }
 
-            return Session(
+            var session = Session(
                 id: sessionUUID,
                 name: entry.sessionName,
                 status: status,
@@ -159,13 +177,22 @@ actor ClaudeSessionService {
                 firstPrompt: entry.firstPrompt,
                 sessionSummary: entry.summary,
                 isSidechain: entry.isSidechain,
-                fileMtime: entry.fileMtime
+                parentSessionId: entry.parentSessionId.flatMap(UUID.init(uuidString:)),
+                fileMtime: entry.fileMtime,
+                pendingPermission: pendingPermission
             )
+            if status != .completed, let tail = PermissionRequestDetector.readTail(of: entry.fullPath) {
+                session.operatingMode = OperatingModeDetector.mode(in: tail, agentType: .claudeCode)
+                if let cwd = WorkingDirectoryDetector.directory(in: tail, agentType: .claudeCode) {
+                    session.workingDirectory = cwd
+                }
+            }
+            return session
         }
 
         // Filter: active-only unless showAll
         if !showAll {
-            sessions = sessions.filter { $0.status == .running }
+            sessions = sessions.filter { $0.status == .running || $0.status == .waiting }
         }
 
         // Sort by most recent first
@@ -196,15 +223,40 @@ actor ClaudeSessionService {
             guard !indexedIds.contains(sessionId) else { continue }
             guard UUID(uuidString: sessionId) != nil else { continue }
 
-            guard let entry = parseJSONLMetadata(file: file, sessionId: sessionId, projectDir: projectDir) else {
-                continue
+            let mtime = (try? file.resourceValues(forKeys: [.contentModificationDateKey]))?.contentModificationDate ?? Date()
+            let entry: ClaudeSessionEntry?
+            if let cached = metadataCache[file.path], cached.mtime == mtime {
+                entry = cached.entry
+            } else {
+                entry = parseJSONLMetadata(file: file, sessionId: sessionId, projectDir: projectDir)
+                metadataCache[file.path] = (mtime: mtime, entry: entry)
             }
+            guard let entry else { continue }
             entries.append(entry)
         }
 
         return entries
     }
 
+    private func indexEntries(at indexFile: URL) -> [ClaudeSessionEntry]? {
+        guard let attrs = try? fileManager.attributesOfItem(atPath: indexFile.path),
+              let mtime = attrs[.modificationDate] as? Date else {
+            return nil
+        }
+        if let cached = indexCache[indexFile.path], cached.mtime == mtime {
+            return cached.entries
+        }
+        do {
+            let data = try Data(contentsOf: indexFile)
+            let index = try JSONDecoder().decode(ClaudeSessionIndex.self, from: data)
+            indexCache[indexFile.path] = (mtime: mtime, entries: index.entries)
+            return index.entries
+        } catch {
+            AppLogger.logWarning("Failed to parse \(indexFile.path): \(error.localizedDescription)", context: "ClaudeSessionService")
+            return nil
+        }
+    }
+
     private func parseJSONLMetadata(file: URL, sessionId: String, projectDir: URL) -> ClaudeSessionEntry? {
         guard let handle = FileHandle(forReadingAtPath: file.path) else { return nil }
         defer { handle.closeFile() }
@@ -219,6 +271,7 @@ actor ClaudeSessionService {
         var cwd: String?
         var gitBranch: String?
         var isSidechain = false
+        var parentSessionId: String?
         var firstTimestamp: String?
         var firstPrompt: String?
 
@@ -244,6 +297,9 @@ actor ClaudeSessionService {
 
             if let sc = json["isSidechain"] as? Bool, sc {
                 isSidechain = true
+                if parentSessionId == nil, let owner = json["sessionId"] as? String, owner != sessionId {
+                    parentSessionId = owner
+                }
             }
 
             if firstPrompt == nil, let type = json["type"] as? String, type == "user" {
@@ -275,7 +331,8 @@ actor ClaudeSessionService {
             modified: modifiedString,
             gitBranch: gitBranch,
             projectPath: cwd,
-            isSidechain: isSidechain
+            isSidechain: isSidechain,
+            parentSessionId: parentSessionId
         )
     }
 
@@ -304,4 +361,11 @@ actor ClaudeSessionService {
         return Date().timeIntervalSince(mtimeDate) < 1800
     }
 
+    private func pendingPermissionRequest(atPath path: String) -> PermissionRequest? {
+        guard let modified = (try? fileManager.attributesOfItem(atPath: path))?[.modificationDate] as? Date else {
+            return nil
+        }
+        return PermissionRequestDetector.pendingRequest(jsonlPath: path, modifiedAt: modified)
+    }
+
 }
@@ -0,0 +1,105 @@
+import Foundation
+
+/// Writes a transcript back out in the JSONL layout Claude Code uses under
+/// `~/.claude/projects`, so a Codex session (or a redacted Claude one) can be
+/// fed to tools that read that format.
+///
+/// Lines are chained through `parentUuid` in order. Each tool call becomes an
+/// assistant `tool_use` line followed by a user `tool_result` line once it
+/// finished.
+enum ClaudeTranscriptExporter {
+    static let version = "agents-monitor"
+
+    static func jsonl(for transcript: Transcript, session: Session) -> String {
+        var writer = Writer(session: session)
+        let toolCalls = Dictionary(transcript.toolCalls.map { ($0.id, $0) }, uniquingKeysWith: { first, _ in first })
+
+        for message in transcript.messages {
+            switch message.role {
+            case .user:
+                let images = message.attachments.compactMap(imageBlock)
+                if images.isEmpty {
+                    writer.add(type: "user", content: message.content, id: message.id, at: message.timestamp)
+                } else {
+                    var blocks = images
+                    if !message.content.isEmpty {
+                        blocks.insert(["type": "text", "text": message.content], at: 0)
+                    }
+                    writer.add(type: "user", content: blocks, id: message.id, at: message.timestamp)
+                }
+            case .assistant:
+                writer.add(type: "assistant", content: [["type": "text", "text": message.content]], id: message.id, at: message.timestamp)
+            case .tool:
+                guard let callId = message.toolUseId, let call = toolCalls[callId] else { continue }
+                let toolUseId = "toolu_" + call.id.uuidString.replacingOccurrences(of: "-", with: "").lowercased()
+                writer.add(type: "assistant", content: [[
+                    "type": "tool_use",
+                    "id": toolUseId,
+                    "name": call.name,
+                    "input": jsonObject(call.input) ?? [String: Any]()
+                ]], id: message.id, at: call.startedAt)
+
+                guard call.status == .completed || call.status == .failed else { continue }
+                writer.add(type: "user", content: [[
+                    "type": "tool_result",
+                    "tool_use_id": toolUseId,
+                    "content": call.output ?? call.error ?? "",
+                    "is_error": call.status == .failed
+                ]], id: UUID(), at: call.completedAt ?? call.startedAt)
+            case .system:
+                continue
+            }
+        }
+        return writer.lines.joined(separator: "\n") + (writer.lines.isEmpty ? "" : "\n")
+    }
+
+    private struct Writer {
+        let session: Session
+        var lines: [String] = []
+        var lastId: UUID?
+        let formatter: ISO8601DateFormatter = {
+            let formatter = ISO8601DateFormatter()
+            formatter.formatOptions = [.withInternetDateTime, .withFractionalSeconds]
+            return formatter
+        }()
+
+        mutating func add(type: String, content: Any, id: UUID, at timestamp: Date) {
+            var line: [String: Any] = [
+                "type": type,
+                "uuid": id.uuidString.lowercased(),
+                "parentUuid": lastId.map { $0.uuidString.lowercased() as Any } ?? NSNull(),
+                "sessionId": session.id.uuidString.lowercased(),
+                "timestamp": formatter.string(from: timestamp),
+                "isSidechain": session.isSidechain,
+                "userType": "external",
+                "version": ClaudeTranscriptExporter.version,
+                "message": ["role": type, "content": content]
+            ]
+            if let cwd = session.projectPath ?? session.workingDirectory?.path {
+                line["cwd"] = cwd
+            }
+            if let branch = session.gitBranch {
+                line["gitBranch"] = branch
+            }
+            guard let data = try? JSONSerialization.data(withJSONObject: line, options: [.sortedKeys, .withoutEscapingSlashes]) else {
+                AppLogger.logWarning("Skipping unencodable \(type) line", context: "ClaudeTranscriptExporter")
+                return
+            }
+            lines.append(String(decoding: data, as: UTF8.self))
+            lastId = id
+        }
+    }
+
+    private static func imageBlock(_ attachment: MessageAttachment) -> [String: Any]? {
+        guard attachment.kind == .image, let data = attachment.data else { return nil }
+        return [
+            "type": "image",
+            "source": ["type": "base64", "media_type": attachment.mediaType ?? "image/png", "data": data.base64EncodedString()]
+        ]
+    }
+
+    private static func jsonObject(_ text: String) -> [String: Any]? {
+        guard let data = text.data(using: .utf8) else { return nil }
+        return try? JSONSerialization.jsonObject(with: data) as? [String: Any]
+    }
+}
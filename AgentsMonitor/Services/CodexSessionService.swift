@@ -2,11 +2,23 @@ import Foundation
 
 actor CodexSessionService {
     private let fileManager = FileManager.default
-    private let codexDir: URL
+    private var codexDir: URL
+    // Parsed rollout headers: path → (mtime, session). Unchanged files are not re-read.
+    private var parsedFiles: [String: (mtime: Int64, session: Session)] = [:]
 
-    init() {
+    init(codexDir: URL? = nil) {
         let home = FileUtilities.realHomeDirectory()
-        self.codexDir = URL(fileURLWithPath: home).appendingPathComponent(".codex")
+        self.codexDir = codexDir ?? URL(fileURLWithPath: home).appendingPathComponent(".codex")
+    }
+
+    var cachedEntryCount: Int {
+        parsedFiles.count
+    }
+
+    func setDirectory(_ url: URL) {
+        guard url != codexDir else { return }
+        codexDir = url
+        parsedFiles.removeAll()
     }
 
     func discoverSessions(showAll: Bool, showSidechains: Bool) async -> [Session] {
@@ -15,6 +27,7 @@ actor CodexSessionService {
 
         let dateDirs = recentDateDirectories(baseDir: sessionsDir)
         var sessions: [Session] = []
+        var visitedPaths = Set<String>()
 
         for dateDir in dateDirs {
             guard fileManager.fileExists(atPath: dateDir.path) else { continue }
@@ -32,7 +45,8 @@ actor CodexSessionService {
             }
 
             for file in jsonlFiles {
-                guard let session = parseSessionFile(file) else { continue }
+                visitedPaths.insert(file.path)
+                guard let session = cachedSession(for: file) else { continue }
 
                 if !showSidechains && session.isSidechain { continue }
                 if !showAll && session.status != .running { continue }
@@ -41,10 +55,31 @@ actor CodexSessionService {
             }
         }
 
+        parsedFiles = parsedFiles.filter { visitedPaths.contains($0.key) }
         sessions.sort { $0.startedAt > $1.startedAt }
         return sessions
     }
 
+    /// Re-reads a rollout file only when its mtime changed. Status is applied
+    /// on every call because it depends on how long ago the file was written.
+    private func cachedSession(for fileURL: URL) -> Session? {
+        let mtime = fileModificationTime(fileURL)
+        if let cached = parsedFiles[fileURL.path], cached.mtime == mtime {
+            return Self.applyingStatus(to: cached.session)
+        }
+        guard let session = parseSessionFile(fileURL) else { return nil }
+        parsedFiles[fileURL.path] = (mtime: mtime, session: session)
+        return session
+    }
+
+    private static func applyingStatus(to session: Session, now: Date = Date()) -> Session {
+        var copy = session
+        let mtimeDate = Date(timeIntervalSince1970: TimeInterval(session.fileMtime) / 1000.0)
+        copy.status = now.timeIntervalSince(mtimeDate) < 1800 ? .running : .completed
+        copy.endedAt = copy.status == .completed ? mtimeDate : nil
+        return copy
+    }
+
     private func parseSessionFile(_ fileURL: URL) -> Session? {
         guard let handle = FileHandle(forReadingAtPath: fileURL.path) else { return nil }
         defer { handle.closeFile() }
@@ -134,9 +169,9 @@ actor CodexSessionService {
         let status: SessionStatus = isRunning ? .running : .completed
 
         let shortId = String(sid.prefix(8))
-        let name = firstPrompt ?? "Codex session \(shortId)"
+        let name = Session.derivedName(firstPrompt: firstPrompt, gitBranch: gitBranch, projectPath: cwd, fallback: "Codex session \(shortId)")
 
-        return Session(
+        var session = Session(
             id: uuid,
             name: name,
             status: status,
@@ -152,6 +187,13 @@ actor CodexSessionService {
             isSidechain: isSidechain,
             fileMtime: fileMtime
         )
+        if isRunning, let tail = PermissionRequestDetector.readTail(of: fileURL.path) {
+            session.operatingMode = OperatingModeDetector.mode(in: tail, agentType: .codex)
+            if let cwd = WorkingDirectoryDetector.directory(in: tail, agentType: .codex) {
+                session.workingDirectory = cwd
+            }
+        }
+        return session
     }
 
     private var rateLimitCache: (path: String, mtime: Date, limits: CodexRateLimits)?
@@ -0,0 +1,145 @@
+import Foundation
+
+/// A shell command from a transcript that matched a destructive pattern.
+struct FlaggedCommand: Hashable {
+    let rule: String
+    let command: String
+    let timestamp: Date?
+}
+
+/// Scans the shell commands agents run for destructive patterns such as
+/// `rm -rf /`, force pushes and `DROP TABLE`.
+///
+/// Claude Code records these as `Bash` tool uses; Codex as `shell` /
+/// `exec_command` function calls. Extra patterns come from the
+/// `dangerousCommandPatterns` setting and are reported under their own text.
+struct DangerousCommandDetector {
+    struct Rule {
+        let name: String
+        let expression: NSRegularExpression
+    }
+
+    static let builtInPatterns: [(name: String, pattern: String)] = [
+        ("Recursive delete of root or home", #"\brm\s+(?:-[A-Za-z]*\s+)*-[A-Za-z]*[rR][A-Za-z]*\s+(?:-[A-Za-z]+\s+)*(?:/|~/?|\$HOME/?)\*?(?=\s|$|[;&|])"#),
+        ("Force push", #"\bgit\s+push\b[^\n;&|]*(?:\s--force\b|\s-f\b|\s--force-with-lease\b)"#),
+        ("Hard reset", #"\bgit\s+(?:reset\s+--hard|clean\s+-[A-Za-z]*f)"#),
+        ("Drop database object", #"(?i)\bdrop\s+(?:table|database|schema)\b"#),
+        ("Truncate table", #"(?i)\btruncate\s+table\b"#),
+        ("Disk overwrite", #"\b(?:mkfs(?:\.\w+)?|dd\s+[^\n]*\bof=/dev/)"#),
+        ("World-writable permissions", #"\bchmod\s+(?:-R\s+)?0?777\s+/"#),
+        ("Pipe to shell", #"\b(?:curl|wget)\b[^\n|]*\|\s*(?:sudo\s+)?(?:ba|z)?sh\b"#)
+    ]
+
+    private let rules: [Rule]
+
+    init(customPatterns: [String] = []) {
+        var rules: [Rule] = []
+        let custom = customPatterns.map { (name: $0, pattern: $0) }
+        for entry in Self.builtInPatterns + custom where !entry.pattern.isEmpty {
+            do {
+                rules.append(Rule(name: entry.name, expression: try NSRegularExpression(pattern: entry.pattern)))
+            } catch {
+                AppLogger.logWarning("Ignoring invalid command pattern: \(entry.pattern)", context: "DangerousCommandDetector")
+            }
+        }
+        self.rules = rules
+    }
+
+    /// The first rule `command` matches, if any.
+    func match(_ command: String) -> String? {
+        let range = NSRange(command.startIndex..., in: command)
+        return rules.first { $0.expression.firstMatch(in: command, range: range) != nil }?.name
+    }
+
+    func scan(jsonlPath: String, agentType: AgentType) -> [FlaggedCommand] {
+        guard let content = try? String(contentsOfFile: jsonlPath, encoding: .utf8) else {
+            AppLogger.logWarning("Cannot read JSONL file: \(jsonlPath)", context: "DangerousCommandDetector")
+            return []
+        }
+        return Self.shellCommands(in: content, agentType: agentType).compactMap { entry in
+            guard let rule = match(entry.command) else { return nil }
+            return FlaggedCommand(rule: rule, command: entry.command, timestamp: entry.timestamp)
+        }
+    }
+
+    // MARK: - Transcript Parsing
+
+    static func shellCommands(in content: String, agentType: AgentType) -> [(command: String, timestamp: Date?)] {
+        var commands: [(command: String, timestamp: Date?)] = []
+        for line in content.split(separator: "\n") {
+            guard let lineData = line.data(using: .utf8),
+                  let json = try? JSONSerialization.jsonObject(with: lineData) as? [String: Any] else {
+                continue
+            }
+            let timestamp = (json["timestamp"] as? String).flatMap(parseISO8601)
+            let found = agentType == .codex ? codexCommands(in: json) : claudeCommands(in: json)
+            commands.append(contentsOf: found.map { (command: $0, timestamp: timestamp) })
+        }
+        return commands
+    }
+
+    private static func claudeCommands(in json: [String: Any]) -> [String] {
+        guard json["type"] as? String == "assistant",
+              let message = json["message"] as? [String: Any],
+              let content = message["content"] as? [[String: Any]] else {
+            return []
+        }
+        return content.compactMap { block in
+            guard block["type"] as? String == "tool_use",
+                  block["name"] as? String == "Bash",
+                  let input = block["input"] as? [String: Any] else {
+                return nil
+            }
+            return input["command"] as? String
+        }
+    }
+
+    private static func codexCommands(in json: [String: Any]) -> [String] {
+        guard json["type"] as? String == "response_item",
+              let payload = json["payload"] as? [String: Any] else {
+            return []
+        }
+        switch payload["type"] as? String {
+        case "function_call":
+            guard let name = payload["name"] as? String, ["shell", "exec_command"].contains(name),
+                  let raw = payload["arguments"] as? String,
+                  let data = raw.data(using: .utf8),
+                  let arguments = try? JSONSerialization.jsonObject(with: data) as? [String: Any] else {
+                return []
+            }
+            return commandText(arguments["command"] ?? arguments["cmd"]).map { [$0] } ?? []
+        case "local_shell_call":
+            let action = payload["action"] as? [String: Any]
+            return commandText(action?["command"]).map { [$0] } ?? []
+        default:
+            return []
+        }
+    }
+
+    /// The command line a parsed shell tool call ran; `nil` for other tools.
+    static func shellCommand(of call: ToolCall) -> String? {
+        guard ["Bash", "shell", "exec_command"].contains(call.name),
+              let data = call.input.data(using: .utf8),
+              let input = try? JSONSerialization.jsonObject(with: data) as? [String: Any] else {
+            return nil
+        }
+        return commandText(input["command"] ?? input["cmd"])
+    }
+
+    /// Codex passes argv arrays like `["bash", "-lc", "<script>"]`; the script is what matters.
+    private static func commandText(_ value: Any?) -> String? {
+        if let string = value as? String { return string }
+        guard let argv = value as? [String], !argv.isEmpty else { return nil }
+        if argv.count >= 3, ["-c", "-lc"].contains(argv[argv.count - 2]) {
+            return argv[argv.count - 1]
+        }
+        return argv.joined(separator: " ")
+    }
+
+    private static func parseISO8601(_ string: String) -> Date? {
+        let fractional = ISO8601DateFormatter()
+        fractional.formatOptions = [.withInternetDateTime, .withFractionalSeconds]
+        if let date = fractional.date(from: string) { return date }
+        return ISO8601DateFormatter().date(from: string)
+    }
+}
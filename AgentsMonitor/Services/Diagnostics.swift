@@ -0,0 +1,235 @@
+import Darwin
+import Foundation
+
+/// How long the first load took and what it found, to spot when a huge
+/// session directory is what makes the app slow to open.
+struct StartupDiagnostics: Equatable {
+    static let slowThreshold: TimeInterval = 2
+
+    var costCacheLoad: TimeInterval = 0
+    var claudeDiscovery: TimeInterval = 0
+    var codexDiscovery: TimeInterval = 0
+    /// From store creation until the first sessions were published; nil until then.
+    var timeToFirstSessions: TimeInterval?
+    var claudeSessionCount = 0
+    var codexSessionCount = 0
+    var costCacheEntries = 0
+
+    var isSlow: Bool {
+        (timeToFirstSessions ?? 0) >= Self.slowThreshold
+    }
+
+    static func format(_ duration: TimeInterval) -> String {
+        duration < 1 ? String(format: "%.0f ms", duration * 1000) : String(format: "%.1f s", duration)
+    }
+}
+
+/// What the app is holding in memory, for tracking down growth after long uptimes.
+struct MemoryReport: Equatable {
+    var residentBytes: UInt64?
+    var sessionCount = 0
+    var costCacheEntries = 0
+    var flaggedCommandEntries = 0
+    var claudeCacheEntries = 0
+    var codexCacheEntries = 0
+
+    var formattedResidentSize: String {
+        guard let residentBytes else { return "Unknown" }
+        return ByteCountFormatter.string(fromByteCount: Int64(residentBytes), countStyle: .memory)
+    }
+
+    var cachedEntryCount: Int {
+        costCacheEntries + flaggedCommandEntries + claudeCacheEntries + codexCacheEntries
+    }
+
+    /// Resident memory of this process as reported by the kernel.
+    static func currentResidentBytes() -> UInt64? {
+        var info = mach_task_basic_info()
+        var count = mach_msg_type_number_t(MemoryLayout<mach_task_basic_info>.size / MemoryLayout<natural_t>.size)
+        let result = withUnsafeMutablePointer(to: &info) { pointer in
+            pointer.withMemoryRebound(to: integer_t.self, capacity: Int(count)) {
+                task_info(mach_task_self_, task_flavor_t(MACH_TASK_BASIC_INFO), $0, &count)
+            }
+        }
+        guard result == KERN_SUCCESS else { return nil }
+        return info.resident_size
+    }
+}
+
+/// What a first run finds on this Mac: which agents are installed, signed in
+/// and have written sessions, and what the user should do next when the
+/// session list comes up empty.
+struct EnvironmentProbe: Equatable {
+    struct Agent: Equatable {
+        let type: AgentType
+        let isEnabled: Bool
+        /// Where the app looks for this agent's sessions.
+        let dataDirectory: URL
+        /// The agent has written at least one session there.
+        let hasSessions: Bool
+        let executablePath: String?
+        /// `nil` when it can't be told without prompting, e.g. Claude Code's
+        /// Keychain item.
+        let isAuthenticated: Bool?
+
+        var isInstalled: Bool {
+            executablePath != nil
+        }
+    }
+
+    var agents: [Agent] = []
+    var gitPath: String?
+
+    /// Folders a GUI app doesn't get on its PATH but package managers install into.
+    static func searchPaths(home: String) -> [String] {
+        [
+            "\(home)/.local/bin", "\(home)/.claude/local", "\(home)/.npm-global/bin", "\(home)/.bun/bin",
+            "\(home)/.volta/bin", "/opt/homebrew/bin", "/usr/local/bin", "/usr/bin"
+        ]
+    }
+
+    static func run(
+        settings: AppSettings = AppSettings.load(),
+        home: String = FileUtilities.realHomeDirectory(),
+        searchPaths: [String]? = nil,
+        fileManager: FileManager = .default
+    ) -> EnvironmentProbe {
+        let paths = searchPaths ?? Self.searchPaths(home: home)
+        func executable(_ name: String) -> String? {
+            paths.map { ($0 as NSString).appendingPathComponent(name) }.first { fileManager.isExecutableFile(atPath: $0) }
+        }
+        func hasEntries(_ url: URL) -> Bool {
+            !((try? fileManager.contentsOfDirectory(atPath: url.path)) ?? []).filter { !$0.hasPrefix(".") }.isEmpty
+        }
+
+        let claudeDirectory = settings.claudeDirectoryURL(home: home)
+        let codexDirectory = settings.codexDirectoryURL(home: home)
+        let claudeCredentials = claudeDirectory.appendingPathComponent(".credentials.json").path
+        var probe = EnvironmentProbe()
+        probe.agents = [
+            Agent(
+                type: .claudeCode,
+                isEnabled: settings.claudeCodeEnabled,
+                dataDirectory: claudeDirectory,
+                hasSessions: hasEntries(claudeDirectory.appendingPathComponent("projects")),
+                executablePath: executable("claude"),
+                // On macOS Claude Code keeps its token in the Keychain; reading that would prompt.
+                isAuthenticated: fileManager.fileExists(atPath: claudeCredentials) ? true : nil
+            ),
+            Agent(
+                type: .codex,
+                isEnabled: settings.codexEnabled,
+                dataDirectory: codexDirectory,
+                hasSessions: hasEntries(codexDirectory.appendingPathComponent("sessions")),
+                executablePath: executable("codex"),
+                isAuthenticated: fileManager.fileExists(atPath: codexDirectory.appendingPathComponent("auth.json").path)
+            )
+        ]
+        probe.gitPath = ["/opt/homebrew/bin/git", "/usr/local/bin/git", "/Library/Developer/CommandLineTools/usr/bin/git"]
+            .first { fileManager.isExecutableFile(atPath: $0) }
+        return probe
+    }
+
+    /// Suggestions in the order worth doing them; empty when sessions should already show up.
+    var nextSteps: [String] {
+        var steps: [String] = []
+        for agent in agents {
+            let name = agent.type.rawValue
+            if agent.hasSessions {
+                if !agent.isEnabled { steps.append("Turn on \(name) in Settings to see its sessions") }
+                continue
+            }
+            guard agent.isEnabled else { continue }
+            if !agent.isInstalled {
+                switch agent.type {
+                case .claudeCode: steps.append("Install Claude Code: npm install -g @anthropic-ai/claude-code")
+                case .codex: steps.append("Install Codex: npm install -g @openai/codex")
+                }
+            } else if agent.isAuthenticated == false {
+                steps.append("Sign in to \(name): run \(agent.type == .codex ? "codex login" : "claude /login")")
+            } else {
+                steps.append("Run \(agent.type == .codex ? "codex" : "claude") in a project folder to start a session")
+            }
+        }
+        if gitPath == nil {
+            steps.append("Install the command line tools for git: xcode-select --install")
+        }
+        return steps
+    }
+}
+
+/// The checks behind "the app seems stuck" reports: can it read the agents'
+/// data, write its cache, and do its caches still match the files on disk.
+struct HealthReport: Equatable {
+    struct Check: Equatable, Identifiable {
+        let name: String
+        let isHealthy: Bool
+        let detail: String
+
+        var id: String { name }
+    }
+
+    static let lowDiskSpace: Int64 = 1_000_000_000
+
+    var checks: [Check] = []
+    /// Cached results for transcripts that are gone; `SessionStore.repairCaches()` drops them.
+    var orphanedCacheEntries = 0
+
+    var isHealthy: Bool {
+        checks.allSatisfy(\.isHealthy)
+    }
+
+    var canRepair: Bool {
+        orphanedCacheEntries > 0
+    }
+
+    static func run(
+        dataDirectories: [(agent: AgentType, url: URL)],
+        cacheDirectory: URL,
+        cachedPaths: [String],
+        runningSessionPaths: [String],
+        availableCapacity: Int64?,
+        fileManager: FileManager = .default
+    ) -> HealthReport {
+        var report = HealthReport()
+        for (agent, url) in dataDirectories {
+            let readable = fileManager.isReadableFile(atPath: url.path)
+            report.checks.append(Check(
+                name: "\(agent.rawValue) data",
+                isHealthy: readable,
+                detail: readable ? url.path : "Can't read \(url.path)"
+            ))
+        }
+
+        let writable = fileManager.isWritableFile(atPath: cacheDirectory.path)
+        report.checks.append(Check(
+            name: "Cache folder",
+            isHealthy: writable,
+            detail: writable ? "Writable" : "Can't write to \(cacheDirectory.path); costs are recalculated every launch"
+        ))
+
+        if let availableCapacity {
+            let formatted = ByteCountFormatter.string(fromByteCount: availableCapacity, countStyle: .file)
+            report.checks.append(Check(
+                name: "Free disk space",
+                isHealthy: availableCapacity >= lowDiskSpace,
+                detail: availableCapacity >= lowDiskSpace ? "\(formatted) free" : "Only \(formatted) free; agents may fail to write transcripts"
+            ))
+        }
+
+        let vanished = runningSessionPaths.filter { !fileManager.fileExists(atPath: $0) }.count
+        report.checks.append(Check(
+            name: "Running sessions",
+            isHealthy: vanished == 0,
+            detail: vanished == 0 ? "\(runningSessionPaths.count) with transcripts on disk" : "\(vanished) shown as running but their transcript is gone; refresh to clear"
+        ))
+
+        report.orphanedCacheEntries = cachedPaths.filter { !fileManager.fileExists(atPath: $0) }.count
+        report.checks.append(Check(
+            name: "Caches",
+            isHealthy: report.orphanedCacheEntries == 0,
+            detail: report.orphanedCacheEntries == 0 ? "Consistent" : "\(report.orphanedCacheEntries) entries for deleted transcripts"
+        ))
+        return report
+    }
+}
@@ -7,4 +7,11 @@ enum FileUtilities {
         }
         return NSHomeDirectory()
     }
+
+    /// Milliseconds since 1970, or 0 when the file is missing.
+    static func modificationTime(atPath path: String) -> Int64 {
+        guard let attrs = try? FileManager.default.attributesOfItem(atPath: path),
+              let mtime = attrs[.modificationDate] as? Date else { return 0 }
+        return Int64(mtime.timeIntervalSince1970 * 1000)
+    }
 }
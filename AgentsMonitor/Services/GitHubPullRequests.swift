@@ -0,0 +1,160 @@
+import Foundation
+
+enum GitHubError: LocalizedError {
+    case missingToken
+    case noPullRequest
+    case httpError(Int, String)
+
+    var errorDescription: String? {
+        switch self {
+        case .missingToken: return "Add a GitHub token in Settings"
+        case .noPullRequest: return "The session doesn't mention a pull request"
+        case .httpError(let status, let body): return "GitHub returned HTTP \(status)\(body.isEmpty ? "" : ": \(body)")"
+        }
+    }
+}
+
+/// A pull request on github.com, named by a `/owner/repo/pull/N` link.
+struct PullRequestLink: Hashable {
+    let owner: String
+    let repo: String
+    let number: Int
+
+    init(owner: String, repo: String, number: Int) {
+        self.owner = owner
+        self.repo = repo
+        self.number = number
+    }
+
+    init?(url: URL) {
+        guard let host = url.host?.lowercased(), host == "github.com" || host == "www.github.com" else { return nil }
+        let parts = url.pathComponents.filter { $0 != "/" }
+        guard parts.count >= 4, parts[2] == "pull", let number = Int(parts[3]), number > 0 else { return nil }
+        self.init(owner: parts[0], repo: parts[1], number: number)
+    }
+
+    var url: URL { URL(string: "https://github.com/\(owner)/\(repo)/pull/\(number)")! }
+
+    func belongs(to repository: GitHubRepository) -> Bool {
+        owner.lowercased() == repository.owner.lowercased() && repo.lowercased() == repository.repo.lowercased()
+    }
+}
+
+/// A github.com repository, named by a remote URL in any of the forms git
+/// accepts: `git@github.com:owner/repo.git`, `ssh://git@github.com/owner/repo`
+/// or `https://github.com/owner/repo.git`.
+struct GitHubRepository: Hashable {
+    let owner: String
+    let repo: String
+
+    init(owner: String, repo: String) {
+        self.owner = owner
+        self.repo = repo
+    }
+
+    init?(remoteURL: String) {
+        var path: Substring
+        let remote = remoteURL.trimmingCharacters(in: .whitespaces)
+        if let url = URL(string: remote), let host = url.host?.lowercased(), url.scheme != nil {
+            guard host == "github.com" || host == "www.github.com" else { return nil }
+            path = Substring(url.path)
+        } else if let colon = remote.firstIndex(of: ":"), remote[..<colon].lowercased().hasSuffix("github.com") {
+            path = remote[remote.index(after: colon)...]
+        } else {
+            return nil
+        }
+        if path.hasSuffix(".git") { path = path.dropLast(4) }
+        let parts = path.split(separator: "/")
+        guard parts.count == 2 else { return nil }
+        self.init(owner: String(parts[0]), repo: String(parts[1]))
+    }
+
+    /// The `origin` remote of the repository at `projectPath`, read from its
+    /// git config, following a worktree's `.git` file to the shared config.
+    static func origin(ofProject projectPath: String, fileManager: FileManager = .default) -> GitHubRepository? {
+        var gitDirectory = URL(fileURLWithPath: projectPath).appendingPathComponent(".git")
+        var isDirectory: ObjCBool = false
+        guard fileManager.fileExists(atPath: gitDirectory.path, isDirectory: &isDirectory) else { return nil }
+        if !isDirectory.boolValue {
+            guard let pointer = try? String(contentsOf: gitDirectory, encoding: .utf8),
+                  let line = pointer.split(separator: "\n").first(where: { $0.hasPrefix("gitdir:") }) else { return nil }
+            let path = line.dropFirst("gitdir:".count).trimmingCharacters(in: .whitespaces)
+            gitDirectory = URL(fileURLWithPath: path, relativeTo: URL(fileURLWithPath: projectPath, isDirectory: true)).standardizedFileURL
+            if let common = try? String(contentsOf: gitDirectory.appendingPathComponent("commondir"), encoding: .utf8) {
+                gitDirectory = URL(fileURLWithPath: common.trimmingCharacters(in: .whitespacesAndNewlines), relativeTo: gitDirectory.appendingPathComponent("/"))
+                    .standardizedFileURL
+            }
+        }
+        guard let config = try? String(contentsOf: gitDirectory.appendingPathComponent("config"), encoding: .utf8) else { return nil }
+        return originURL(inConfig: config).flatMap(GitHubRepository.init(remoteURL:))
+    }
+
+    static func originURL(inConfig config: String) -> String? {
+        var inOrigin = false
+        for rawLine in config.split(whereSeparator: \.isNewline) {
+            let line = rawLine.trimmingCharacters(in: .whitespaces)
+            if line.hasPrefix("[") {
+                inOrigin = line.replacingOccurrences(of: " ", with: "") == "[remote\"origin\"]"
+            } else if inOrigin, let equals = line.firstIndex(of: "="),
+                      line[..<equals].trimmingCharacters(in: .whitespaces) == "url" {
+                return line[line.index(after: equals)...].trimmingCharacters(in: .whitespaces)
+            }
+        }
+        return nil
+    }
+}
+
+/// Posts session reports as comments on the pull request a session opened
+/// or worked on. Agents read and fetch other people's pull requests too, so
+/// only two kinds of link count: the one `gh pr create` printed, else the
+/// last one into the project's own `origin` repository. The token lives in
+/// the keychain.
+enum GitHubPullRequests {
+    static let keychainAccount = "github"
+
+    static func linkedPullRequest(in transcript: Transcript, projectPath: String?) -> PullRequestLink? {
+        linkedPullRequest(in: transcript, origin: projectPath.flatMap { GitHubRepository.origin(ofProject: $0) })
+    }
+
+    static func linkedPullRequest(in transcript: Transcript, origin: GitHubRepository?) -> PullRequestLink? {
+        let created = transcript.toolCalls.filter { call in
+            DangerousCommandDetector.shellCommand(of: call)?.contains("gh pr create") == true
+        }
+        let printed = created.compactMap(\.output).flatMap(pullRequests(in:))
+        if let last = printed.last { return last }
+        guard let origin else { return nil }
+        return TranscriptSearch.links(in: transcript).compactMap { PullRequestLink(url: $0.url) }.last(where: { $0.belongs(to: origin) })
+    }
+
+    private static let pullRequestURL = try! NSRegularExpression(pattern: #"https://github\.com/[\w.-]+/[\w.-]+/pull/\d+"#)
+
+    private static func pullRequests(in text: String) -> [PullRequestLink] {
+        pullRequestURL.matches(in: text, range: NSRange(text.startIndex..., in: text)).compactMap { match in
+            Range(match.range, in: text).flatMap { URL(string: String(text[$0])) }.flatMap(PullRequestLink.init(url:))
+        }
+    }
+
+    static func commentRequest(body: String, pullRequest: PullRequestLink, token: String) throws -> URLRequest {
+        // Pull request conversation comments go through the issues API.
+        var request = URLRequest(url: URL(string: "https://api.github.com/repos/\(pullRequest.owner)/\(pullRequest.repo)/issues/\(pullRequest.number)/comments")!)
+        request.httpMethod = "POST"
+        request.setValue("Bearer \(token)", forHTTPHeaderField: "Authorization")
+        request.setValue("application/vnd.github+json", forHTTPHeaderField: "Accept")
+        request.setValue("2022-11-28", forHTTPHeaderField: "X-GitHub-Api-Version")
+        request.setValue("application/json", forHTTPHeaderField: "Content-Type")
+        request.timeoutInterval = 15
+        request.httpBody = try JSONSerialization.data(withJSONObject: ["body": body])
+        return request
+    }
+
+    static func postComment(_ body: String, on pullRequest: PullRequestLink) async throws {
+        guard let token = KeychainStore.token(for: keychainAccount), !token.isEmpty else {
+            throw GitHubError.missingToken
+        }
+        let request = try commentRequest(body: body, pullRequest: pullRequest, token: token)
+        let (data, response) = try await URLSession.shared.data(for: request)
+        if let http = response as? HTTPURLResponse, !(200..<300).contains(http.statusCode) {
+            throw GitHubError.httpError(http.statusCode, String(data: data, encoding: .utf8) ?? "")
+        }
+    }
+}
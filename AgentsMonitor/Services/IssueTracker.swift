@@ -0,0 +1,132 @@
+import Foundation
+
+enum IssueTrackerError: LocalizedError {
+    case notConfigured
+    case missingToken(IssueTracker)
+    case invalidBaseURL(String)
+    case httpError(Int, String)
+    case rejected(String)
+
+    var errorDescription: String? {
+        switch self {
+        case .notConfigured: return "Choose an issue tracker in Settings first"
+        case .missingToken(let tracker): return "Add a \(tracker.displayName) API token in Settings"
+        case .invalidBaseURL(let url): return "Not a Jira site URL: \(url)"
+        case .httpError(let status, let body): return "Tracker returned HTTP \(status)\(body.isEmpty ? "" : ": \(body)")"
+        case .rejected(let message): return message
+        }
+    }
+}
+
+/// Posts session reports as comments on the issue a session works on.
+///
+/// A session is tied to an issue key (`ENG-142`): one the user linked by
+/// hand, else the first key in its branch name or first prompt. Tokens live
+/// in the keychain; the Jira site and account email live in settings.
+enum IssueTracker: String, CaseIterable {
+    case linear
+    case jira
+
+    static let linkedKeysDefaultsKey = "sessionIssueKeys"
+
+    private static let keyPattern = try! NSRegularExpression(pattern: #"\b[A-Z][A-Z0-9]{1,9}-[0-9]+\b"#)
+    /// Prefixes that read like issue keys but name standards and versions.
+    private static let nonIssuePrefixes: Set<String> = ["UTF", "SHA", "ISO", "RFC", "CVE", "GPT", "PEP", "TLS", "HTTP", "MP", "ES"]
+
+    var displayName: String {
+        switch self {
+        case .linear: return "Linear"
+        case .jira: return "Jira"
+        }
+    }
+
+    var keychainAccount: String { "issueTracker.\(rawValue)" }
+
+    /// The first issue key in the branch name, else in the prompt. Branches
+    /// are lower-case by habit (`eng-142-fix-login`), so case is ignored
+    /// there; in prose only keys written upper-case count, or every "top-10"
+    /// and "node-18" would read as one.
+    static func detectKey(branch: String?, prompt: String?) -> String? {
+        firstKey(in: branch?.uppercased()) ?? firstKey(in: prompt)
+    }
+
+    private static func firstKey(in text: String?) -> String? {
+        guard let text else { return nil }
+        let range = NSRange(text.startIndex..., in: text)
+        for match in keyPattern.matches(in: text, range: range) {
+            guard let found = Range(match.range, in: text) else { continue }
+            let key = String(text[found])
+            if !nonIssuePrefixes.contains(String(key.prefix { $0 != "-" })) { return key }
+        }
+        return nil
+    }
+
+    static func isValidKey(_ key: String) -> Bool {
+        let range = NSRange(key.startIndex..., in: key)
+        return keyPattern.firstMatch(in: key, range: range)?.range == range
+    }
+
+    // MARK: - Linked Keys
+
+    /// Keys the user linked by hand, by session id.
+    static func loadLinkedKeys(from defaults: UserDefaults = .standard) -> [UUID: String] {
+        let stored = defaults.dictionary(forKey: linkedKeysDefaultsKey) as? [String: String] ?? [:]
+        return stored.reduce(into: [:]) { keys, entry in
+            guard let id = UUID(uuidString: entry.key), isValidKey(entry.value) else { return }
+            keys[id] = entry.value
+        }
+    }
+
+    static func saveLinkedKeys(_ keys: [UUID: String], to defaults: UserDefaults = .standard) {
+        defaults.set(Dictionary(uniqueKeysWithValues: keys.map { ($0.key.uuidString, $0.value) }), forKey: linkedKeysDefaultsKey)
+    }
+
+    // MARK: - Posting
+
+    func commentRequest(body: String, issueKey: String, token: String, settings: AppSettings) throws -> URLRequest {
+        var request: URLRequest
+        let payload: [String: Any]
+        switch self {
+        case .linear:
+            request = URLRequest(url: URL(string: "https://api.linear.app/graphql")!)
+            // Personal API keys go in the header as-is, without "Bearer".
+            request.setValue(token, forHTTPHeaderField: "Authorization")
+            payload = [
+                "query": "mutation($input: CommentCreateInput!) { commentCreate(input: $input) { success } }",
+                "variables": ["input": ["issueId": issueKey, "body": body]]
+            ]
+        case .jira:
+            let site = settings.jiraBaseURL.trimmingCharacters(in: CharacterSet(charactersIn: "/"))
+            guard let url = URL(string: "\(site)/rest/api/2/issue/\(issueKey)/comment"),
+                  url.scheme == "https", url.host?.isEmpty == false else {
+                throw IssueTrackerError.invalidBaseURL(settings.jiraBaseURL)
+            }
+            request = URLRequest(url: url)
+            let credentials = Data("\(settings.jiraEmail):\(token)".utf8).base64EncodedString()
+            request.setValue("Basic \(credentials)", forHTTPHeaderField: "Authorization")
+            payload = ["body": body]
+        }
+        request.httpMethod = "POST"
+        request.setValue("application/json", forHTTPHeaderField: "Content-Type")
+        request.setValue("application/json", forHTTPHeaderField: "Accept")
+        request.timeoutInterval = 15
+        request.httpBody = try JSONSerialization.data(withJSONObject: payload)
+        return request
+    }
+
+    func postComment(_ body: String, issueKey: String, settings: AppSettings) async throws {
+        guard let token = KeychainStore.token(for: keychainAccount), !token.isEmpty else {
+            throw IssueTrackerError.missingToken(self)
+        }
+        let request = try commentRequest(body: body, issueKey: issueKey, token: token, settings: settings)
+        let (data, response) = try await URLSession.shared.data(for: request)
+        if let http = response as? HTTPURLResponse, !(200..<300).contains(http.statusCode) {
+            throw IssueTrackerError.httpError(http.statusCode, String(data: data, encoding: .utf8) ?? "")
+        }
+        // GraphQL reports failures in a 200 response.
+        if self == .linear, let json = try? JSONSerialization.jsonObject(with: data) as? [String: Any],
+           let errors = json["errors"] as? [[String: Any]], let first = errors.first {
+            throw IssueTrackerError.rejected(first["message"] as? String ?? "Linear rejected the comment")
+        }
+    }
+}
@@ -0,0 +1,64 @@
+import Foundation
+import Security
+
+enum KeychainError: LocalizedError {
+    case unexpectedStatus(OSStatus)
+
+    var errorDescription: String? {
+        switch self {
+        case .unexpectedStatus(let status):
+            let message = SecCopyErrorMessageString(status, nil).map { $0 as String } ?? "OSStatus \(status)"
+            return "Keychain error: \(message)"
+        }
+    }
+}
+
+/// API tokens for the integrations, kept as generic passwords in the login
+/// keychain under the app's own service name rather than in `UserDefaults`.
+enum KeychainStore {
+    static let service = "com.agentsmonitor.app"
+
+    static func token(for account: String) -> String? {
+        var query = baseQuery(account: account)
+        query[kSecReturnData as String] = true
+        query[kSecMatchLimit as String] = kSecMatchLimitOne
+        var result: AnyObject?
+        guard SecItemCopyMatching(query as CFDictionary, &result) == errSecSuccess,
+              let data = result as? Data else {
+            return nil
+        }
+        return String(data: data, encoding: .utf8)
+    }
+
+    static func hasToken(for account: String) -> Bool {
+        token(for: account)?.isEmpty == false
+    }
+
+    /// Stores `token`, or removes the entry when it is `nil` or empty.
+    static func setToken(_ token: String?, for account: String) throws {
+        let query = baseQuery(account: account)
+        guard let token, !token.isEmpty else {
+            let status = SecItemDelete(query as CFDictionary)
+            guard status == errSecSuccess || status == errSecItemNotFound else { throw KeychainError.unexpectedStatus(status) }
+            return
+        }
+
+        let data = Data(token.utf8)
+        var status = SecItemUpdate(query as CFDictionary, [kSecValueData as String: data] as CFDictionary)
+        if status == errSecItemNotFound {
+            var item = query
+            item[kSecValueData as String] = data
+            item[kSecAttrAccessible as String] = kSecAttrAccessibleAfterFirstUnlock
+            status = SecItemAdd(item as CFDictionary, nil)
+        }
+        guard status == errSecSuccess else { throw KeychainError.unexpectedStatus(status) }
+    }
+
+    private static func baseQuery(account: String) -> [String: Any] {
+        [
+            kSecClass as String: kSecClassGenericPassword,
+            kSecAttrService as String: service,
+            kSecAttrAccount as String: account
+        ]
+    }
+}
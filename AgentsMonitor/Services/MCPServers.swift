@@ -0,0 +1,101 @@
+import Foundation
+
+/// MCP servers a session could reach and the ones it called.
+///
+/// Both agents expose MCP tools to the model under qualified names:
+/// Claude Code as `mcp__<server>__<tool>`, Codex as `<server>__<tool>` (newer
+/// builds also use the `mcp__` form).
+enum MCPServers {
+    struct ToolUse: Hashable {
+        let server: String
+        let tool: String
+
+        init?(toolName: String, agentType: AgentType) {
+            var name = Substring(toolName)
+            if name.hasPrefix("mcp__") {
+                name = name.dropFirst(5)
+            } else if agentType != .codex {
+                return nil
+            }
+            guard let separator = name.range(of: "__"),
+                  separator.lowerBound > name.startIndex,
+                  separator.upperBound < name.endIndex else {
+                return nil
+            }
+            server = String(name[..<separator.lowerBound])
+            tool = String(name[separator.upperBound...])
+        }
+    }
+
+    /// Servers named by the MCP tools a session called, in order of first use.
+    static func usedServers(toolNames: [String], agentType: AgentType) -> [String] {
+        var servers: [String] = []
+        for name in toolNames {
+            guard let use = ToolUse(toolName: name, agentType: agentType), !servers.contains(use.server) else { continue }
+            servers.append(use.server)
+        }
+        return servers
+    }
+
+    /// Servers the agent's config gives a session in `projectPath`, sorted by name.
+    ///
+    /// Claude Code: `mcpServers` in `~/.claude.json`, globally and under the
+    /// project's entry, plus the project's `.mcp.json` minus servers the user
+    /// turned down. Codex: `[mcp_servers.<name>]` tables in `~/.codex/config.toml`.
+    static func configured(
+        agentType: AgentType,
+        projectPath: String?,
+        home: URL = URL(fileURLWithPath: FileUtilities.realHomeDirectory())
+    ) -> [String] {
+        switch agentType {
+        case .claudeCode:
+            return claudeServers(projectPath: projectPath, home: home)
+        case .codex:
+            return codexServers(home: home)
+        }
+    }
+
+    private static func claudeServers(projectPath: String?, home: URL) -> [String] {
+        var servers = Set<String>()
+        let config = jsonObject(at: home.appendingPathComponent(".claude.json"))
+        servers.formUnion(names(config?["mcpServers"]))
+
+        guard let projectPath else { return servers.sorted() }
+        let project = (config?["projects"] as? [String: Any])?[projectPath] as? [String: Any]
+        servers.formUnion(names(project?["mcpServers"]))
+
+        let shared = jsonObject(at: URL(fileURLWithPath: projectPath).appendingPathComponent(".mcp.json"))
+        let disabled = Set(project?["disabledMcpjsonServers"] as? [String] ?? [])
+        servers.formUnion(names(shared?["mcpServers"]).filter { !disabled.contains($0) })
+        return servers.sorted()
+    }
+
+    private static func codexServers(home: URL) -> [String] {
+        let url = home.appendingPathComponent(".codex/config.toml")
+        guard let content = try? String(contentsOf: url, encoding: .utf8) else { return [] }
+        var servers = Set<String>()
+        for line in content.split(separator: "\n") {
+            let trimmed = line.trimmingCharacters(in: .whitespaces)
+            guard trimmed.hasPrefix("[mcp_servers."), trimmed.hasSuffix("]") else { continue }
+            // `[mcp_servers.github.env]` is a sub-table of `github`.
+            let key = trimmed.dropFirst("[mcp_servers.".count).dropLast()
+            let name: Substring
+            if key.hasPrefix("\""), let close = key.dropFirst().firstIndex(of: "\"") {
+                name = key[key.index(after: key.startIndex)..<close]
+            } else {
+                name = key.prefix { $0 != "." }
+            }
+            if !name.isEmpty { servers.insert(String(name)) }
+        }
+        return servers.sorted()
+    }
+
+    private static func names(_ servers: Any?) -> [String] {
+        (servers as? [String: Any]).map { Array($0.keys) } ?? []
+    }
+
+    private static func jsonObject(at url: URL) -> [String: Any]? {
+        guard let data = try? Data(contentsOf: url) else { return nil }
+        return try? JSONSerialization.jsonObject(with: data) as? [String: Any]
+    }
+}
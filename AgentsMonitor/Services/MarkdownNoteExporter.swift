@@ -0,0 +1,162 @@
+import Foundation
+
+/// Writes a session as a Markdown note with YAML frontmatter, for a notes
+/// vault such as Obsidian: metrics and project details up top where the
+/// vault can query them, then files changed, tool use and the conversation.
+///
+/// Exporting a session again updates its note instead of adding another,
+/// even after the session or the note has been renamed.
+enum MarkdownNoteExporter {
+    static func note(for session: Session, transcript: Transcript, now: Date = Date()) -> String {
+        var lines = ["---"]
+        lines.append("title: \(yaml(session.name))")
+        lines.append("session_id: \(session.id.uuidString.lowercased())")
+        lines.append("agent: \(yaml(session.agentType.rawValue))")
+        lines.append("status: \(yaml(session.status.rawValue))")
+        lines.append("started: \(timestamp(session.startedAt))")
+        if let endedAt = session.endedAt {
+            lines.append("ended: \(timestamp(endedAt))")
+        }
+        lines.append("duration_seconds: \(Int(session.duration(asOf: now)))")
+        if let projectPath = session.projectPath {
+            lines.append("repo: \(yaml(URL(fileURLWithPath: projectPath).lastPathComponent))")
+            lines.append("project_path: \(yaml(projectPath))")
+        }
+        if let branch = session.gitBranch {
+            lines.append("branch: \(yaml(branch))")
+        }
+        if let model = session.metrics.modelIds.first ?? transcript.models.first {
+            lines.append("model: \(yaml(model))")
+        }
+        lines.append("input_tokens: \(session.metrics.inputTokens)")
+        lines.append("output_tokens: \(session.metrics.outputTokens)")
+        lines.append("cost: \(String(format: "%.4f", session.metrics.cost))")
+        lines.append("tool_calls: \(transcript.toolCalls.count)")
+        lines.append("tags:")
+        lines.append(contentsOf: tags(for: session).map { "  - \($0)" })
+        lines.append("---")
+        lines.append("")
+        lines.append("# \(session.name)")
+
+        if let summary = session.sessionSummary, !summary.isEmpty {
+            lines.append("")
+            lines.append(summary)
+        }
+
+        let changes = transcript.lineCountsByFile
+        if !changes.isEmpty {
+            lines.append("")
+            lines.append("## Files changed")
+            lines.append("")
+            for change in changes {
+                lines.append("- `\(session.relativePath(change.path))` +\(change.additions) −\(change.deletions)")
+            }
+        }
+
+        if !transcript.toolCalls.isEmpty {
+            var counts: [String: (calls: Int, failed: Int)] = [:]
+            for call in transcript.toolCalls {
+                let current = counts[call.name] ?? (0, 0)
+                counts[call.name] = (current.calls + 1, current.failed + (call.status == .failed ? 1 : 0))
+            }
+            lines.append("")
+            lines.append("## Tools")
+            lines.append("")
+            lines.append("| Tool | Calls | Failed |")
+            lines.append("| --- | ---: | ---: |")
+            for (name, count) in counts.sorted(by: { $0.value.calls != $1.value.calls ? $0.value.calls > $1.value.calls : $0.key < $1.key }) {
+                lines.append("| \(name) | \(count.calls) | \(count.failed) |")
+            }
+        }
+
+        let conversation = transcript.messages.filter { $0.role == .user || $0.role == .assistant }
+        if !conversation.isEmpty {
+            lines.append("")
+            lines.append("## Conversation")
+            let timeFormatter = DateFormatter()
+            timeFormatter.dateStyle = .none
+            timeFormatter.timeStyle = .short
+            for message in conversation where !message.content.isEmpty {
+                lines.append("")
+                lines.append("### \(message.role.rawValue) · \(timeFormatter.string(from: message.timestamp))")
+                lines.append("")
+                lines.append(message.content)
+            }
+        }
+        return lines.joined(separator: "\n") + "\n"
+    }
+
+    /// `2026-01-01 Fix login tests (1a2b3c4d).md`: sorts by day and stays
+    /// unique per session.
+    static func fileName(for session: Session) -> String {
+        let dayFormatter = DateFormatter()
+        dayFormatter.locale = Locale(identifier: "en_US_POSIX")
+        dayFormatter.dateFormat = "yyyy-MM-dd"
+        let unsafe = CharacterSet(charactersIn: "/\\:*?\"<>|#^[]").union(.newlines).union(.controlCharacters)
+        let title = session.name.components(separatedBy: unsafe).joined(separator: " ")
+            .split(separator: " ").joined(separator: " ")
+            .prefix(80)
+        let shortId = session.id.uuidString.prefix(8).lowercased()
+        return "\(dayFormatter.string(from: session.startedAt)) \(title) (\(shortId)).md"
+    }
+
+    /// The note already written for `session`: a file ending in its short id,
+    /// else one whose frontmatter has its `session_id`.
+    static func existingNote(for session: Session, inFolder folder: URL, fileManager: FileManager = .default) -> URL? {
+        let notes = ((try? fileManager.contentsOfDirectory(at: folder, includingPropertiesForKeys: nil)) ?? [])
+            .filter { $0.pathExtension == "md" }
+            .sorted { $0.lastPathComponent < $1.lastPathComponent }
+        let shortId = session.id.uuidString.prefix(8).lowercased()
+        if let named = notes.first(where: { $0.lastPathComponent.hasSuffix("(\(shortId)).md") }) {
+            return named
+        }
+        let idLine = "session_id: \(session.id.uuidString.lowercased())"
+        return notes.first { url in
+            guard let handle = try? FileHandle(forReadingFrom: url) else { return false }
+            defer { try? handle.close() }
+            let head = (try? handle.read(upToCount: 1024)).map { String(decoding: $0, as: UTF8.self) } ?? ""
+            return head.hasPrefix("---") && head.components(separatedBy: "\n").contains(idLine)
+        }
+    }
+
+    @discardableResult
+    static func write(_ session: Session, transcript: Transcript, toFolder folder: URL, now: Date = Date()) throws -> URL {
+        try FileManager.default.createDirectory(at: folder, withIntermediateDirectories: true)
+        let url = existingNote(for: session, inFolder: folder) ?? folder.appendingPathComponent(fileName(for: session))
+        try note(for: session, transcript: transcript, now: now).write(to: url, atomically: true, encoding: .utf8)
+        return url
+    }
+
+    // MARK: - Formatting
+
+    private static func tags(for session: Session) -> [String] {
+        var tags = ["agents-monitor", "agent/\(slug(session.agentType.rawValue))", "status/\(slug(session.status.rawValue))"]
+        if let projectPath = session.projectPath {
+            tags.append("repo/\(slug(URL(fileURLWithPath: projectPath).lastPathComponent))")
+        }
+        return tags
+    }
+
+    /// Obsidian tags can't hold spaces or most punctuation.
+    private static func slug(_ text: String) -> String {
+        let allowed = CharacterSet.alphanumerics.union(CharacterSet(charactersIn: "-_"))
+        return text.lowercased()
+            .components(separatedBy: allowed.inverted)
+            .filter { !$0.isEmpty }
+            .joined(separator: "-")
+    }
+
+    private static func yaml(_ text: String) -> String {
+        let escaped = text
+            .replacingOccurrences(of: "\\", with: "\\\\")
+            .replacingOccurrences(of: "\"", with: "\\\"")
+            .replacingOccurrences(of: "\n", with: "\\n")
+        return "\"\(escaped)\""
+    }
+
+    private static let formatter = ISO8601DateFormatter()
+
+    private static func timestamp(_ date: Date) -> String {
+        formatter.string(from: date)
+    }
+}
@@ -0,0 +1,240 @@
+import Foundation
+
+enum OTLPExportError: LocalizedError {
+    case invalidEndpoint(String)
+    case httpError(Int, String)
+
+    var errorDescription: String? {
+        switch self {
+        case .invalidEndpoint(let endpoint): return "Not a collector URL: \(endpoint)"
+        case .httpError(let status, let body): return "Collector returned HTTP \(status)\(body.isEmpty ? "" : ": \(body)")"
+        }
+    }
+}
+
+/// Sends sessions to an OpenTelemetry collector as traces, over OTLP/HTTP
+/// with the JSON encoding.
+///
+/// Each session is one trace with a root span; each tool call is a child span,
+/// nested under the Task call that spawned it when a sub-agent made it. Ids
+/// come from the session id and each call's place in the transcript, so
+/// sending a session again reuses them instead of starting a new trace.
+enum OTLPExporter {
+    static let serviceName = "agents-monitor"
+    static let keychainAccount = "otlp.headers"
+    /// Collectors commonly cap a request at a few MB; this keeps a batch of
+    /// busy sessions well under that.
+    static let maxSpansPerRequest = 1000
+
+    /// `http://localhost:4318` and `http://localhost:4318/v1/traces` both
+    /// reach the collector's trace receiver.
+    static func tracesURL(for endpoint: String) -> URL? {
+        let trimmed = endpoint.trimmingCharacters(in: .whitespacesAndNewlines)
+        guard var components = URLComponents(string: trimmed),
+              components.scheme == "http" || components.scheme == "https",
+              components.host?.isEmpty == false else {
+            return nil
+        }
+        if !components.path.hasSuffix("/v1/traces") {
+            components.path = (components.path.hasSuffix("/") ? String(components.path.dropLast()) : components.path) + "/v1/traces"
+        }
+        return components.url
+    }
+
+    /// `OTEL_EXPORTER_OTLP_HEADERS` style `key=value` pairs separated by
+    /// commas, or a bare token, sent as `Authorization: Bearer <token>`.
+    static func headers(from text: String) -> [String: String] {
+        let trimmed = text.trimmingCharacters(in: .whitespacesAndNewlines)
+        guard !trimmed.isEmpty else { return [:] }
+        guard trimmed.contains("=") else { return ["Authorization": "Bearer \(trimmed)"] }
+        var headers: [String: String] = [:]
+        for pair in trimmed.split(separator: ",") {
+            let parts = pair.split(separator: "=", maxSplits: 1)
+            guard parts.count == 2 else { continue }
+            let name = parts[0].trimmingCharacters(in: .whitespaces)
+            let value = parts[1].trimmingCharacters(in: .whitespaces)
+            guard !name.isEmpty else { continue }
+            headers[name] = value.removingPercentEncoding ?? value
+        }
+        return headers
+    }
+
+    /// Splits `sessions` into runs of at most `maxSpans` spans each. A session
+    /// is never split, so one with more calls than that goes on its own.
+    static func batches(
+        of sessions: [(session: Session, transcript: Transcript)],
+        maxSpans: Int = maxSpansPerRequest
+    ) -> [[(session: Session, transcript: Transcript)]] {
+        var batches: [[(session: Session, transcript: Transcript)]] = []
+        var current: [(session: Session, transcript: Transcript)] = []
+        var spanCount = 0
+        for item in sessions {
+            let count = 1 + item.transcript.toolCalls.count
+            if !current.isEmpty, spanCount + count > maxSpans {
+                batches.append(current)
+                current = []
+                spanCount = 0
+            }
+            current.append(item)
+            spanCount += count
+        }
+        if !current.isEmpty { batches.append(current) }
+        return batches
+    }
+
+    static func payload(for sessions: [(session: Session, transcript: Transcript)], now: Date = Date()) -> [String: Any] {
+        [
+            "resourceSpans": [[
+                "resource": ["attributes": attributes(["service.name": serviceName])],
+                "scopeSpans": [[
+                    "scope": ["name": serviceName],
+                    "spans": sessions.flatMap { spans(for: $0.session, transcript: $0.transcript, now: now) }
+                ]]
+            ]]
+        ]
+    }
+
+    static func send(_ payload: [String: Any], to endpoint: String, headers: [String: String] = [:]) async throws {
+        guard let url = tracesURL(for: endpoint) else { throw OTLPExportError.invalidEndpoint(endpoint) }
+        var request = URLRequest(url: url)
+        request.httpMethod = "POST"
+        for (name, value) in headers {
+            request.setValue(value, forHTTPHeaderField: name)
+        }
+        request.setValue("application/json", forHTTPHeaderField: "Content-Type")
+        request.httpBody = try JSONSerialization.data(withJSONObject: payload)
+        request.timeoutInterval = 30
+
+        let (data, response) = try await URLSession.shared.data(for: request)
+        if let http = response as? HTTPURLResponse, !(200..<300).contains(http.statusCode) {
+            throw OTLPExportError.httpError(http.statusCode, String(data: data, encoding: .utf8) ?? "")
+        }
+    }
+
+    // MARK: - Mapping
+
+    private static func spans(for session: Session, transcript: Transcript, now: Date) -> [[String: Any]] {
+        let traceId = hex(session.id)
+        let rootId = spanId(session.id)
+        let end = session.endedAt ?? transcript.toolCalls.compactMap(\.completedAt).max() ?? now
+
+        var sessionAttributes: [String: Any] = [
+            "agent.type": session.agentType.rawValue,
+            "session.name": session.name,
+            "session.status": session.status.rawValue,
+            "gen_ai.usage.input_tokens": session.metrics.inputTokens,
+            "gen_ai.usage.output_tokens": session.metrics.outputTokens,
+            "gen_ai.usage.cache_read_tokens": session.metrics.cacheReadTokens,
+            "gen_ai.usage.cache_write_tokens": session.metrics.cacheWriteTokens,
+            "gen_ai.usage.cost_usd": session.metrics.cost,
+            "tool_call.count": transcript.toolCalls.count
+        ]
+        if let model = session.metrics.modelIds.first ?? transcript.models.first {
+            sessionAttributes["gen_ai.request.model"] = model
+        }
+        if let projectPath = session.projectPath { sessionAttributes["project.path"] = projectPath }
+        if let branch = session.gitBranch { sessionAttributes["vcs.branch"] = branch }
+
+        var spans: [[String: Any]] = [span(
+            traceId: traceId,
+            spanId: rootId,
+            parentSpanId: nil,
+            name: session.name,
+            start: session.startedAt,
+            end: end,
+            failed: session.status == .failed,
+            attributes: sessionAttributes
+        )]
+
+        var callSpanIds: [UUID: String] = [:]
+        for (index, call) in transcript.toolCalls.enumerated() where callSpanIds[call.id] == nil {
+            callSpanIds[call.id] = spanId(session.id, index: index)
+        }
+        var parents: [UUID: String] = [:]
+        for (taskId, callIds) in transcript.subagentToolCallIds {
+            for callId in callIds { parents[callId] = callSpanIds[taskId] }
+        }
+        for call in transcript.toolCalls {
+            var callAttributes: [String: Any] = [
+                "tool.name": call.name,
+                "tool.status": call.status.rawValue
+            ]
+            if let error = call.error { callAttributes["tool.error"] = error }
+            spans.append(span(
+                traceId: traceId,
+                spanId: callSpanIds[call.id] ?? rootId,
+                parentSpanId: parents[call.id] ?? rootId,
+                name: call.name,
+                start: call.startedAt,
+                end: call.completedAt ?? end,
+                failed: call.status == .failed,
+                attributes: callAttributes
+            ))
+        }
+        return spans
+    }
+
+    private static func span(
+        traceId: String,
+        spanId: String,
+        parentSpanId: String?,
+        name: String,
+        start: Date,
+        end: Date,
+        failed: Bool,
+        attributes values: [String: Any]
+    ) -> [String: Any] {
+        var span: [String: Any] = [
+            "traceId": traceId,
+            "spanId": spanId,
+            "name": name,
+            // SPAN_KIND_INTERNAL
+            "kind": 1,
+            "startTimeUnixNano": nanoseconds(start),
+            "endTimeUnixNano": nanoseconds(max(start, end)),
+            "attributes": attributes(values),
+            // STATUS_CODE_ERROR / STATUS_CODE_OK
+            "status": ["code": failed ? 2 : 1]
+        ]
+        if let parentSpanId { span["parentSpanId"] = parentSpanId }
+        return span
+    }
+
+    /// OTLP's `KeyValue` list, with values wrapped by type.
+    private static func attributes(_ values: [String: Any]) -> [[String: Any]] {
+        values.keys.sorted().compactMap { key in
+            let value: [String: Any]
+            switch values[key] {
+            case let bool as Bool: value = ["boolValue": bool]
+            case let int as Int: value = ["intValue": String(int)]
+            case let double as Double: value = ["doubleValue": double]
+            case let string as String: value = ["stringValue": string]
+            default: return nil
+            }
+            return ["key": key, "value": value]
+        }
+    }
+
+    /// 64-bit integers travel as strings in OTLP JSON.
+    private static func nanoseconds(_ date: Date) -> String {
+        String(UInt64(max(date.timeIntervalSince1970, 0) * 1_000_000_000))
+    }
+
+    private static func hex(_ id: UUID) -> String {
+        id.uuidString.replacingOccurrences(of: "-", with: "").lowercased()
+    }
+
+    private static func spanId(_ id: UUID) -> String {
+        String(hex(id).prefix(16))
+    }
+
+    /// FNV-1a over the session id and call index: stable across launches,
+    /// unlike `Hasher`.
+    private static func spanId(_ sessionId: UUID, index: Int) -> String {
+        var hash: UInt64 = 0xcbf2_9ce4_8422_2325
+        for byte in "\(sessionId.uuidString)#\(index)".utf8 {
+            hash = (hash ^ UInt64(byte)) &* 0x0000_0100_0000_01b3
+        }
+        return String(format: "%016llx", hash)
+    }
+}
@@ -0,0 +1,85 @@
+import Foundation
+
+/// How much a running agent may do without asking.
+enum OperatingMode: String, Hashable {
+    /// Claude Code plan mode: read-only until the user accepts a plan.
+    case plan
+    /// Asks before edits and commands.
+    case ask
+    /// Claude Code's auto-accept edits; Codex approval policies that don't ask
+    /// inside the sandbox.
+    case autoEdit
+    /// Claude Code's bypass permissions; Codex with no approvals and no sandbox.
+    case fullAuto
+
+    /// The agent can change things right now without anyone approving it.
+    var isAutonomous: Bool {
+        self == .autoEdit || self == .fullAuto
+    }
+
+    var label: String {
+        switch self {
+        case .plan: return "Plan mode"
+        case .ask: return "Asks before acting"
+        case .autoEdit: return "Auto-accepting edits"
+        case .fullAuto: return "Running without approvals"
+        }
+    }
+
+    var icon: String {
+        switch self {
+        case .plan: return "list.bullet.clipboard"
+        case .ask: return "hand.raised"
+        case .autoEdit: return "bolt"
+        case .fullAuto: return "bolt.trianglebadge.exclamationmark"
+        }
+    }
+}
+
+/// Reads a running session's operating mode from the end of its transcript.
+///
+/// Claude Code stamps `permissionMode` on the user lines it writes; the last
+/// one seen wins. Codex writes its approval and sandbox policies in every
+/// `turn_context`.
+enum OperatingModeDetector {
+    static func mode(in content: String, agentType: AgentType) -> OperatingMode? {
+        for line in content.split(separator: "\n").reversed() {
+            guard let lineData = line.data(using: .utf8),
+                  let json = try? JSONSerialization.jsonObject(with: lineData) as? [String: Any] else {
+                continue
+            }
+            let found = agentType == .codex ? codexMode(json) : claudeMode(json)
+            if let found { return found }
+        }
+        return nil
+    }
+
+    private static func claudeMode(_ json: [String: Any]) -> OperatingMode? {
+        switch json["permissionMode"] as? String {
+        case "plan": return .plan
+        case "default": return .ask
+        case "acceptEdits": return .autoEdit
+        case "bypassPermissions": return .fullAuto
+        default: return nil
+        }
+    }
+
+    private static func codexMode(_ json: [String: Any]) -> OperatingMode? {
+        guard json["type"] as? String == "turn_context",
+              let payload = json["payload"] as? [String: Any],
+              let approval = payload["approval_policy"] as? String else {
+            return nil
+        }
+        // Older rollouts write the sandbox as a bare string.
+        let sandbox = (payload["sandbox_policy"] as? [String: Any])?["mode"] as? String
+            ?? payload["sandbox_policy"] as? String
+        switch approval {
+        case "never":
+            return sandbox == "danger-full-access" ? .fullAuto : .autoEdit
+        case "on-failure":
+            return .autoEdit
+        default:
+            return .ask
+        }
+    }
+}
@@ -0,0 +1,35 @@
+import Foundation
+
+/// Rewrites machine-specific details in exported text: the home folder
+/// becomes `~`, other `/Users/<name>` and `/home/<name>` paths lose the
+/// account name, and stray mentions of the user name become `<user>`.
+struct PathAnonymizer {
+    static let userPlaceholder = "<user>"
+
+    let homeDirectory: String
+    let userName: String
+
+    init(homeDirectory: String = FileUtilities.realHomeDirectory(), userName: String = NSUserName()) {
+        self.homeDirectory = homeDirectory.hasSuffix("/") ? String(homeDirectory.dropLast()) : homeDirectory
+        self.userName = userName
+    }
+
+    func anonymize(_ text: String) -> String {
+        var result = text
+        if !homeDirectory.isEmpty && homeDirectory != "/" {
+            result = replacing(NSRegularExpression.escapedPattern(for: homeDirectory) + #"(?=/|\b|$)"#, in: result, with: "~")
+        }
+        result = replacing(#"/(Users|home)/[^/\s"'`]+"#, in: result, with: "/$1/" + Self.userPlaceholder)
+        // Very short names would match inside ordinary words.
+        if userName.count >= 3 {
+            result = replacing(#"\b"# + NSRegularExpression.escapedPattern(for: userName) + #"\b"#, in: result, with: Self.userPlaceholder)
+        }
+        return result
+    }
+
+    private func replacing(_ pattern: String, in text: String, with template: String) -> String {
+        guard let expression = try? NSRegularExpression(pattern: pattern) else { return text }
+        let range = NSRange(text.startIndex..., in: text)
+        return expression.stringByReplacingMatches(in: text, range: range, withTemplate: template)
+    }
+}
@@ -0,0 +1,99 @@
+import Foundation
+
+/// A tool use the agent issued that has no result yet.
+struct PermissionRequest: Hashable {
+    let toolUseId: String
+    let toolName: String
+    let input: String
+    let requestedAt: Date?
+}
+
+/// Spots Claude Code sessions that are most likely sitting at a permission
+/// prompt: the transcript ends in a tool use that needs approval, no tool
+/// result has been written for it, and the file has been quiet for a few
+/// seconds. A slow command that was already approved looks the same, so the
+/// result is a hint, not a certainty.
+enum PermissionRequestDetector {
+    /// Tools Claude Code asks about by default; read-only tools are auto-approved.
+    static let approvalTools: Set<String> = ["Bash", "Edit", "MultiEdit", "Write", "NotebookEdit", "WebFetch"]
+    static let quietInterval: TimeInterval = 3
+    private static let tailBytes: UInt64 = 256 * 1024
+
+    static func pendingRequest(jsonlPath: String, modifiedAt: Date, now: Date = Date()) -> PermissionRequest? {
+        guard now.timeIntervalSince(modifiedAt) >= quietInterval,
+              let content = readTail(of: jsonlPath) else {
+            return nil
+        }
+        return pendingRequest(in: content)
+    }
+
+    static func pendingRequest(in content: String) -> PermissionRequest? {
+        var pending: [PermissionRequest] = []
+        var resolved = Set<String>()
+
+        for line in content.split(separator: "\n") {
+            guard let lineData = line.data(using: .utf8),
+                  let json = try? JSONSerialization.jsonObject(with: lineData) as? [String: Any],
+                  let type = json["type"] as? String,
+                  let message = json["message"] as? [String: Any],
+                  let blocks = message["content"] as? [[String: Any]] else {
+                continue
+            }
+            let timestamp = (json["timestamp"] as? String).flatMap(parseISO8601)
+
+            for block in blocks {
+                switch (type, block["type"] as? String) {
+                case ("assistant", "tool_use"):
+                    guard let id = block["id"] as? String, let name = block["name"] as? String else { continue }
+                    pending.append(PermissionRequest(
+                        toolUseId: id,
+                        toolName: name,
+                        input: summarize(input: block["input"] as? [String: Any] ?? [:]),
+                        requestedAt: timestamp
+                    ))
+                case ("user", "tool_result"):
+                    if let id = block["tool_use_id"] as? String { resolved.insert(id) }
+                default:
+                    continue
+                }
+            }
+        }
+
+        guard let last = pending.last(where: { !resolved.contains($0.toolUseId) }),
+              approvalTools.contains(last.toolName) || last.toolName.hasPrefix("mcp__") else {
+            return nil
+        }
+        return last
+    }
+
+    /// The one input field a person needs to decide: the command, the file or the URL.
+    private static func summarize(input: [String: Any]) -> String {
+        for key in ["command", "file_path", "notebook_path", "url"] {
+            if let value = input[key] as? String { return value }
+        }
+        guard let data = try? JSONSerialization.data(withJSONObject: input, options: [.sortedKeys]) else { return "" }
+        return String(decoding: data, as: UTF8.self)
+    }
+
+    static func readTail(of path: String) -> String? {
+        guard let handle = FileHandle(forReadingAtPath: path) else { return nil }
+        defer { try? handle.close() }
+        do {
+            let size = try handle.seekToEnd()
+            try handle.seek(toOffset: size > tailBytes ? size - tailBytes : 0)
+            guard let data = try handle.readToEnd() else { return nil }
+            // The first line may be cut mid-record; JSON parsing skips it.
+            return String(decoding: data, as: UTF8.self)
+        } catch {
+            AppLogger.logWarning("Cannot read tail of \(path): \(error.localizedDescription)", context: "PermissionRequestDetector")
+            return nil
+        }
+    }
+
+    private static func parseISO8601(_ string: String) -> Date? {
+        let fractional = ISO8601DateFormatter()
+        fractional.formatOptions = [.withInternetDateTime, .withFractionalSeconds]
+        if let date = fractional.date(from: string) { return date }
+        return ISO8601DateFormatter().date(from: string)
+    }
+}
@@ -0,0 +1,89 @@
+import Foundation
+
+/// Where a usage-limit window stands and, when it is filling up, when it will
+/// run out at the current pace.
+struct QuotaStatus: Equatable, Identifiable {
+    let provider: AgentType
+    /// The window's label as shown in the popover, e.g. "5-hour".
+    let window: String
+    let utilization: Double
+    let resetsAt: Date?
+    /// Utilization gained per hour over the recent samples; `nil` until there
+    /// is enough history or while usage is flat.
+    let ratePerHour: Double?
+    let projectedLimitAt: Date?
+
+    var id: String { "\(provider.rawValue).\(window)" }
+
+    /// The limit arrives before the window resets, so the user will be cut off.
+    var willHitLimitBeforeReset: Bool {
+        guard let projectedLimitAt else { return false }
+        guard let resetsAt else { return true }
+        return projectedLimitAt < resetsAt
+    }
+}
+
+/// Keeps recent utilization samples per limit window and projects when each
+/// reaches 100%.
+struct QuotaTracker {
+    /// Pace is measured over at most this much history.
+    static let sampleHorizon: TimeInterval = 3600
+    /// Shorter spans are too noisy to extrapolate from.
+    static let minimumSpan: TimeInterval = 300
+
+    private struct Sample {
+        let date: Date
+        let utilization: Double
+    }
+
+    private var samples: [String: [Sample]] = [:]
+
+    mutating func record(
+        _ window: AnthropicUsage.UsageWindow,
+        provider: AgentType,
+        label: String,
+        at date: Date = Date()
+    ) -> QuotaStatus {
+        let key = "\(provider.rawValue).\(label)"
+        var history = samples[key] ?? []
+        // A drop means the window reset; earlier samples describe the old one.
+        if let last = history.last, window.utilization < last.utilization {
+            history.removeAll()
+        }
+        history.append(Sample(date: date, utilization: window.utilization))
+        history.removeAll { date.timeIntervalSince($0.date) > Self.sampleHorizon }
+        samples[key] = history
+
+        var rate: Double?
+        var projected: Date?
+        if let first = history.first {
+            let span = date.timeIntervalSince(first.date)
+            let gained = window.utilization - first.utilization
+            if span >= Self.minimumSpan, gained > 0 {
+                let perSecond = gained / span
+                rate = perSecond * 3600
+                projected = date.addingTimeInterval(max(1 - window.utilization, 0) / perSecond)
+            }
+        }
+
+        return QuotaStatus(
+            provider: provider,
+            window: label,
+            utilization: window.utilization,
+            resetsAt: window.resetsAt.flatMap(Self.parseISO8601),
+            ratePerHour: rate,
+            projectedLimitAt: projected
+        )
+    }
+
+    mutating func reset() {
+        samples.removeAll()
+    }
+
+    private static func parseISO8601(_ string: String) -> Date? {
+        let fractional = ISO8601DateFormatter()
+        fractional.formatOptions = [.withInternetDateTime, .withFractionalSeconds]
+        if let date = fractional.date(from: string) { return date }
+        return ISO8601DateFormatter().date(from: string)
+    }
+}
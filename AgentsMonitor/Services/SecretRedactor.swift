@@ -0,0 +1,95 @@
+import Foundation
+
+/// Masks credentials in transcript text before the app shows or writes it.
+///
+/// The built-in patterns cover provider API keys, AWS access keys and secrets,
+/// bearer/basic `Authorization:` headers and PEM private keys. Extra regular
+/// expressions come from the `redactionPatterns` setting; invalid ones are logged
+/// and skipped.
+struct SecretRedactor {
+    static let placeholder = "[REDACTED]"
+
+    static let builtInPatterns: [String] = [
+        // Anthropic, OpenAI and similar `sk-` keys
+        #"\bsk-(?:ant-)?[A-Za-z0-9_\-]{20,}"#,
+        // GitHub tokens
+        #"\bgh[pousr]_[A-Za-z0-9]{36,}"#,
+        // AWS access key IDs
+        #"\b(?:AKIA|ASIA)[0-9A-Z]{16}\b"#,
+        // AWS secret access keys assigned in env files or CLI config
+        #"(?i)aws_secret_access_key\s*[=:]\s*["']?[A-Za-z0-9/+=]{40}["']?"#,
+        // Authorization headers
+        #"(?i)\bauthorization\s*:\s*(?:bearer|basic|token)?\s*[^\s"']+"#,
+        // PEM private keys
+        #"-----BEGIN [A-Z ]*PRIVATE KEY-----[\s\S]*?-----END [A-Z ]*PRIVATE KEY-----"#
+    ]
+
+    private let expressions: [NSRegularExpression]
+
+    init(customPatterns: [String] = []) {
+        var expressions: [NSRegularExpression] = []
+        for pattern in Self.builtInPatterns + customPatterns where !pattern.isEmpty {
+            do {
+                expressions.append(try NSRegularExpression(pattern: pattern))
+            } catch {
+                AppLogger.logWarning("Ignoring invalid redaction pattern: \(pattern)", context: "SecretRedactor")
+            }
+        }
+        self.expressions = expressions
+    }
+
+    func redact(_ text: String) -> String {
+        var result = text
+        for expression in expressions {
+            let range = NSRange(result.startIndex..., in: result)
+            result = expression.stringByReplacingMatches(
+                in: result,
+                range: range,
+                withTemplate: NSRegularExpression.escapedTemplate(for: Self.placeholder)
+            )
+        }
+        return result
+    }
+
+    func redact(_ text: String?) -> String? {
+        text.map { redact($0) }
+    }
+
+    /// Returns `session` with every free-text field passed through `redact(_:)`.
+    func redacted(_ session: Session) -> Session {
+        var copy = session
+        copy.name = redact(session.name)
+        copy.firstPrompt = redact(session.firstPrompt)
+        copy.sessionSummary = redact(session.sessionSummary)
+        copy.errorMessage = redact(session.errorMessage)
+        copy.messages = redacted(Transcript(messages: session.messages)).messages
+        copy.toolCalls = redacted(Transcript(toolCalls: session.toolCalls)).toolCalls
+        if let output = session.terminalOutput, let text = String(data: output, encoding: .utf8) {
+            copy.terminalOutput = Data(redact(text).utf8)
+        }
+        return copy
+    }
+
+    func redacted(_ command: FlaggedCommand) -> FlaggedCommand {
+        FlaggedCommand(rule: command.rule, command: redact(command.command), timestamp: command.timestamp)
+    }
+
+    func redacted(_ transcript: Transcript) -> Transcript {
+        var copy = transcript
+        for i in copy.messages.indices {
+            copy.messages[i].content = redact(copy.messages[i].content)
+            for j in copy.messages[i].codeBlocks.indices {
+                copy.messages[i].codeBlocks[j].content = redact(copy.messages[i].codeBlocks[j].content)
+            }
+        }
+        for i in copy.toolCalls.indices {
+            copy.toolCalls[i].input = redact(copy.toolCalls[i].input)
+            copy.toolCalls[i].output = redact(copy.toolCalls[i].output)
+            copy.toolCalls[i].error = redact(copy.toolCalls[i].error)
+            if copy.toolCalls[i].diff != nil {
+                copy.toolCalls[i].diff = FileDiff(toolName: copy.toolCalls[i].name, input: copy.toolCalls[i].input)
+            }
+        }
+        return copy
+    }
+}
@@ -0,0 +1,293 @@
+import Foundation
+
+/// Session time by weekday and hour of day, to show when agents actually
+/// run. Each interval is split across the hour slots it covers.
+struct ActivityHeatmap: Equatable {
+    /// `seconds[weekday][hour]`, weekday 0 being Sunday as in `Calendar`.
+    private(set) var seconds = Array(repeating: Array(repeating: 0.0, count: 24), count: 7)
+
+    init() {}
+
+    /// Pass `range` to count only the part of each interval inside it.
+    init(intervals: [DateInterval], range: DateInterval? = nil, calendar: Calendar = .current) {
+        for interval in intervals {
+            guard let clipped = range == nil ? interval : range.flatMap(interval.intersection),
+                  clipped.duration > 0 else { continue }
+            var cursor = clipped.start
+            while cursor < clipped.end {
+                guard let slot = calendar.dateInterval(of: .hour, for: cursor) else { break }
+                let end = min(slot.end, clipped.end)
+                let weekday = calendar.component(.weekday, from: cursor) - 1
+                seconds[weekday][calendar.component(.hour, from: cursor)] += end.timeIntervalSince(cursor)
+                cursor = end
+            }
+        }
+    }
+
+    var peak: TimeInterval { seconds.joined().max() ?? 0 }
+
+    var isEmpty: Bool { peak == 0 }
+
+    /// The weekday and hour with the most session time.
+    var busiestSlot: (weekday: Int, hour: Int)? {
+        guard !isEmpty else { return nil }
+        var best = (weekday: 0, hour: 0)
+        for weekday in 0..<7 {
+            for hour in 0..<24 where seconds[weekday][hour] > seconds[best.weekday][best.hour] {
+                best = (weekday, hour)
+            }
+        }
+        return best
+    }
+}
+
+/// A file agents keep editing, across the sessions of one project.
+struct FileHotspot: Hashable, Identifiable {
+    let projectPath: String?
+    /// Relative to the project when the file is inside it.
+    let path: String
+    var edits = 0
+    /// Edits the tool rejected or that failed to apply.
+    var failedEdits = 0
+    var sessions = 0
+    /// Sessions that touched the file and ended failed.
+    var failedSessions = 0
+    var additions = 0
+    var deletions = 0
+
+    var id: String { "\(projectPath ?? "")|\(path)" }
+
+    /// Files with the most edits first. Pass `projectPath` to look at one
+    /// project only.
+    static func rank(
+        _ batch: [(session: Session, transcript: Transcript)],
+        projectPath: String? = nil,
+        limit: Int = 10
+    ) -> [FileHotspot] {
+        var hotspots: [String: FileHotspot] = [:]
+        for (session, transcript) in batch where projectPath == nil || session.projectPath == projectPath {
+            var touched = Set<String>()
+            for call in transcript.toolCalls {
+                guard let diff = call.diff else { continue }
+                var hotspot = FileHotspot(projectPath: session.projectPath, path: session.relativePath(diff.filePath))
+                hotspot = hotspots[hotspot.id] ?? hotspot
+                hotspot.edits += 1
+                if call.status == .failed {
+                    hotspot.failedEdits += 1
+                } else {
+                    hotspot.additions += diff.additions
+                    hotspot.deletions += diff.deletions
+                }
+                if touched.insert(hotspot.id).inserted {
+                    hotspot.sessions += 1
+                    if session.status == .failed { hotspot.failedSessions += 1 }
+                }
+                hotspots[hotspot.id] = hotspot
+            }
+        }
+        return Array(hotspots.values
+            .sorted { $0.edits != $1.edits ? $0.edits > $1.edits : $0.path < $1.path }
+            .prefix(limit))
+    }
+}
+
+/// Failures that share a signature across sessions: the same error once
+/// paths, numbers, ids and quoted values are taken out. A signature that
+/// turns up in many sessions points at a systemic problem, like an expired
+/// token or a flaky test, rather than one bad run.
+struct FailureCluster: Hashable, Identifiable {
+    let signature: String
+    /// The first line of the first failure seen with this signature.
+    let example: String
+    var occurrences = 0
+    var sessionIds: Set<UUID> = []
+    var tools: Set<String> = []
+    var lastSeen: Date = .distantPast
+
+    var id: String { signature }
+
+    /// Tool results that are the user declining, not something breaking.
+    private static let declined = ["The user doesn't want to proceed", "[Request interrupted by user"]
+
+    /// Lines every failure of a kind starts with, which say nothing about
+    /// what broke: Claude Code's `Exit code 1` header on Bash results, and the
+    /// like.
+    private static let boilerplate = try! NSRegularExpression(
+        pattern: #"^(?:(?:command failed with )?exit (?:code|status):? ?-?\d+\.?|process exited with code -?\d+\.?|error:?|stderr:?|traceback \(most recent call last\):|\$ .*)$"#,
+        options: .caseInsensitive
+    )
+
+    private static let replacements: [(NSRegularExpression, String)] = [
+        (#"'[^']*'|"[^"]*"|`[^`]*`"#, "<str>"),
+        (#"(?:~|\.{1,2})?(?:/[\w.@+-]+)+/?"#, "<path>"),
+        (#"\b[0-9a-fA-F]{8}(?:-[0-9a-fA-F]{4}){3}-[0-9a-fA-F]{12}\b|\b[0-9a-f]{12,}\b"#, "<id>"),
+        // Three-digit 1xx-5xx numbers stay: they are usually HTTP statuses.
+        (#"\b(?![1-5]\d\d\b)\d+(?:\.\d+)*\b"#, "<n>"),
+        (#"\s+"#, " ")
+    ].map { (try! NSRegularExpression(pattern: $0.0), $0.1) }
+
+    /// Most sessions first, then most occurrences.
+    static func group(_ batch: [(session: Session, transcript: Transcript)], limit: Int = 10) -> [FailureCluster] {
+        var clusters: [String: FailureCluster] = [:]
+        func add(_ text: String, tool: String?, session: Session, at date: Date) {
+            guard let line = errorLine(in: text), !declined.contains(where: { line.hasPrefix($0) }) else { return }
+            let key = signature(of: line)
+            var cluster = clusters[key] ?? FailureCluster(signature: key, example: String(line.prefix(200)))
+            cluster.occurrences += 1
+            cluster.sessionIds.insert(session.id)
+            if let tool { cluster.tools.insert(tool) }
+            cluster.lastSeen = max(cluster.lastSeen, date)
+            clusters[key] = cluster
+        }
+
+        for (session, transcript) in batch {
+            for call in transcript.toolCalls where call.status == .failed {
+                add(call.error ?? call.output ?? "", tool: call.name, session: session, at: call.completedAt ?? call.startedAt)
+            }
+            for message in transcript.messages where message.role == .assistant && message.content.hasPrefix("API Error") {
+                add(message.content, tool: nil, session: session, at: message.timestamp)
+            }
+        }
+        return Array(clusters.values
+            .sorted {
+                if $0.sessionIds.count != $1.sessionIds.count { return $0.sessionIds.count > $1.sessionIds.count }
+                if $0.occurrences != $1.occurrences { return $0.occurrences > $1.occurrences }
+                return $0.signature < $1.signature
+            }
+            .prefix(limit))
+    }
+
+    /// The first line that says what went wrong. A Python traceback names
+    /// the error on its last line instead.
+    static func errorLine(in text: String) -> String? {
+        let lines = text.split(whereSeparator: \.isNewline)
+            .map { $0.replacingOccurrences(of: "</?tool_use_error>", with: "", options: .regularExpression).trimmingCharacters(in: .whitespaces) }
+            .filter { !$0.isEmpty }
+        let meaningful = lines.filter { line in
+            boilerplate.firstMatch(in: line, range: NSRange(line.startIndex..., in: line)) == nil
+        }
+        if lines.contains(where: { $0.hasPrefix("Traceback (most recent call last)") }),
+           let raised = meaningful.last(where: { !$0.hasPrefix("File \"") }) {
+            return raised
+        }
+        return meaningful.first ?? lines.first
+    }
+
+    static func signature(of line: String) -> String {
+        var text = String(line.prefix(300))
+        for (pattern, template) in replacements {
+            text = pattern.stringByReplacingMatches(in: text, range: NSRange(text.startIndex..., in: text), withTemplate: template)
+        }
+        return text.trimmingCharacters(in: .whitespaces).lowercased()
+    }
+}
+
+/// Derived measures for comparing agents and prompting styles on output
+/// per token and dollar. Lines changed stand in for committed lines, which
+/// transcripts don't record.
+struct EfficiencyMetrics: Equatable {
+    var sessions = 0
+    var filesChanged = 0
+    var linesChanged = 0
+    var totalTokens = 0
+    var cost = 0.0
+    /// Failed tool calls the agent followed with another call to the same tool.
+    var retriesByTool: [String: Int] = [:]
+
+    init() {}
+
+    init(session: Session, transcript: Transcript) {
+        let changes = transcript.lineCountsByFile
+        sessions = 1
+        filesChanged = changes.count
+        linesChanged = changes.reduce(0) { $0 + $1.additions + $1.deletions }
+        totalTokens = session.metrics.totalTokens
+        cost = session.metrics.cost
+        var failedTools = Set<String>()
+        for call in transcript.toolCalls {
+            if failedTools.remove(call.name) != nil {
+                retriesByTool[call.name, default: 0] += 1
+            }
+            if call.status == .failed { failedTools.insert(call.name) }
+        }
+    }
+
+    var tokensPerFile: Double? {
+        filesChanged > 0 ? Double(totalTokens) / Double(filesChanged) : nil
+    }
+
+    var costPerChangedLine: Double? {
+        linesChanged > 0 ? cost / Double(linesChanged) : nil
+    }
+
+    var retries: Int { retriesByTool.values.reduce(0, +) }
+
+    /// Totals across sessions, so the ratios weight each session by its size.
+    static func combined(_ metrics: [EfficiencyMetrics]) -> EfficiencyMetrics {
+        metrics.reduce(into: EfficiencyMetrics()) { total, next in
+            total.sessions += next.sessions
+            total.filesChanged += next.filesChanged
+            total.linesChanged += next.linesChanged
+            total.totalTokens += next.totalTokens
+            total.cost += next.cost
+            total.retriesByTool.merge(next.retriesByTool, uniquingKeysWith: +)
+        }
+    }
+}
+
+/// A live session running far longer than its project's sessions usually
+/// take: "this usually takes 12 min, it's been 2 hr".
+struct DurationAnomaly: Identifiable, Equatable {
+    let sessionId: UUID
+    let sessionName: String
+    let startedAt: Date
+    /// Median duration of the project's ended sessions with the same agent.
+    let typical: TimeInterval
+
+    var id: UUID { sessionId }
+
+    /// The alert stays up while the session keeps running, so the elapsed
+    /// time is worked out when shown rather than when it was raised.
+    func elapsed(asOf now: Date) -> TimeInterval {
+        max(now.timeIntervalSince(startedAt), 0)
+    }
+
+    func message(asOf now: Date) -> String {
+        "Usually takes \(SessionStore.formatDuration(typical)), running for \(SessionStore.formatDuration(elapsed(asOf: now)))"
+    }
+}
+
+/// Typical session durations learned from the ended sessions in the list,
+/// per agent and project.
+enum DurationBaseline {
+    /// Fewer ended sessions than this and there is no baseline yet.
+    static let minimumSamples = 3
+    static let anomalyFactor = 3.0
+    /// Short sessions aren't worth an alert however far past typical they are.
+    static let minimumElapsed: TimeInterval = 600
+
+    static func medians(of sessions: [Session], now: Date) -> [String: TimeInterval] {
+        let ended = sessions.filter { $0.endedAt != nil && $0.projectPath != nil }
+        return Dictionary(grouping: ended, by: key).compactMapValues { group in
+            guard group.count >= minimumSamples else { return nil }
+            let durations = group.map { $0.duration(asOf: now) }.sorted()
+            let middle = durations.count / 2
+            return durations.count.isMultiple(of: 2) ? (durations[middle - 1] + durations[middle]) / 2 : durations[middle]
+        }
+    }
+
+    static func anomalies(in sessions: [Session], now: Date) -> [DurationAnomaly] {
+        let medians = medians(of: sessions, now: now)
+        return sessions.compactMap { session in
+            guard session.endedAt == nil, session.projectPath != nil,
+                  let typical = medians[key(session)], typical > 0 else { return nil }
+            let elapsed = session.duration(asOf: now)
+            guard elapsed >= minimumElapsed, elapsed > typical * anomalyFactor else { return nil }
+            return DurationAnomaly(sessionId: session.id, sessionName: session.name, startedAt: session.startedAt, typical: typical)
+        }
+    }
+
+    private static func key(_ session: Session) -> String {
+        "\(session.agentType.rawValue)|\(session.projectPath ?? "")"
+    }
+}
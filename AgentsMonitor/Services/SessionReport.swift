@@ -0,0 +1,75 @@
+import Foundation
+
+/// A short Markdown account of a session for posting where the work is
+/// tracked: outcome, time, cost, its summary, the files it changed and the
+/// test commands it ran.
+enum SessionReport {
+    /// Files listed before the rest are folded into a count.
+    static let fileLimit = 20
+    static let testLimit = 10
+
+    private static let testCommand = try! NSRegularExpression(
+        pattern: #"(?:^|[\s;&|(])(?:swift test|xcodebuild\b.*\btest\b|cargo (?:test|nextest)|go test|pytest|py\.test|jest|vitest|mocha|rspec|phpunit|(?:npm|pnpm|yarn|bun) (?:run )?test|make (?:test|check)|gradlew? test|mvn test|dotnet test|tox)\b"#
+    )
+
+    static func markdown(for session: Session, transcript: Transcript, now: Date = Date()) -> String {
+        let metrics = session.metrics
+        var lines = ["**\(session.agentType.rawValue) session: \(session.name)**", ""]
+
+        var facts = [
+            "Status: \(session.status.rawValue)",
+            "Duration: \(SessionStore.formatDuration(session.duration(asOf: now)))",
+            "Cost: \(String(format: "$%.2f", metrics.cost))\(metrics.isEstimated ? " (estimated)" : "")",
+            "Tokens: \(metrics.inputTokens.formatted()) in / \(metrics.outputTokens.formatted()) out"
+        ]
+        if let branch = session.gitBranch { facts.append("Branch: `\(branch)`") }
+        lines.append(contentsOf: facts.map { "- \($0)" })
+
+        if let summary = session.sessionSummary, !summary.isEmpty {
+            lines.append("")
+            lines.append(summary)
+        }
+
+        let changes = transcript.lineCountsByFile
+        if !changes.isEmpty {
+            let additions = changes.reduce(0) { $0 + $1.additions }
+            let deletions = changes.reduce(0) { $0 + $1.deletions }
+            lines.append("")
+            lines.append("**Files changed (\(changes.count), +\(additions) −\(deletions))**")
+            for change in changes.prefix(fileLimit) {
+                lines.append("- `\(session.relativePath(change.path))` +\(change.additions) −\(change.deletions)")
+            }
+            if changes.count > fileLimit {
+                lines.append("- …and \(changes.count - fileLimit) more")
+            }
+        }
+
+        let tests = testRuns(in: transcript)
+        if !tests.isEmpty {
+            lines.append("")
+            lines.append("**Tests run**")
+            for test in tests.prefix(testLimit) {
+                lines.append("- \(test.passed ? "✅" : "❌") `\(test.command)`")
+            }
+        }
+        return lines.joined(separator: "\n")
+    }
+
+    /// Distinct test commands the session ran, each with how its last run
+    /// ended, in order of first run.
+    static func testRuns(in transcript: Transcript) -> [(command: String, passed: Bool)] {
+        var runs: [(command: String, passed: Bool)] = []
+        for call in transcript.toolCalls where call.status == .completed || call.status == .failed {
+            guard let command = DangerousCommandDetector.shellCommand(of: call) else { continue }
+            let range = NSRange(command.startIndex..., in: command)
+            guard testCommand.firstMatch(in: command, range: range) != nil else { continue }
+            let line = command.split(separator: "\n").first.map(String.init) ?? command
+            if let index = runs.firstIndex(where: { $0.command == line }) {
+                runs[index].passed = call.status == .completed
+            } else {
+                runs.append((line, call.status == .completed))
+            }
+        }
+        return runs
+    }
+}
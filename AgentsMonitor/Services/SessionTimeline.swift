@@ -0,0 +1,96 @@
+import Foundation
+
+enum TimelinePhase: String, CaseIterable {
+    /// The agent's turn with no tool running: reading, reasoning, writing.
+    case thinking = "Thinking"
+    case toolExecution = "Running tools"
+    /// The agent finished its turn and the next prompt hasn't come yet.
+    /// Transcripts don't record when the user starts typing, so composing
+    /// the prompt counts here too.
+    case waitingForUser = "Waiting for you"
+    /// Gaps past `SessionTimeline.idleThreshold`: nobody is at the keyboard.
+    case idle = "Idle"
+}
+
+struct TimelineSegment: Hashable {
+    let phase: TimelinePhase
+    let start: Date
+    let end: Date
+
+    var duration: TimeInterval { end.timeIntervalSince(start) }
+}
+
+/// Splits a session's wall-clock time into phases, for a Gantt-style view
+/// of where it went. Derived from message timestamps and tool call start
+/// and completion times.
+enum SessionTimeline {
+    /// Stretches longer than this are counted as idle past this point.
+    static let idleThreshold: TimeInterval = 300
+
+    /// Pass `now` for a live session to carry its current phase up to now.
+    static func segments(for transcript: Transcript, now: Date? = nil) -> [TimelineSegment] {
+        enum Event { case prompt, reply, other }
+        let calls = transcript.toolCalls.filter { $0.startedAt > .distantPast }.sorted { $0.startedAt < $1.startedAt }
+        var events: [(time: Date, kind: Event)] = transcript.messages
+            .filter { $0.timestamp > .distantPast && ($0.role == .user || $0.role == .assistant) }
+            .map { (time: $0.timestamp, kind: $0.role == .user ? Event.prompt : Event.reply) }
+        for call in calls {
+            events.append((call.startedAt, .other))
+            if let completedAt = call.completedAt { events.append((completedAt, .other)) }
+        }
+        events.sort { $0.time < $1.time }
+        guard let first = events.first else { return [] }
+        if let now, now > events[events.count - 1].time { events.append((now, .other)) }
+
+        var segments: [TimelineSegment] = []
+        var lastMessage: Event?
+        var previous = first
+        // Events come in time order, so calls start and finish in one sweep:
+        // `running` holds the end of each call started and not yet done.
+        var nextCall = 0
+        var running: [Date?] = []
+        for event in events.dropFirst() {
+            if previous.kind != .other { lastMessage = previous.kind }
+            defer { previous = event }
+            guard event.time > previous.time else { continue }
+
+            while nextCall < calls.count, calls[nextCall].startedAt <= previous.time {
+                running.append(calls[nextCall].completedAt)
+                nextCall += 1
+            }
+            running.removeAll { $0.map { $0 <= previous.time } ?? false }
+
+            let phase: TimelinePhase
+            if !running.isEmpty {
+                phase = .toolExecution
+            } else if lastMessage == .reply && (event.kind == .prompt || event.time == now) {
+                phase = .waitingForUser
+            } else {
+                phase = .thinking
+            }
+            let busyUntil = phase == .toolExecution ? event.time : min(event.time, previous.time.addingTimeInterval(idleThreshold))
+            append(TimelineSegment(phase: phase, start: previous.time, end: busyUntil), to: &segments)
+            if busyUntil < event.time {
+                append(TimelineSegment(phase: .idle, start: busyUntil, end: event.time), to: &segments)
+            }
+        }
+        return segments
+    }
+
+    /// Time spent in each phase, in `TimelinePhase` order, leaving out phases
+    /// with none.
+    static func totals(_ segments: [TimelineSegment]) -> [(phase: TimelinePhase, duration: TimeInterval)] {
+        TimelinePhase.allCases.compactMap { phase in
+            let duration = segments.filter { $0.phase == phase }.reduce(0) { $0 + $1.duration }
+            return duration > 0 ? (phase, duration) : nil
+        }
+    }
+
+    private static func append(_ segment: TimelineSegment, to segments: inout [TimelineSegment]) {
+        if let last = segments.last, last.phase == segment.phase, last.end == segment.start {
+            segments[segments.count - 1] = TimelineSegment(phase: last.phase, start: last.start, end: segment.end)
+        } else {
+            segments.append(segment)
+        }
+    }
+}
@@ -0,0 +1,49 @@
+import Foundation
+
+/// Observes the preference keys behind `AppSettings` and reports validated
+/// snapshots when they change, whether the write came from a settings toggle
+/// or from `defaults write com.agentsmonitor.app ...` while the app is running.
+final class SettingsWatcher: NSObject {
+    typealias ChangeHandler = (_ old: AppSettings, _ new: AppSettings) -> Void
+
+    private let defaults: UserDefaults
+    private let onChange: ChangeHandler
+    private(set) var current: AppSettings
+
+    init(defaults: UserDefaults = .standard, onChange: @escaping ChangeHandler) {
+        self.defaults = defaults
+        self.onChange = onChange
+        self.current = AppSettings.load(from: defaults)
+        super.init()
+        for key in AppSettings.Key.all {
+            defaults.addObserver(self, forKeyPath: key, options: [.new], context: nil)
+        }
+    }
+
+    deinit {
+        for key in AppSettings.Key.all {
+            defaults.removeObserver(self, forKeyPath: key)
+        }
+    }
+
+    override func observeValue(
+        forKeyPath keyPath: String?,
+        of object: Any?,
+        change: [NSKeyValueChangeKey: Any]?,
+        context: UnsafeMutableRawPointer?
+    ) {
+        // KVO fires on the writer's thread; external edits arrive on an arbitrary one.
+        DispatchQueue.main.async { [weak self] in
+            self?.reload()
+        }
+    }
+
+    private func reload() {
+        let updated = AppSettings.load(from: defaults)
+        guard updated != current else { return }
+        let previous = current
+        current = updated
+        NotificationCenter.default.post(name: AppSettings.didChangeNotification, object: self)
+        onChange(previous, updated)
+    }
+}
@@ -0,0 +1,98 @@
+import Foundation
+
+/// Builds a copy of a session that is safe to attach to a bug report or post:
+/// secrets masked, home folder and user name stripped, long tool output cut
+/// down, pasted images left out. The copy gets a new session id so it can't be
+/// mistaken for the original.
+enum ShareCopy {
+    struct Options: Equatable {
+        var redactSecrets = true
+        var anonymizePaths = true
+        var includeImages = false
+        /// Tool output longer than this keeps its head with a trimmed marker; `nil` keeps it whole.
+        var maxToolOutputLength: Int? = 2_000
+        /// `false` drops tool output entirely and keeps only the calls.
+        var includeToolOutput = true
+    }
+
+    static func make(
+        from transcript: Transcript,
+        session: Session,
+        options: Options = Options(),
+        redactor: SecretRedactor = SecretRedactor(customPatterns: AppSettings.load().redactionPatterns),
+        anonymizer: PathAnonymizer = PathAnonymizer()
+    ) -> (session: Session, transcript: Transcript) {
+        func clean(_ text: String) -> String {
+            var result = text
+            if options.redactSecrets { result = redactor.redact(result) }
+            if options.anonymizePaths { result = anonymizer.anonymize(result) }
+            return result
+        }
+        func clean(_ text: String?) -> String? {
+            text.map { clean($0) }
+        }
+
+        var copy = Session(
+            name: clean(session.name),
+            status: session.status,
+            agentType: session.agentType,
+            startedAt: session.startedAt,
+            endedAt: session.endedAt,
+            metrics: session.metrics,
+            projectPath: clean(session.projectPath),
+            gitBranch: clean(session.gitBranch),
+            firstPrompt: clean(session.firstPrompt),
+            sessionSummary: clean(session.sessionSummary),
+            isSidechain: session.isSidechain
+        )
+        copy.errorMessage = clean(session.errorMessage)
+
+        var shared = Transcript()
+        shared.messages = transcript.messages.map { message in
+            var message = message
+            message.content = clean(message.content)
+            for i in message.codeBlocks.indices {
+                message.codeBlocks[i].content = clean(message.codeBlocks[i].content)
+            }
+            message.attachments = message.attachments.compactMap { attachment in
+                switch attachment.kind {
+                case .image:
+                    return options.includeImages ? attachment : nil
+                case .file:
+                    return MessageAttachment(id: attachment.id, kind: .file, filePath: clean(attachment.filePath))
+                }
+            }
+            return message
+        }
+        shared.toolCalls = transcript.toolCalls.map { call in
+            var call = call
+            call.input = clean(call.input)
+            call.error = clean(call.error).map { trimmed($0, to: options.maxToolOutputLength) }
+            call.output = options.includeToolOutput
+                ? clean(call.output).map { trimmed($0, to: options.maxToolOutputLength) }
+                : nil
+            if call.diff != nil {
+                call.diff = FileDiff(toolName: call.name, input: call.input)
+            }
+            return call
+        }
+        shared.subagentToolCallIds = transcript.subagentToolCallIds
+        return (copy, shared)
+    }
+
+    /// The share copy as Claude Code JSONL, the format `ClaudeTranscriptExporter` writes.
+    static func jsonl(
+        from transcript: Transcript,
+        session: Session,
+        options: Options = Options(),
+        anonymizer: PathAnonymizer = PathAnonymizer()
+    ) -> String {
+        let copy = make(from: transcript, session: session, options: options, anonymizer: anonymizer)
+        return ClaudeTranscriptExporter.jsonl(for: copy.transcript, session: copy.session)
+    }
+
+    static func trimmed(_ text: String, to limit: Int?) -> String {
+        guard let limit, text.count > limit else { return text }
+        return String(text.prefix(limit)) + "\n[\(text.count - limit) characters trimmed]"
+    }
+}
@@ -8,6 +8,16 @@ struct SessionTokenSummary: Codable {
     let cost: Double
     let modelName: String
     let apiCalls: Int
+    /// Set when the counts come from `TokenEstimator` rather than reported usage.
+    var isEstimated: Bool? = nil
+    /// Every model id with usage in the session, most used first.
+    var modelIds: [String]? = nil
+    /// Tokens in the context at the last API call: the prompt, cached or not, plus the reply.
+    var contextTokens: Int? = nil
+    /// The window size the agent reported, when it reports one (Codex does).
+    var contextWindow: Int? = nil
+    /// MCP tools the session called, by qualified name, in order of first use.
+    var mcpTools: [String]? = nil
 }
 
 struct CodexRateLimits {
@@ -58,6 +68,8 @@ struct TokenCostCalculator {
         var modelCounts: [String: Int] = [:]
         var modelTokenTotals: [String: TokenTotals] = [:]
         var apiCalls = 0
+        var lastContext = 0
+        var mcpTools: [String] = []
 
         for line in content.split(separator: "\n") {
             guard let lineData = line.data(using: .utf8),
@@ -66,7 +78,14 @@ struct TokenCostCalculator {
             }
 
             guard let type = json["type"] as? String, type == "assistant" else { continue }
+            // Sidechain (sub-agent) calls run in their own context.
+            let isSidechain = json["isSidechain"] as? Bool ?? false
             guard let message = json["message"] as? [String: Any] else { continue }
+            for block in message["content"] as? [[String: Any]] ?? [] where block["type"] as? String == "tool_use" {
+                if let name = block["name"] as? String, name.hasPrefix("mcp__"), !mcpTools.contains(name) {
+                    mcpTools.append(name)
+                }
+            }
             guard let usage = message["usage"] as? [String: Any] else { continue }
 
             apiCalls += 1
@@ -76,6 +95,10 @@ struct TokenCostCalculator {
             let cacheRead = usage["cache_read_input_tokens"] as? Int ?? 0
             let model = message["model"] as? String ?? ""
 
+            if !isSidechain, input + cacheRead + cacheWrite > 0 {
+                lastContext = input + cacheRead + cacheWrite + output
+            }
+
             totalInput += input
             totalOutput += output
             totalCacheWrite += cacheWrite
@@ -93,11 +116,12 @@ struct TokenCostCalculator {
             modelTokenTotals[model] = totals
         }
 
-        let primaryModel = modelCounts.max(by: { $0.value < $1.value })?.key ?? ""
+        let modelIds = modelCounts.sorted { $0.value != $1.value ? $0.value > $1.value : $0.key < $1.key }.map(\.key)
+        let primaryModel = modelIds.first ?? ""
         let cost = modelTokenTotals.reduce(into: 0.0) { partial, item in
             let model = item.key
             let totals = item.value
-            partial += calculateCost(
+            partial += Self.cost(
                 model: model,
                 inputTokens: totals.inputTokens,
                 outputTokens: totals.outputTokens,
@@ -112,8 +136,11 @@ struct TokenCostCalculator {
             cacheWriteTokens: totalCacheWrite,
             cacheReadTokens: totalCacheRead,
             cost: cost,
-            modelName: formatModelName(primaryModel),
-            apiCalls: apiCalls
+            modelName: displayName(forModel: primaryModel),
+            apiCalls: apiCalls,
+            modelIds: modelIds,
+            contextTokens: lastContext,
+            mcpTools: mcpTools
         )
     }
 
@@ -124,12 +151,16 @@ struct TokenCostCalculator {
         }
 
         var model = ""
+        var models: [String] = []
         var apiCalls = 0
         var lastInput = 0
         var lastCached = 0
         var lastOutput = 0
         var foundTokens = false
         var lastRateLimits: [String: Any]?
+        var lastContext = 0
+        var contextWindow: Int?
+        var mcpTools: [String] = []
 
         for line in content.split(separator: "\n") {
             guard let lineData = line.data(using: .utf8),
@@ -141,8 +172,9 @@ struct TokenCostCalculator {
             switch type {
             case "turn_context":
                 apiCalls += 1
-                if model.isEmpty, let m = payload["model"] as? String {
-                    model = m
+                if let m = payload["model"] as? String, !m.isEmpty {
+                    if model.isEmpty { model = m }
+                    if !models.contains(m) { models.append(m) }
                 }
 
             case "event_msg":
@@ -155,11 +187,26 @@ struct TokenCostCalculator {
                 guard let info = payload["info"] as? [String: Any],
                       let usage = info["total_token_usage"] as? [String: Any] else { continue }
 
+                if let last = info["last_token_usage"] as? [String: Any] {
+                    lastContext = (last["input_tokens"] as? Int ?? 0) + (last["output_tokens"] as? Int ?? 0)
+                }
+                if let window = info["model_context_window"] as? Int, window > 0 {
+                    contextWindow = window
+                }
+
                 lastInput = usage["input_tokens"] as? Int ?? 0
                 lastCached = usage["cached_input_tokens"] as? Int ?? 0
                 lastOutput = usage["output_tokens"] as? Int ?? 0
                 foundTokens = true
 
+            case "response_item":
+                if payload["type"] as? String == "function_call",
+                   let name = payload["name"] as? String,
+                   MCPServers.ToolUse(toolName: name, agentType: .codex) != nil,
+                   !mcpTools.contains(name) {
+                    mcpTools.append(name)
+                }
+
             default:
                 break
             }
@@ -168,7 +215,7 @@ struct TokenCostCalculator {
         guard foundTokens else { return nil }
 
         let uncachedInput = max(lastInput - lastCached, 0)
-        let cost = calculateCost(
+        let cost = Self.cost(
             model: model,
             inputTokens: uncachedInput,
             outputTokens: lastOutput,
@@ -182,8 +229,12 @@ struct TokenCostCalculator {
             cacheWriteTokens: 0,
             cacheReadTokens: lastCached,
             cost: cost,
-            modelName: formatModelName(model),
-            apiCalls: apiCalls
+            modelName: displayName(forModel: model),
+            apiCalls: apiCalls,
+            modelIds: models,
+            contextTokens: lastContext,
+            contextWindow: contextWindow,
+            mcpTools: mcpTools
         )
 
         let rateLimits = parseCodexRateLimits(lastRateLimits)
@@ -220,7 +271,7 @@ struct TokenCostCalculator {
         return CodexRateLimits(primary: parseWindow(primary), secondary: parseWindow(secondary))
     }
 
-    private static func calculateCost(model: String, inputTokens: Int, outputTokens: Int, cacheWriteTokens: Int, cacheReadTokens: Int) -> Double {
+    static func cost(model: String, inputTokens: Int, outputTokens: Int, cacheWriteTokens: Int, cacheReadTokens: Int) -> Double {
         guard let pricing = pricingTable.first(where: { model.hasPrefix($0.prefix) })?.pricing else {
             AppLogger.logWarning("Unknown model '\(model)', falling back to Sonnet pricing", context: "TokenCostCalculator")
             let fallback = pricingTable[1].pricing
@@ -237,7 +288,8 @@ struct TokenCostCalculator {
         return input + output + cacheWrite + cacheRead
     }
 
-    private static func formatModelName(_ model: String) -> String {
+    /// Short name for a model id, e.g. "Sonnet 4"; unknown ids pass through.
+    static func displayName(forModel model: String) -> String {
         if model.hasPrefix("claude-opus-4") { return "Opus 4" }
         if model.hasPrefix("claude-sonnet-4") { return "Sonnet 4" }
         if model.hasPrefix("claude-haiku-4") { return "Haiku 4" }
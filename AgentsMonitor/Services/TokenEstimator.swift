@@ -0,0 +1,81 @@
+import Foundation
+
+/// Approximate token counts for text the agent sent or received, for sessions
+/// whose transcript carries no usage data.
+///
+/// Text is split the way BPE tokenizers pre-split it (words with their leading
+/// space, runs of up to three digits, punctuation, line breaks) and each piece
+/// is costed by length. Good enough for a "~" figure, not for billing.
+enum TokenEstimator {
+    private static let pieces = try! NSRegularExpression(
+        pattern: #"'(?:[sdmt]|ll|ve|re)|[^\r\n\p{L}\p{N}]?\p{L}+|\p{N}{1,3}| ?[^\s\p{L}\p{N}]+[\r\n]*|\s*[\r\n]+|\s+"#,
+        options: .caseInsensitive
+    )
+
+    static func estimate(_ text: String) -> Int {
+        guard !text.isEmpty else { return 0 }
+        let range = NSRange(text.startIndex..., in: text)
+        var total = 0
+        pieces.enumerateMatches(in: text, range: range) { match, _, _ in
+            guard let match, let pieceRange = Range(match.range, in: text) else { return }
+            total += cost(of: text[pieceRange])
+        }
+        return total
+    }
+
+    private static func cost(of piece: Substring) -> Int {
+        let core = piece.trimmingCharacters(in: .whitespaces)
+        // A run of spaces is one token; spaces before a word merge into it.
+        guard !core.isEmpty else { return 1 }
+        let nonASCII = core.unicodeScalars.filter { !$0.isASCII }.count
+        if nonASCII > 0 {
+            // CJK and other non-Latin scripts average about a token per character.
+            return nonASCII + (core.unicodeScalars.count - nonASCII + 3) / 4
+        }
+        if core.allSatisfy(\.isLetter) {
+            return core.count <= 7 ? 1 : (core.count + 3) / 4
+        }
+        return max(1, (core.count + 1) / 2)
+    }
+
+    /// The usage the agent reported, else an estimate from the transcript:
+    /// for sessions that recorded zero tokens, and for Codex sessions with no
+    /// `token_count` events at all, which report nothing. `transcript` is
+    /// only read when an estimate is needed.
+    static func summary(reported: SessionTokenSummary?, transcript: () -> Transcript?) -> SessionTokenSummary? {
+        if let reported, reported.inputTokens + reported.outputTokens > 0 { return reported }
+        guard let transcript = transcript(), !transcript.messages.isEmpty || !transcript.toolCalls.isEmpty else { return reported }
+        return summary(for: transcript)
+    }
+
+    /// Rolls a transcript into token totals: what the user typed and tools
+    /// returned is input, what the assistant wrote and the tool calls it made
+    /// are output. Cost is priced at the first model the transcript names.
+    static func summary(for transcript: Transcript) -> SessionTokenSummary {
+        var input = 0
+        var output = 0
+        for message in transcript.messages {
+            switch message.role {
+            case .assistant: output += message.estimatedTokens
+            case .user, .system: input += message.estimatedTokens
+            case .tool: break
+            }
+        }
+        for call in transcript.toolCalls {
+            output += estimate(call.input)
+            input += estimate(call.output ?? "")
+        }
+        let model = transcript.models.first
+        return SessionTokenSummary(
+            inputTokens: input,
+            outputTokens: output,
+            cacheWriteTokens: 0,
+            cacheReadTokens: 0,
+            cost: model.map { TokenCostCalculator.cost(model: $0, inputTokens: input, outputTokens: output, cacheWriteTokens: 0, cacheReadTokens: 0) } ?? 0,
+            modelName: model.map(TokenCostCalculator.displayName(forModel:)) ?? "",
+            apiCalls: transcript.messages.filter { $0.role == .assistant }.count,
+            isEstimated: true,
+            modelIds: transcript.models
+        )
+    }
+}
@@ -0,0 +1,103 @@
+import Foundation
+
+/// Parsed, redacted transcripts by file path, reused until the file (or a
+/// sub-agent file under it) changes, so expanding a row or typing in its
+/// search field doesn't parse the same JSONL again. Sweeps over the whole
+/// history go through a `TranscriptBatch` instead, which reads past the
+/// few entries here rather than evicting them.
+actor TranscriptCache {
+    static let defaultCapacity = 24
+
+    private struct Entry {
+        let stamp: [String: Date]
+        let redactionPatterns: [String]
+        let transcript: Transcript
+        var lastUsed: Date
+    }
+
+    private let capacity: Int
+    private let fileManager: FileManager
+    private var entries: [String: Entry] = [:]
+
+    init(capacity: Int = defaultCapacity, fileManager: FileManager = .default) {
+        self.capacity = capacity
+        self.fileManager = fileManager
+    }
+
+    /// Pass `retain: false` to reuse a current entry without adding one.
+    func transcript(at path: String, agentType: AgentType, redactionPatterns: [String], retain: Bool = true) -> Transcript? {
+        let stamp = stamp(for: path, agentType: agentType)
+        if var entry = entries[path], entry.stamp == stamp, entry.redactionPatterns == redactionPatterns {
+            entry.lastUsed = Date()
+            entries[path] = entry
+            return entry.transcript
+        }
+        let redactor = SecretRedactor(customPatterns: redactionPatterns)
+        guard let transcript = TranscriptReader.read(jsonlPath: path, agentType: agentType).map(redactor.redacted) else {
+            entries[path] = nil
+            return nil
+        }
+        guard retain else { return transcript }
+        entries[path] = Entry(stamp: stamp, redactionPatterns: redactionPatterns, transcript: transcript, lastUsed: Date())
+        if entries.count > capacity, let oldest = entries.min(by: { $0.value.lastUsed < $1.value.lastUsed })?.key {
+            entries[oldest] = nil
+        }
+        return transcript
+    }
+
+    func removeAll() {
+        entries.removeAll()
+    }
+
+    /// The mtimes of the file and of the sub-agent files `TranscriptReader`
+    /// merges into it, which keep growing while the parent waits on their
+    /// Task calls. A sub-agent file appearing changes the stamp too.
+    private func stamp(for path: String, agentType: AgentType) -> [String: Date] {
+        var files = [URL(fileURLWithPath: path)]
+        if agentType == .claudeCode {
+            files += TranscriptReader.sidechainFiles(forSessionAt: path, fileManager: fileManager)
+        }
+        var stamp: [String: Date] = [:]
+        for file in files {
+            stamp[file.path] = (try? fileManager.attributesOfItem(atPath: file.path)[.modificationDate] as? Date) ?? .distantPast
+        }
+        return stamp
+    }
+}
+
+/// Transcripts for one pass over many sessions, such as an export or the
+/// Settings insights: each file is read at most once per batch, however
+/// many of the pass's steps ask for it.
+actor TranscriptBatch {
+    private let cache: TranscriptCache
+    private let redactionPatterns: [String]
+    private var loads: [String: Task<Transcript?, Never>] = [:]
+
+    init(cache: TranscriptCache, redactionPatterns: [String]) {
+        self.cache = cache
+        self.redactionPatterns = redactionPatterns
+    }
+
+    /// `sessions` paired with their transcripts, in order, leaving out those
+    /// without a readable file.
+    func transcripts(for sessions: [Session]) async -> [(session: Session, transcript: Transcript)] {
+        var batch: [(session: Session, transcript: Transcript)] = []
+        for session in sessions {
+            guard let path = session.jsonlPath, let transcript = await load(path, agentType: session.agentType) else { continue }
+            batch.append((session, transcript))
+        }
+        return batch
+    }
+
+    /// Steps running side by side share one read of a file.
+    private func load(_ path: String, agentType: AgentType) async -> Transcript? {
+        if let load = loads[path] { return await load.value }
+        let cache = cache
+        let redactionPatterns = redactionPatterns
+        let load = Task {
+            await cache.transcript(at: path, agentType: agentType, redactionPatterns: redactionPatterns, retain: false)
+        }
+        loads[path] = load
+        return await load.value
+    }
+}
@@ -0,0 +1,548 @@
+import Foundation
+
+/// The conversation in a session transcript, in file order.
+struct Transcript {
+    var messages: [Message] = []
+    var toolCalls: [ToolCall] = []
+    /// Model ids the agent ran, in order of first use.
+    var models: [String] = []
+    /// Points where the agent summarized the conversation to free up context.
+    var compactions: [Compaction] = []
+    /// Task call id → ids of the tool calls the sub-agent it spawned made.
+    var subagentToolCallIds: [UUID: [UUID]] = [:]
+    /// Lines that couldn't be read, so the transcript shown is missing them.
+    var issues: [TranscriptIssue] = []
+
+    /// Some lines were skipped; what's shown is a partial transcript.
+    var isPartial: Bool { !issues.isEmpty }
+
+    /// The chain of messages leading to `id`, oldest first. Falls back to file
+    /// order for transcripts that don't record parents.
+    func thread(endingAt id: UUID) -> [Message] {
+        let byId = Dictionary(messages.map { ($0.id, $0) }, uniquingKeysWith: { first, _ in first })
+        guard let last = byId[id] else { return [] }
+        guard messages.contains(where: { $0.parentId != nil }) else {
+            return Array(messages.prefix { $0.id != id }) + [last]
+        }
+
+        var chain = [last]
+        var seen: Set<UUID> = [id]
+        while let parentId = chain.last?.parentId, !seen.contains(parentId), let parent = byId[parentId] {
+            chain.append(parent)
+            seen.insert(parentId)
+        }
+        return chain.reversed()
+    }
+
+    func replies(to id: UUID) -> [Message] {
+        messages.filter { $0.parentId == id }
+    }
+
+    /// Messages answered more than once, i.e. where a retry or edit branched the conversation.
+    var branchPoints: [Message] {
+        let counts = messages.reduce(into: [UUID: Int]()) { counts, message in
+            if let parentId = message.parentId { counts[parentId, default: 0] += 1 }
+        }
+        return messages.filter { (counts[$0.id] ?? 0) > 1 }
+    }
+
+    func diff(forToolCall id: UUID) -> FileDiff? {
+        toolCalls.first { $0.id == id }?.diff
+    }
+
+    /// Every file change in the session, in the order the agent made them.
+    var fileChanges: [FileDiff] {
+        toolCalls.compactMap(\.diff)
+    }
+
+    /// Sub-agents the session spawned through the Task tool, in launch order.
+    var subagents: [SubagentRun] {
+        let byId = Dictionary(toolCalls.map { ($0.id, $0) }, uniquingKeysWith: { first, _ in first })
+        return toolCalls.filter { SubagentRun.spawningTools.contains($0.name) }.map { call in
+            SubagentRun(call: call, toolCalls: (subagentToolCallIds[call.id] ?? []).compactMap { byId[$0] })
+        }
+    }
+
+    /// Folds in sub-agent transcripts Claude Code wrote to files of their
+    /// own: their tool calls join the session's, each credited to the Task
+    /// call whose prompt started it, else the latest unclaimed one launched
+    /// before it.
+    mutating func attachSubagents(_ sidechains: [Transcript]) {
+        var unmatched: [Transcript] = []
+        for sidechain in sidechains {
+            let prompt = sidechain.messages.first { $0.role == .user }?.content
+            if let owner = unclaimedTasks.first(where: { prompt != nil && SubagentRun.prompt(of: $0) == prompt }) {
+                attach(sidechain, to: owner)
+            } else {
+                unmatched.append(sidechain)
+            }
+        }
+        for sidechain in unmatched {
+            guard let start = sidechain.messages.first?.timestamp ?? sidechain.toolCalls.first?.startedAt,
+                  let owner = unclaimedTasks.last(where: { $0.startedAt <= start }) else { continue }
+            attach(sidechain, to: owner)
+        }
+        toolCalls.sort { $0.startedAt < $1.startedAt }
+    }
+
+    private var unclaimedTasks: [ToolCall] {
+        toolCalls.filter { SubagentRun.spawningTools.contains($0.name) && subagentToolCallIds[$0.id] == nil }
+    }
+
+    private mutating func attach(_ sidechain: Transcript, to task: ToolCall) {
+        toolCalls.append(contentsOf: sidechain.toolCalls)
+        subagentToolCallIds[task.id] = sidechain.toolCalls.map(\.id)
+    }
+
+    /// Lines added and removed per file across the session's edits, in order
+    /// of first change.
+    var lineCountsByFile: [(path: String, additions: Int, deletions: Int)] {
+        var changes: [(path: String, additions: Int, deletions: Int)] = []
+        for diff in toolCalls.compactMap(\.diff) {
+            if let index = changes.firstIndex(where: { $0.path == diff.filePath }) {
+                changes[index].additions += diff.additions
+                changes[index].deletions += diff.deletions
+            } else {
+                changes.append((diff.filePath, diff.additions, diff.deletions))
+            }
+        }
+        return changes
+    }
+}
+
+/// A sub-agent launched by a Task tool call. Claude Code logs its turns as
+/// sidechain lines, in the parent transcript or in a file of their own; its
+/// tool calls are those.
+struct SubagentRun: Identifiable, Hashable {
+    static let spawningTools: Set<String> = ["Task", "Agent"]
+
+    /// The prompt a Task call handed its sub-agent.
+    static func prompt(of call: ToolCall) -> String? {
+        call.input.data(using: .utf8)
+            .flatMap { try? JSONSerialization.jsonObject(with: $0) as? [String: Any] }?["prompt"] as? String
+    }
+
+    /// The id of the Task call that spawned it.
+    let id: UUID
+    let description: String
+    /// The `subagent_type` asked for, e.g. `general-purpose`.
+    let agentType: String?
+    let prompt: String
+    let startedAt: Date
+    let completedAt: Date?
+    let status: ToolCallStatus
+    let toolCalls: [ToolCall]
+
+    init(call: ToolCall, toolCalls: [ToolCall]) {
+        let input = call.input.data(using: .utf8)
+            .flatMap { try? JSONSerialization.jsonObject(with: $0) as? [String: Any] } ?? [:]
+        self.id = call.id
+        self.description = input["description"] as? String ?? call.name
+        self.agentType = input["subagent_type"] as? String
+        self.prompt = input["prompt"] as? String ?? ""
+        self.startedAt = call.startedAt
+        self.completedAt = call.completedAt
+        self.status = call.status
+        self.toolCalls = toolCalls
+    }
+
+    var duration: TimeInterval? {
+        completedAt.map { $0.timeIntervalSince(startedAt) }
+    }
+}
+
+/// A transcript line the reader skipped, and why.
+struct TranscriptIssue: Hashable {
+    /// 1-based line number in the JSONL file.
+    let line: Int
+    let problem: String
+
+    var description: String { "Line \(line): \(problem)" }
+}
+
+/// A context compaction: the agent replaced the conversation so far with a
+/// summary, either on its own (`auto`) or because the user ran `/compact`.
+struct Compaction: Hashable {
+    let timestamp: Date
+    /// `auto` or `manual`; `nil` when the transcript doesn't say.
+    let trigger: String?
+    /// Context size just before compacting, when recorded.
+    let tokensBefore: Int?
+}
+
+/// Reads a full Claude Code or Codex JSONL transcript into `Message` and
+/// `ToolCall` values. Discovery only looks at the first few lines; this is for
+/// features that need the whole conversation, so call it off the main actor.
+enum TranscriptReader {
+    static func read(jsonlPath: String, agentType: AgentType) -> Transcript? {
+        guard let content = try? String(contentsOfFile: jsonlPath, encoding: .utf8) else {
+            AppLogger.logWarning("Cannot read JSONL file: \(jsonlPath)", context: "TranscriptReader")
+            return nil
+        }
+        var transcript = parse(content, agentType: agentType)
+        if agentType == .claudeCode {
+            let sidechains = sidechainFiles(forSessionAt: jsonlPath).compactMap { file in
+                (try? String(contentsOf: file, encoding: .utf8)).map { parse($0, agentType: .claudeCode) }
+            }
+            if !sidechains.isEmpty { transcript.attachSubagents(sidechains) }
+        }
+        return transcript
+    }
+
+    /// Sub-agent transcripts Claude Code filed apart from the session at
+    /// `jsonlPath`: under `<session id>/subagents/`, or next to it as
+    /// sidechain files whose lines carry the session's id.
+    static func sidechainFiles(forSessionAt jsonlPath: String, fileManager: FileManager = .default) -> [URL] {
+        let file = URL(fileURLWithPath: jsonlPath)
+        let sessionId = file.deletingPathExtension().lastPathComponent
+        let folder = file.deletingLastPathComponent()
+        let nested = (try? fileManager.contentsOfDirectory(
+            at: folder.appendingPathComponent(sessionId).appendingPathComponent("subagents"),
+            includingPropertiesForKeys: nil
+        )) ?? []
+        let siblings = ((try? fileManager.contentsOfDirectory(at: folder, includingPropertiesForKeys: nil)) ?? [])
+            .filter { $0.pathExtension == "jsonl" && $0.lastPathComponent != file.lastPathComponent }
+            .filter { sidechainParent(ofFileAt: $0.path) == sessionId }
+        return (nested.filter { $0.pathExtension == "jsonl" } + siblings).sorted { $0.lastPathComponent < $1.lastPathComponent }
+    }
+
+    /// The `sessionId` a sidechain file's first line names, when it isn't the
+    /// file's own: the session whose Task call spawned it.
+    static func sidechainParent(ofFileAt path: String) -> String? {
+        guard let handle = FileHandle(forReadingAtPath: path) else { return nil }
+        defer { handle.closeFile() }
+        let head = handle.readData(ofLength: 16_384)
+        guard let text = String(data: head, encoding: .utf8),
+              let line = text.split(separator: "\n").first,
+              let json = try? JSONSerialization.jsonObject(with: Data(line.utf8)) as? [String: Any],
+              json["isSidechain"] as? Bool == true,
+              let parent = json["sessionId"] as? String,
+              parent != URL(fileURLWithPath: path).deletingPathExtension().lastPathComponent else {
+            return nil
+        }
+        return parent
+    }
+
+    static func parse(_ content: String, agentType: AgentType) -> Transcript {
+        var builder = Builder()
+        let lines = content.split(separator: "\n", omittingEmptySubsequences: false)
+        for (index, line) in lines.enumerated() where !line.allSatisfy(\.isWhitespace) {
+            guard let lineData = line.data(using: .utf8),
+                  let json = try? JSONSerialization.jsonObject(with: lineData) as? [String: Any] else {
+                // A running agent may be mid-way through writing the last line.
+                if index < lines.count - 1 {
+                    builder.transcript.issues.append(TranscriptIssue(line: index + 1, problem: "Not a JSON object"))
+                }
+                continue
+            }
+            if let problem = validate(json, agentType: agentType) {
+                builder.transcript.issues.append(TranscriptIssue(line: index + 1, problem: problem))
+            }
+            let timestamp = (json["timestamp"] as? String).flatMap(parseISO8601) ?? builder.lastTimestamp
+            builder.lastTimestamp = timestamp
+            if agentType == .codex {
+                builder.addCodexLine(json, timestamp: timestamp ?? .distantPast)
+            } else {
+                builder.addClaudeLine(json, timestamp: timestamp ?? .distantPast)
+            }
+        }
+        return builder.transcript
+    }
+
+    /// Structural checks on a line: the fields every entry of its kind needs.
+    /// Unknown entry types are fine, since both CLIs add new ones over time.
+    private static func validate(_ json: [String: Any], agentType: AgentType) -> String? {
+        guard let type = json["type"] as? String else { return "Missing \"type\"" }
+        if let timestamp = json["timestamp"] {
+            guard let string = timestamp as? String, parseISO8601(string) != nil else { return "Unreadable \"timestamp\"" }
+        }
+        switch (agentType, type) {
+        case (.codex, "response_item"), (.codex, "event_msg"), (.codex, "turn_context"), (.codex, "session_meta"):
+            return json["payload"] is [String: Any] ? nil : "\(type) without a \"payload\" object"
+        case (.claudeCode, "user"), (.claudeCode, "assistant"):
+            return json["message"] is [String: Any] ? nil : "\(type) entry without a \"message\" object"
+        default:
+            return nil
+        }
+    }
+
+    // MARK: - Building
+
+    private struct Builder {
+        var transcript = Transcript()
+        var lastTimestamp: Date?
+        /// Agent-assigned tool call id → index into `transcript.toolCalls`.
+        var toolCallIndex: [String: Int] = [:]
+        /// Claude line `uuid` → id of the last message that line produced (or
+        /// inherited from its parent when it produced none, e.g. tool results).
+        var lineTail: [UUID: UUID] = [:]
+        /// Parent for the next message added from the current line, and the
+        /// line's own uuid, which becomes the first message's id.
+        var pendingParent: UUID?
+        var pendingLineId: UUID?
+        var isChainingLine = false
+        /// Sidechain line `uuid` → the Task call whose sub-agent wrote it.
+        var sidechainOwner: [UUID: UUID] = [:]
+        /// The Task call the current line belongs to, when it is a sidechain line.
+        var currentSubagent: UUID?
+
+        func runningTask(forPrompt prompt: String?) -> UUID? {
+            let running = transcript.toolCalls.filter { SubagentRun.spawningTools.contains($0.name) && $0.status == .running }
+            return (running.first { prompt != nil && SubagentRun.prompt(of: $0) == prompt } ?? running.last)?.id
+        }
+
+        mutating func addClaudeLine(_ json: [String: Any], timestamp: Date) {
+            if json["type"] as? String == "system", json["subtype"] as? String == "compact_boundary" {
+                let metadata = json["compactMetadata"] as? [String: Any]
+                transcript.compactions.append(Compaction(
+                    timestamp: timestamp,
+                    trigger: metadata?["trigger"] as? String,
+                    tokensBefore: metadata?["preTokens"] as? Int
+                ))
+                return
+            }
+            guard let type = json["type"] as? String, type == "user" || type == "assistant",
+                  let message = json["message"] as? [String: Any] else {
+                return
+            }
+            // The summary Claude Code continues from is filed as a user turn.
+            let isCompactSummary = json["isCompactSummary"] as? Bool ?? false
+            let role: MessageRole = isCompactSummary ? .system : (type == "user" ? .user : .assistant)
+            if role == .assistant, let model = message["model"] as? String {
+                noteModel(model)
+            }
+
+            let lineId = (json["uuid"] as? String).flatMap(UUID.init(uuidString:))
+            let parentLine = (json["parentUuid"] as? String).flatMap(UUID.init(uuidString:))
+            pendingLineId = lineId
+            pendingParent = parentLine.map { lineTail[$0] ?? $0 }
+            isChainingLine = lineId != nil
+            if json["isSidechain"] as? Bool ?? false {
+                // A sub-agent's first line has no parent. It belongs to the
+                // running Task call that sent that prompt; several run at
+                // once when the agent fans out, so only fall back to the latest.
+                let prompt = message["content"] as? String
+                    ?? (message["content"] as? [[String: Any]])?.first(where: { $0["type"] as? String == "text" })?["text"] as? String
+                currentSubagent = parentLine.flatMap { sidechainOwner[$0] } ?? runningTask(forPrompt: prompt)
+                if let lineId, let currentSubagent { sidechainOwner[lineId] = currentSubagent }
+            }
+            defer {
+                if let lineId { lineTail[lineId] = pendingParent ?? lineId }
+                pendingLineId = nil
+                pendingParent = nil
+                isChainingLine = false
+                currentSubagent = nil
+            }
+
+            if let text = message["content"] as? String {
+                addMessage(role: role, text: text, timestamp: timestamp)
+                return
+            }
+            guard let blocks = message["content"] as? [[String: Any]] else { return }
+            let firstMessageIndex = transcript.messages.count
+            var images: [MessageAttachment] = []
+            for block in blocks {
+                switch block["type"] as? String {
+                case "text":
+                    addMessage(role: role, text: block["text"] as? String ?? "", timestamp: timestamp)
+                case "image":
+                    let source = block["source"] as? [String: Any]
+                    if source?["type"] as? String == "base64",
+                       let encoded = source?["data"] as? String,
+                       let data = Data(base64Encoded: encoded) {
+                        images.append(MessageAttachment(kind: .image, mediaType: source?["media_type"] as? String, data: data))
+                    }
+                case "tool_use":
+                    guard let id = block["id"] as? String else { continue }
+                    let input = block["input"] as? [String: Any] ?? [:]
+                    let name = block["name"] as? String ?? "tool"
+                    startToolCall(
+                        id: id,
+                        name: name,
+                        input: TranscriptReader.jsonString(input),
+                        timestamp: timestamp,
+                        fileReference: (input["file_path"] ?? input["notebook_path"]) as? String,
+                        diff: FileDiff(toolName: name, input: input)
+                    )
+                case "tool_result":
+                    guard let id = block["tool_use_id"] as? String else { continue }
+                    finishToolCall(
+                        id: id,
+                        output: TranscriptReader.resultText(block["content"]),
+                        failed: block["is_error"] as? Bool ?? false,
+                        timestamp: timestamp
+                    )
+                default:
+                    continue
+                }
+            }
+            attach(images, role: role, timestamp: timestamp, from: firstMessageIndex)
+        }
+
+        mutating func addCodexLine(_ json: [String: Any], timestamp: Date) {
+            if json["type"] as? String == "turn_context",
+               let model = (json["payload"] as? [String: Any])?["model"] as? String {
+                noteModel(model)
+                return
+            }
+            if json["type"] as? String == "compacted" {
+                transcript.compactions.append(Compaction(timestamp: timestamp, trigger: nil, tokensBefore: nil))
+                if let summary = (json["payload"] as? [String: Any])?["message"] as? String {
+                    addMessage(role: .system, text: summary, timestamp: timestamp)
+                }
+                return
+            }
+            guard json["type"] as? String == "response_item",
+                  let payload = json["payload"] as? [String: Any] else {
+                return
+            }
+            switch payload["type"] as? String {
+            case "message":
+                let role: MessageRole
+                switch payload["role"] as? String {
+                case "user": role = .user
+                case "assistant": role = .assistant
+                default: return
+                }
+                let parts = payload["content"] as? [[String: Any]] ?? []
+                let text = parts.compactMap { $0["text"] as? String }.joined(separator: "\n")
+                let firstMessageIndex = transcript.messages.count
+                addMessage(role: role, text: text, timestamp: timestamp)
+                let images = parts.compactMap { part -> MessageAttachment? in
+                    guard part["type"] as? String == "input_image", let url = part["image_url"] as? String else { return nil }
+                    return TranscriptReader.imageAttachment(fromDataURL: url)
+                }
+                attach(images, role: role, timestamp: timestamp, from: firstMessageIndex)
+            case "function_call":
+                guard let id = payload["call_id"] as? String else { return }
+                startToolCall(
+                    id: id,
+                    name: payload["name"] as? String ?? "tool",
+                    input: payload["arguments"] as? String ?? "",
+                    timestamp: timestamp
+                )
+            case "function_call_output":
+                guard let id = payload["call_id"] as? String else { return }
+                let output: String
+                if let text = payload["output"] as? String {
+                    output = text
+                } else {
+                    output = TranscriptReader.jsonString(payload["output"])
+                }
+                finishToolCall(id: id, output: output, failed: false, timestamp: timestamp)
+            default:
+                return
+            }
+        }
+
+        private mutating func addMessage(role: MessageRole, text: String, timestamp: Date) {
+            let trimmed = text.trimmingCharacters(in: .whitespacesAndNewlines)
+            guard !trimmed.isEmpty else { return }
+            let codeBlocks = role == .assistant ? CodeBlock.extract(from: trimmed) : []
+            append(Message(
+                id: nextMessageId(),
+                role: role,
+                content: trimmed,
+                timestamp: timestamp,
+                parentId: pendingParent,
+                codeBlocks: codeBlocks
+            ))
+        }
+
+        private mutating func startToolCall(id: String, name: String, input: String, timestamp: Date, fileReference: String? = nil, diff: FileDiff? = nil) {
+            let call = ToolCall(name: name, input: input, startedAt: timestamp, diff: diff)
+            toolCallIndex[id] = transcript.toolCalls.count
+            transcript.toolCalls.append(call)
+            if let currentSubagent {
+                transcript.subagentToolCallIds[currentSubagent, default: []].append(call.id)
+            }
+            let attachments = fileReference.map { [MessageAttachment(kind: .file, filePath: $0)] } ?? []
+            append(Message(
+                id: nextMessageId(),
+                role: .tool,
+                content: name,
+                timestamp: timestamp,
+                toolUseId: call.id,
+                parentId: pendingParent,
+                attachments: attachments
+            ))
+        }
+
+        /// Images go on the first message the line produced; an image-only
+        /// prompt gets a message of its own.
+        private mutating func attach(_ images: [MessageAttachment], role: MessageRole, timestamp: Date, from index: Int) {
+            guard !images.isEmpty else { return }
+            if index < transcript.messages.count {
+                transcript.messages[index].attachments.append(contentsOf: images)
+            } else {
+                append(Message(id: nextMessageId(), role: role, content: "", timestamp: timestamp, parentId: pendingParent, attachments: images))
+            }
+        }
+
+        /// The first message from a Claude line reuses the line's uuid so
+        /// `parentUuid` references resolve; later ones get fresh ids.
+        private mutating func nextMessageId() -> UUID {
+            guard let lineId = pendingLineId else { return UUID() }
+            pendingLineId = nil
+            return lineId
+        }
+
+        /// Claude Code writes `<synthetic>` for messages it made up itself.
+        private mutating func noteModel(_ model: String) {
+            guard !model.isEmpty, !model.hasPrefix("<"), !transcript.models.contains(model) else { return }
+            transcript.models.append(model)
+        }
+
+        /// Messages from the same line chain onto each other.
+        private mutating func append(_ message: Message) {
+            transcript.messages.append(message)
+            if isChainingLine {
+                pendingParent = message.id
+            }
+        }
+
+        private mutating func finishToolCall(id: String, output: String, failed: Bool, timestamp: Date) {
+            guard let index = toolCallIndex[id] else { return }
+            transcript.toolCalls[index].output = output
+            transcript.toolCalls[index].completedAt = timestamp
+            transcript.toolCalls[index].status = failed ? .failed : .completed
+            if failed {
+                transcript.toolCalls[index].error = output
+            }
+        }
+    }
+
+    // MARK: - Helpers
+
+    static func imageAttachment(fromDataURL url: String) -> MessageAttachment? {
+        guard url.hasPrefix("data:"), let comma = url.firstIndex(of: ",") else { return nil }
+        let header = url[url.index(url.startIndex, offsetBy: 5)..<comma]
+        guard header.hasSuffix(";base64"), let data = Data(base64Encoded: String(url[url.index(after: comma)...])) else {
+            return nil
+        }
+        return MessageAttachment(kind: .image, mediaType: String(header.dropLast(7)), data: data)
+    }
+
+    private static func resultText(_ content: Any?) -> String {
+        if let text = content as? String { return text }
+        if let parts = content as? [[String: Any]] {
+            return parts.compactMap { $0["text"] as? String }.joined(separator: "\n")
+        }
+        return ""
+    }
+
+    static func jsonString(_ value: Any?) -> String {
+        guard let value, JSONSerialization.isValidJSONObject(value),
+              let data = try? JSONSerialization.data(withJSONObject: value, options: [.sortedKeys]) else {
+            return ""
+        }
+        return String(decoding: data, as: UTF8.self)
+    }
+
+    private static func parseISO8601(_ string: String) -> Date? {
+        let fractional = ISO8601DateFormatter()
+        fractional.formatOptions = [.withInternetDateTime, .withFractionalSeconds]
+        if let date = fractional.date(from: string) { return date }
+        return ISO8601DateFormatter().date(from: string)
+    }
+}
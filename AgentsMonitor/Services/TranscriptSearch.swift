@@ -0,0 +1,125 @@
+import Foundation
+
+/// One hit for a find-in-session query.
+struct TranscriptMatch: Identifiable, Hashable {
+    enum Source: Hashable {
+        case message(MessageRole)
+        case toolInput(String)
+        case toolOutput(String)
+
+        var label: String {
+            switch self {
+            case .message(let role): return role.rawValue
+            case .toolInput(let name): return "\(name) input"
+            case .toolOutput(let name): return "\(name) output"
+            }
+        }
+    }
+
+    let id = UUID()
+    let source: Source
+    let timestamp: Date
+    /// Index into `Transcript.messages` or `Transcript.toolCalls`, depending on `source`.
+    let itemIndex: Int
+    /// Character offset of the match within the searched text.
+    let offset: Int
+    let snippet: String
+
+    var sourceLabel: String { source.label }
+}
+
+/// A web link the agent, the user or a tool output mentioned.
+struct MentionedLink: Identifiable, Hashable {
+    let url: URL
+    let source: TranscriptMatch.Source
+    let timestamp: Date
+    let snippet: String
+
+    var id: URL { url }
+}
+
+/// Case- and diacritic-insensitive search over a session's messages and tool
+/// calls, returning matches in transcript order with surrounding context.
+enum TranscriptSearch {
+    static let contextLength = 40
+
+    /// The first `limit` matches in transcript order. Messages and tool
+    /// calls are listed separately, so everything is collected and ordered
+    /// before the cut.
+    static func search(_ query: String, in transcript: Transcript, limit: Int = 100) -> [TranscriptMatch] {
+        let needle = query.trimmingCharacters(in: .whitespacesAndNewlines)
+        guard !needle.isEmpty else { return [] }
+
+        var matches: [TranscriptMatch] = []
+        func collect(_ text: String, source: TranscriptMatch.Source, timestamp: Date, itemIndex: Int) {
+            var searchRange = text.startIndex..<text.endIndex
+            while let found = text.range(of: needle, options: [.caseInsensitive, .diacriticInsensitive], range: searchRange) {
+                matches.append(TranscriptMatch(
+                    source: source,
+                    timestamp: timestamp,
+                    itemIndex: itemIndex,
+                    offset: text.distance(from: text.startIndex, to: found.lowerBound),
+                    snippet: snippet(of: text, around: found)
+                ))
+                searchRange = found.upperBound..<text.endIndex
+            }
+        }
+
+        for (index, message) in transcript.messages.enumerated() where message.role != .tool {
+            collect(message.content, source: .message(message.role), timestamp: message.timestamp, itemIndex: index)
+        }
+        for (index, call) in transcript.toolCalls.enumerated() {
+            collect(call.input, source: .toolInput(call.name), timestamp: call.startedAt, itemIndex: index)
+            if let output = call.output {
+                collect(output, source: .toolOutput(call.name), timestamp: call.completedAt ?? call.startedAt, itemIndex: index)
+            }
+        }
+        return Array(matches.enumerated()
+            .sorted { ($0.element.timestamp, $0.offset) < ($1.element.timestamp, $1.offset) }
+            .map(\.element)
+            .prefix(limit))
+    }
+
+    private static let linkDetector = try! NSDataDetector(types: NSTextCheckingResult.CheckingType.link.rawValue)
+
+    /// Every distinct http(s) link in the session, at its first mention.
+    /// Terminal hyperlinks (OSC 8) in tool output carry the URL in plain
+    /// text, so they are found the same way as bare URLs. Only text written
+    /// with its scheme counts; the detector would otherwise turn file names
+    /// like `README.md` or `setup.py` into links.
+    static func links(in transcript: Transcript) -> [MentionedLink] {
+        var mentions: [MentionedLink] = []
+        func collect(_ text: String, source: TranscriptMatch.Source, timestamp: Date) {
+            let range = NSRange(text.startIndex..., in: text)
+            for result in linkDetector.matches(in: text, range: range) {
+                guard let url = result.url, url.scheme == "http" || url.scheme == "https",
+                      let found = Range(result.range, in: text),
+                      text[found].range(of: "^https?://", options: [.regularExpression, .caseInsensitive]) != nil else { continue }
+                mentions.append(MentionedLink(url: url, source: source, timestamp: timestamp, snippet: snippet(of: text, around: found)))
+            }
+        }
+
+        for message in transcript.messages where message.role != .tool {
+            collect(message.content, source: .message(message.role), timestamp: message.timestamp)
+        }
+        for call in transcript.toolCalls {
+            collect(call.input, source: .toolInput(call.name), timestamp: call.startedAt)
+            if let output = call.output {
+                collect(output, source: .toolOutput(call.name), timestamp: call.completedAt ?? call.startedAt)
+            }
+        }
+        var seen = Set<URL>()
+        return mentions
+            .sorted { $0.timestamp < $1.timestamp }
+            .filter { seen.insert($0.url).inserted }
+    }
+
+    static func snippet(of text: String, around range: Range<String.Index>) -> String {
+        let start = text.index(range.lowerBound, offsetBy: -contextLength, limitedBy: text.startIndex) ?? text.startIndex
+        let end = text.index(range.upperBound, offsetBy: contextLength, limitedBy: text.endIndex) ?? text.endIndex
+        var snippet = String(text[start..<end]).replacingOccurrences(of: "\n", with: " ")
+        if start > text.startIndex { snippet = "…" + snippet }
+        if end < text.endIndex { snippet += "…" }
+        return snippet
+    }
+}
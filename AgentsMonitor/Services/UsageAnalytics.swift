@@ -0,0 +1,210 @@
+import Foundation
+
+/// Opt-in, local-only record of which app features get used and how sessions
+/// end. Nothing leaves the machine: events are kept in a JSON file in the
+/// app's Application Support folder and only summarized on demand via
+/// `insights()`.
+///
+/// Writes are coalesced: changes within `saveDelay` of each other produce one
+/// write, and `flush()` forces it out before quitting.
+actor UsageAnalytics {
+    enum Feature: String, Codable, CaseIterable {
+        case popoverOpened
+        case manualRefresh
+        case settingsOpened
+        case sourceTabChanged
+        case settingsExported
+        case settingsImported
+    }
+
+    struct SessionOutcome: Codable, Equatable {
+        let sessionId: UUID
+        let agentType: AgentType
+        let status: SessionStatus
+        let startedAt: Date
+        let duration: TimeInterval
+        let totalTokens: Int
+        let cost: Double
+    }
+
+    struct Insights: Equatable {
+        var featureCounts: [Feature: Int] = [:]
+        var sessionCount: Int = 0
+        var failedCount: Int = 0
+        var totalCost: Double = 0
+        var averageDuration: TimeInterval = 0
+        var sessionsByAgent: [AgentType: Int] = [:]
+        var trackingSince: Date?
+
+        var failureRate: Double {
+            sessionCount > 0 ? Double(failedCount) / Double(sessionCount) : 0
+        }
+    }
+
+    private struct Snapshot: Codable {
+        var featureCounts: [String: Int] = [:]
+        var outcomes: [UUID: SessionOutcome] = [:]
+        var trackingSince: Date?
+    }
+
+    static var defaultFileURL: URL {
+        FileManager.default.urls(for: .applicationSupportDirectory, in: .userDomainMask)[0]
+            .appendingPathComponent("AgentsMonitor", isDirectory: true)
+            .appendingPathComponent("usage-analytics.json")
+    }
+
+    /// Where earlier versions kept the file, inside Claude Code's own folder.
+    static var legacyFileURL: URL {
+        AppSettings.load().claudeDirectoryURL()
+            .appendingPathComponent("agents-monitor-analytics.json")
+    }
+
+    private let fileURL: URL?
+    private let legacyFileURL: URL?
+    private let saveDelay: Duration
+    private var snapshot = Snapshot()
+    private var isLoaded = false
+    private var pendingSave: Task<Void, Never>?
+
+    /// Pass `nil` to keep everything in memory (used by tests). A file at
+    /// `legacyFileURL` is moved to `fileURL` the first time it's read.
+    init(fileURL: URL? = UsageAnalytics.defaultFileURL, legacyFileURL: URL? = nil, saveDelay: Duration = .seconds(5)) {
+        self.fileURL = fileURL
+        self.legacyFileURL = legacyFileURL
+        self.saveDelay = saveDelay
+    }
+
+    func record(_ feature: Feature, at date: Date = Date()) {
+        loadIfNeeded()
+        snapshot.featureCounts[feature.rawValue, default: 0] += 1
+        snapshot.trackingSince = snapshot.trackingSince ?? date
+        scheduleSave()
+    }
+
+    /// Stores the latest outcome of every session that has ended. Sessions
+    /// that are resumed and end again overwrite their earlier outcome.
+    func recordOutcomes(of sessions: [Session], at date: Date = Date()) {
+        loadIfNeeded()
+        var changed = false
+        for session in sessions where session.endedAt != nil {
+            let outcome = SessionOutcome(
+                sessionId: session.id,
+                agentType: session.agentType,
+                status: session.status,
+                startedAt: session.startedAt,
+                duration: session.duration(asOf: date),
+                totalTokens: session.metrics.totalTokens,
+                cost: session.metrics.cost
+            )
+            if snapshot.outcomes[session.id] != outcome {
+                snapshot.outcomes[session.id] = outcome
+                changed = true
+            }
+        }
+        guard changed else { return }
+        snapshot.trackingSince = snapshot.trackingSince ?? date
+        scheduleSave()
+    }
+
+    func insights() -> Insights {
+        loadIfNeeded()
+        var insights = Insights()
+        for (raw, count) in snapshot.featureCounts {
+            guard let feature = Feature(rawValue: raw) else { continue }
+            insights.featureCounts[feature] = count
+        }
+
+        let outcomes = Array(snapshot.outcomes.values)
+        insights.sessionCount = outcomes.count
+        insights.failedCount = outcomes.filter { $0.status == .failed }.count
+        insights.totalCost = outcomes.reduce(0) { $0 + $1.cost }
+        if !outcomes.isEmpty {
+            insights.averageDuration = outcomes.reduce(0) { $0 + $1.duration } / Double(outcomes.count)
+        }
+        for outcome in outcomes {
+            insights.sessionsByAgent[outcome.agentType, default: 0] += 1
+        }
+        insights.trackingSince = snapshot.trackingSince
+        return insights
+    }
+
+    func reset() {
+        pendingSave?.cancel()
+        pendingSave = nil
+        snapshot = Snapshot()
+        isLoaded = true
+        guard let fileURL else { return }
+        do {
+            try FileManager.default.removeItem(at: fileURL)
+        } catch let error as NSError where !(error.domain == NSCocoaErrorDomain && error.code == NSFileNoSuchFileError) {
+            AppLogger.logWarning("Failed to delete analytics: \(error.localizedDescription)", context: "UsageAnalytics")
+        } catch {}
+    }
+
+    // MARK: - Persistence
+
+    private func loadIfNeeded() {
+        guard !isLoaded else { return }
+        isLoaded = true
+        guard let fileURL else { return }
+        migrateLegacyFile(to: fileURL)
+
+        let data: Data
+        do {
+            data = try Data(contentsOf: fileURL)
+        } catch let error as NSError where error.domain == NSCocoaErrorDomain && error.code == NSFileReadNoSuchFileError {
+            return
+        } catch {
+            AppLogger.logWarning("Failed to read analytics: \(error.localizedDescription)", context: "UsageAnalytics")
+            return
+        }
+        guard let decoded = try? JSONDecoder().decode(Snapshot.self, from: data) else {
+            AppLogger.logWarning("Corrupt analytics file, starting fresh", context: "UsageAnalytics")
+            return
+        }
+        snapshot = decoded
+    }
+
+    private func migrateLegacyFile(to fileURL: URL) {
+        let fileManager = FileManager.default
+        guard let legacyFileURL, fileManager.fileExists(atPath: legacyFileURL.path),
+              !fileManager.fileExists(atPath: fileURL.path) else {
+            return
+        }
+        do {
+            try fileManager.createDirectory(at: fileURL.deletingLastPathComponent(), withIntermediateDirectories: true)
+            try fileManager.moveItem(at: legacyFileURL, to: fileURL)
+        } catch {
+            AppLogger.logWarning("Failed to move analytics from \(legacyFileURL.path): \(error.localizedDescription)", context: "UsageAnalytics")
+        }
+    }
+
+    /// Writes any pending changes now.
+    func flush() {
+        guard pendingSave != nil else { return }
+        pendingSave?.cancel()
+        pendingSave = nil
+        save()
+    }
+
+    private func scheduleSave() {
+        guard fileURL != nil, pendingSave == nil else { return }
+        let delay = saveDelay
+        pendingSave = Task { [weak self] in
+            try? await Task.sleep(for: delay)
+            guard !Task.isCancelled else { return }
+            await self?.flush()
+        }
+    }
+
+    private func save() {
+        guard let fileURL else { return }
+        do {
+            let data = try JSONEncoder().encode(snapshot)
+            try FileManager.default.createDirectory(at: fileURL.deletingLastPathComponent(), withIntermediateDirectories: true)
+            try data.write(to: fileURL, options: .atomic)
+        } catch {
+            AppLogger.logWarning("Failed to save analytics: \(error.localizedDescription)", context: "UsageAnalytics")
+        }
+    }
+}
@@ -0,0 +1,30 @@
+import Foundation
+
+/// Reads where a running session is working now from the end of its
+/// transcript, so `Session.workingDirectory` follows the agent as it `cd`s
+/// around instead of staying at the folder it started in.
+///
+/// Claude Code stamps `cwd` on every line it writes; Codex repeats it in each
+/// `turn_context`. The last one seen wins.
+enum WorkingDirectoryDetector {
+    static func directory(in content: String, agentType: AgentType) -> URL? {
+        for line in content.split(separator: "\n").reversed() {
+            guard let lineData = line.data(using: .utf8),
+                  let json = try? JSONSerialization.jsonObject(with: lineData) as? [String: Any] else {
+                continue
+            }
+            let cwd: String?
+            if agentType == .codex {
+                cwd = json["type"] as? String == "turn_context"
+                    ? (json["payload"] as? [String: Any])?["cwd"] as? String
+                    : nil
+            } else {
+                cwd = json["cwd"] as? String
+            }
+            if let cwd, cwd.hasPrefix("/") {
+                return URL(fileURLWithPath: cwd)
+            }
+        }
+        return nil
+    }
+}
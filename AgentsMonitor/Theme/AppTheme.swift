@@ -40,6 +40,15 @@ enum AppTheme {
         .failed: .red
     ]
 
+    // MARK: - Timeline Phase Colors
+
+    static let timelinePhaseColors: [TimelinePhase: AppColor] = [
+        .thinking: .purple,
+        .toolExecution: .blue,
+        .waitingForUser: .orange,
+        .idle: AppColor.gray.opacity(0.3)
+    ]
+
     // MARK: - Font Sizes
 
     enum FontSize: Int, CaseIterable {
@@ -101,6 +110,22 @@ enum AppTheme {
         toolCallStatusColors[status] ?? .gray
     }
 
+    static func timelinePhaseColor(for phase: TimelinePhase) -> AppColor {
+        timelinePhaseColors[phase] ?? .gray
+    }
+
+    static func labelColor(for color: SessionLabel.Color) -> AppColor {
+        switch color {
+        case .red: return .red
+        case .orange: return .orange
+        case .yellow: return .yellow
+        case .green: return .green
+        case .blue: return .blue
+        case .purple: return .purple
+        case .gray: return .gray
+        }
+    }
+
     // MARK: - Tab Colors
 
     static let tabSelectedBackground: AppColor = .accentColor
@@ -1,4 +1,5 @@
 import Foundation
+import os
 import SwiftUI
 
 enum SessionSourceTab: String, CaseIterable, Identifiable {
@@ -9,30 +10,93 @@ enum SessionSourceTab: String, CaseIterable, Identifiable {
     var id: Self { self }
 }
 
+struct QuickStats: Equatable {
+    var runningCount: Int = 0
+    var waitingCount: Int = 0
+    var failedCount: Int = 0
+    var todayTokens: Int = 0
+    var todayCost: Double = 0
+    var alertCount: Int = 0
+
+    var needsAttentionCount: Int {
+        waitingCount + failedCount + alertCount
+    }
+}
+
+/// A destructive command spotted in a session that was still active.
+struct CommandAlert: Identifiable, Equatable {
+    let id = UUID()
+    let sessionId: UUID
+    let sessionName: String
+    let command: FlaggedCommand
+}
+
 @Observable
 final class SessionStore {
     // MARK: - Published State
 
     private(set) var sessions: [Session] = []
     var selectedSessionId: UUID?
+    /// A session a relaunch asked to show; the list expands it and clears this.
+    var requestedSessionId: UUID?
     var isLoading: Bool = false
     var error: String?
+    private(set) var isPopoverVisible: Bool = false
+    private(set) var commandAlerts: [CommandAlert] = []
+    /// Live sessions running well past their project's usual duration.
+    private(set) var durationAlerts: [DurationAnomaly] = []
+    private(set) var startupDiagnostics = StartupDiagnostics()
+    /// Saved dashboard views, mirrored from `AppSettings.viewPresets`.
+    private(set) var viewPresets: [ViewPreset] = []
+    /// User-set labels by session id; applied to sessions on every refresh.
+    private(set) var sessionLabels: [UUID: SessionLabel] = [:]
+    /// Issue keys the user linked by hand, by session id; win over detected keys.
+    private(set) var linkedIssueKeys: [UUID: String] = [:]
+    /// Agents whose data folder was read earlier and can't be reached now, e.g.
+    /// an unmounted disk or a dropped network share. Their last sessions stay
+    /// listed until it comes back.
+    private(set) var offlineSources: Set<AgentType> = []
 
     // Usage API
     var usageData: AnthropicUsage?
     var usageError: String?
     var codexUsage: CodexRateLimits?
+    /// Pace and projected run-out for each usage-limit window, refreshed with the usage data.
+    private(set) var quotaStatuses: [QuotaStatus] = []
 
     // MARK: - Dependencies
 
     private let sessionService: ClaudeSessionService
     private let codexService: CodexSessionService
     private let usageService: any UsageServiceProviding
+    private let analytics: UsageAnalytics
     private let environment: AppEnvironment
 
     // Token cost cache: jsonlPath → (mtime, summary)
     private var costCache: [String: CostCacheEntry] = [:]
+    // Dangerous command scan results: jsonlPath → (mtime, flagged commands)
+    private var flaggedCommandCache: [String: (mtime: Int64, commands: [FlaggedCommand])] = [:]
+    private let transcriptCache = TranscriptCache()
     private var costCalculationTask: Task<Void, Never>?
+    private var backgroundRefreshTask: Task<Void, Never>?
+    private var settingsWatcher: SettingsWatcher?
+    private var detector = DangerousCommandDetector()
+    private var quotaTracker = QuotaTracker()
+    private var contextWarnedSessionIds: Set<UUID> = []
+    private var durationWarnedSessionIds: Set<UUID> = []
+    // Sessions seen running that have since ended, until their note and issue comment go out
+    private var sessionsAwaitingCompletion: Set<UUID> = []
+    // Notes that couldn't be written (e.g. the vault is on a share that dropped out), with when to retry
+    private var pendingNoteRetries: [UUID: (attempts: Int, retryAt: Date)] = [:]
+    // Data folders sessions were last read from, to tell an outage from an empty folder
+    private var readDirectories: [AgentType: URL] = [:]
+    // Configured MCP servers: "<agent>|<project path>" → names, valid while the config mtimes match
+    private var mcpServerCache: [String: [String]] = [:]
+    private var mcpConfigStamp: [Int64] = []
+    private let createdAt = CFAbsoluteTimeGetCurrent()
+    static let backgroundRefreshInterval: TimeInterval = 60
+    static let maxNoteAttempts = 5
+    static let offlineCheckTimeout: TimeInterval = 2
 
     struct CostCacheEntry: Codable {
         let mtime: Int64
@@ -40,8 +104,7 @@ final class SessionStore {
     }
 
     private static var cacheFileURL: URL {
-        URL(fileURLWithPath: FileUtilities.realHomeDirectory())
-            .appendingPathComponent(".claude")
+        AppSettings.load().claudeDirectoryURL()
             .appendingPathComponent("agents-monitor-cost-cache.json")
     }
 
@@ -55,14 +118,32 @@ final class SessionStore {
         sessionService: ClaudeSessionService = ClaudeSessionService(),
         codexService: CodexSessionService = CodexSessionService(),
         usageService: any UsageServiceProviding = AnthropicUsageService(),
+        analytics: UsageAnalytics? = nil,
         environment: AppEnvironment = .current
     ) {
         self.sessionService = sessionService
         self.codexService = codexService
         self.usageService = usageService
+        self.analytics = analytics ?? UsageAnalytics(
+            fileURL: environment.isTesting ? nil : UsageAnalytics.defaultFileURL,
+            legacyFileURL: environment.isTesting ? nil : UsageAnalytics.legacyFileURL
+        )
         self.environment = environment
         if !environment.isTesting {
+            let start = CFAbsoluteTimeGetCurrent()
             loadCostCache()
+            startupDiagnostics.costCacheLoad = CFAbsoluteTimeGetCurrent() - start
+            startupDiagnostics.costCacheEntries = costCache.count
+        }
+        if !environment.isUnitTesting {
+            viewPresets = AppSettings.load().viewPresets
+            sessionLabels = SessionLabel.loadAll()
+            linkedIssueKeys = IssueTracker.loadLinkedKeys()
+            settingsWatcher = SettingsWatcher { [weak self] old, new in
+                Task { @MainActor in
+                    self?.applySettingsChange(from: old, to: new)
+                }
+            }
         }
 
         Task {
@@ -90,6 +171,18 @@ final class SessionStore {
         sessions.filter { $0.status == .running || $0.status == .waiting }
     }
 
+    /// Running sessions allowed to edit or run commands without approval.
+    var autonomousSessions: [Session] {
+        activeSessions.filter { $0.operatingMode?.isAutonomous == true }
+    }
+
+    /// Sessions that look blocked on a permission prompt, oldest request first.
+    var pendingPermissionSessions: [Session] {
+        sessions
+            .filter { $0.pendingPermission != nil }
+            .sorted { ($0.pendingPermission?.requestedAt ?? .distantPast) < ($1.pendingPermission?.requestedAt ?? .distantPast) }
+    }
+
     var completedSessions: [Session] {
         sessions.filter { $0.status == .completed }
     }
@@ -126,6 +219,25 @@ final class SessionStore {
         Self.formatCost(aggregateCost)
     }
 
+    /// Single-pass summary for the menu bar popover, cheap enough to poll on every refresh tick.
+    var quickStats: QuickStats {
+        let startOfToday = Calendar.current.startOfDay(for: environment.now)
+        var stats = QuickStats(alertCount: commandAlerts.count)
+        for session in sessions {
+            switch session.status {
+            case .running: stats.runningCount += 1
+            case .waiting: stats.waitingCount += 1
+            case .failed: stats.failedCount += 1
+            default: break
+            }
+            if session.startedAt >= startOfToday {
+                stats.todayTokens += session.metrics.totalTokens
+                stats.todayCost += session.metrics.cost
+            }
+        }
+        return stats
+    }
+
     static func formatTokenCount(_ total: Int) -> String {
         if total >= 1_000_000 {
             return String(format: "%.1fM", Double(total) / 1_000_000)
@@ -195,11 +307,28 @@ final class SessionStore {
         sessions.removeAll()
         selectedSessionId = nil
         costCache.removeAll()
+        Task { await transcriptCache.removeAll() }
         do {
             try FileManager.default.removeItem(at: Self.cacheFileURL)
         } catch let error as NSError where !(error.domain == NSCocoaErrorDomain && error.code == NSFileNoSuchFileError) {
             AppLogger.logWarning("Failed to delete cost cache: \(error.localizedDescription)", context: "SessionStore")
         } catch {}
+        if !isRunningTests {
+            AttachmentStore.removeAll()
+        }
+    }
+
+    /// Stops background cost calculation and writes out whatever has been
+    /// cached so far, so quitting mid-scan doesn't discard finished parses.
+    func prepareForTermination() async {
+        backgroundRefreshTask?.cancel()
+        backgroundRefreshTask = nil
+        costCalculationTask?.cancel()
+        costCalculationTask = nil
+        saveCostCache()
+
+        // The analytics actor coalesces writes; push out the pending one.
+        await analytics.flush()
     }
 
     // MARK: - Refresh & Loading
@@ -230,26 +359,60 @@ final class SessionStore {
         error = nil
 
         await AppLogger.measureAsync("load sessions") {
-            let defaults = UserDefaults.standard
-            let showAll = !defaults.bool(forKey: "activeOnly")
-            let showSidechains = defaults.bool(forKey: "showSidechains")
-            let codexEnabled = Self.boolPreference(forKey: "codexEnabled", defaultValue: true, defaults: defaults)
-            let claudeCodeEnabled = Self.boolPreference(forKey: "claudeCodeEnabled", defaultValue: true, defaults: defaults)
+            let settings = AppSettings.load()
+            let showAll = !settings.activeOnly
+            let showSidechains = settings.showSidechains
+            let codexEnabled = settings.codexEnabled
+            let claudeCodeEnabled = settings.claudeCodeEnabled
 
             if !codexEnabled { codexUsage = nil }
 
-            async let claudeSessionsTask: [Session] = claudeCodeEnabled
-                ? sessionService.discoverSessions(showAll: showAll, showSidechains: showSidechains)
-                : []
-            async let codexSessionsTask: [Session] = codexEnabled
-                ? codexService.discoverSessions(showAll: showAll, showSidechains: showSidechains)
-                : []
+            let claudeURL = settings.claudeDirectoryURL()
+            let codexURL = settings.codexDirectoryURL()
+            await sessionService.setDirectory(claudeURL)
+            await codexService.setDirectory(codexURL)
+            let claudeData = claudeURL.appendingPathComponent("projects")
+            let codexData = codexURL.appendingPathComponent("sessions")
+            let claudeLastRead = claudeCodeEnabled ? readDirectories[.claudeCode] : nil
+            let codexLastRead = codexEnabled ? readDirectories[.codex] : nil
+            async let claudeOfflineCheck = Self.isOffline(claudeData, lastRead: claudeLastRead, timeout: Self.offlineCheckTimeout)
+            async let codexOfflineCheck = Self.isOffline(codexData, lastRead: codexLastRead, timeout: Self.offlineCheckTimeout)
+            let claudeOffline = await claudeOfflineCheck
+            let codexOffline = await codexOfflineCheck
+
+            async let claudeSessionsTask = Self.timed {
+                claudeCodeEnabled && !claudeOffline
+                    ? await sessionService.discoverSessions(showAll: showAll, showSidechains: showSidechains)
+                    : []
+            }
+            async let codexSessionsTask = Self.timed {
+                codexEnabled && !codexOffline
+                    ? await codexService.discoverSessions(showAll: showAll, showSidechains: showSidechains)
+                    : []
+            }
             async let codexLimitsTask: CodexRateLimits? = codexEnabled
                 ? codexService.fetchRateLimits()
                 : nil
 
-            var discovered = await claudeSessionsTask + codexSessionsTask
+            let redactor = SecretRedactor(customPatterns: settings.redactionPatterns)
+            detector = DangerousCommandDetector(customPatterns: settings.dangerousCommandPatterns)
+            let (claudeFound, claudeDuration) = await claudeSessionsTask
+            let (codexFound, codexDuration) = await codexSessionsTask
+            // Keep showing what was last read from a folder that dropped out.
+            let claudeSessions = claudeOffline ? sessions.filter { $0.agentType == .claudeCode } : claudeFound
+            let codexSessions = codexOffline ? sessions.filter { $0.agentType == .codex } : codexFound
+            if !claudeOffline && !claudeSessions.isEmpty { readDirectories[.claudeCode] = claudeData }
+            if !codexOffline && !codexSessions.isEmpty { readDirectories[.codex] = codexData }
+            let offline: Set<AgentType> = Set([claudeOffline ? .claudeCode : nil, codexOffline ? .codex : nil].compactMap { $0 })
+            if offline != offlineSources {
+                for agent in offline.subtracting(offlineSources) {
+                    AppLogger.logWarning("\(agent.rawValue) data folder is offline; keeping the last sessions read", context: "SessionStore")
+                }
+                offlineSources = offline
+            }
+            var discovered = (claudeSessions + codexSessions).map(redactor.redacted)
             codexUsage = await codexLimitsTask
+            updateQuotaStatuses()
             discovered.sort { $0.startedAt > $1.startedAt }
 
             // Apply cached costs immediately
@@ -259,9 +422,30 @@ final class SessionStore {
                 if let cached = costCache[jsonlPath], cached.mtime == mtime {
                     applyTokenSummary(cached.summary, to: &discovered[i])
                 }
+                if let flagged = flaggedCommandCache[jsonlPath], flagged.mtime == mtime {
+                    discovered[i].flaggedCommands = flagged.commands
+                }
+            }
+            applyConfiguredMCPServers(to: &discovered)
+            for i in discovered.indices {
+                discovered[i].label = sessionLabels[discovered[i].id]
+                discovered[i].issueKey = linkedIssueKeys[discovered[i].id]
+                    ?? IssueTracker.detectKey(branch: discovered[i].gitBranch, prompt: discovered[i].firstPrompt)
             }
 
+            let wereActive = Set(sessions.filter { $0.endedAt == nil }.map(\.id))
+            sessionsAwaitingCompletion.formUnion(discovered.filter { $0.endedAt != nil && wereActive.contains($0.id) }.map(\.id))
             sessions = discovered
+            checkDurationAnomalies()
+
+            if startupDiagnostics.timeToFirstSessions == nil {
+                recordStartupDiagnostics(
+                    claudeDuration: claudeDuration,
+                    codexDuration: codexDuration,
+                    claudeCount: claudeSessions.count,
+                    codexCount: codexSessions.count
+                )
+            }
 
             if let current = selectedSessionId, sessions.contains(where: { $0.id == current }) {
                 // Keep selection
@@ -279,23 +463,40 @@ final class SessionStore {
             return
         }
         costCalculationTask = Task.detached(priority: .utility) {
-            let sessionMeta: [(id: UUID, jsonlPath: String, mtime: Int64, agentType: AgentType)] = await MainActor.run {
-                self.sessions.compactMap { session in
-                    guard let path = session.jsonlPath else { return nil }
-                    let cached = self.costCache[path]?.mtime == session.fileMtime
-                    guard !cached else { return nil }
-                    return (id: session.id, jsonlPath: path, mtime: session.fileMtime, agentType: session.agentType)
-                }
+            let (sessionMeta, detector) = await MainActor.run {
+                let meta: [(id: UUID, jsonlPath: String, mtime: Int64, agentType: AgentType, needsCost: Bool, needsScan: Bool)] =
+                    self.sessions.compactMap { session in
+                        guard let path = session.jsonlPath else { return nil }
+                        let needsCost = self.costCache[path]?.mtime != session.fileMtime
+                        let needsScan = self.flaggedCommandCache[path]?.mtime != session.fileMtime
+                        guard needsCost || needsScan else { return nil }
+                        return (id: session.id, jsonlPath: path, mtime: session.fileMtime, agentType: session.agentType,
+                                needsCost: needsCost, needsScan: needsScan)
+                    }
+                return (meta, self.detector)
             }
 
             for entry in sessionMeta {
                 guard !Task.isCancelled else { break }
 
-                let summary: SessionTokenSummary?
+                if entry.needsScan {
+                    let flagged = detector.scan(jsonlPath: entry.jsonlPath, agentType: entry.agentType)
+                    await MainActor.run {
+                        self.applyFlaggedCommands(flagged, jsonlPath: entry.jsonlPath, mtime: entry.mtime, sessionId: entry.id)
+                    }
+                }
+
+                guard entry.needsCost else { continue }
+
+                let reported: SessionTokenSummary?
                 if entry.agentType == .codex {
-                    summary = TokenCostCalculator.calculateCodex(jsonlPath: entry.jsonlPath)?.tokenSummary
+                    reported = TokenCostCalculator.calculateCodex(jsonlPath: entry.jsonlPath)?.tokenSummary
                 } else {
-                    summary = TokenCostCalculator.calculate(jsonlPath: entry.jsonlPath)
+                    reported = TokenCostCalculator.calculate(jsonlPath: entry.jsonlPath)
+                }
+                // No usage recorded: estimate from the conversation instead of showing zero.
+                let summary = TokenEstimator.summary(reported: reported) {
+                    TranscriptReader.read(jsonlPath: entry.jsonlPath, agentType: entry.agentType)
                 }
 
                 if let summary {
@@ -311,6 +512,8 @@ final class SessionStore {
             await MainActor.run {
                 self.saveCostCache()
             }
+            await self.recordSessionOutcomes()
+            await self.handleCompletedSessions()
         }
     }
 
@@ -319,12 +522,576 @@ final class SessionStore {
         do {
             usageData = try await usageService.fetchUsage()
             usageError = nil
+            updateQuotaStatuses()
         } catch {
             usageError = error.localizedDescription
             AppLogger.logWarning("Usage API: \(error.localizedDescription)", context: "fetchUsageData")
         }
     }
 
+    /// Live sessions close enough to their context limit that earlier turns may soon be dropped.
+    var sessionsNearContextLimit: [Session] {
+        sessions.filter { $0.endedAt == nil && $0.metrics.isNearContextLimit }
+    }
+
+    func quotaStatus(for provider: AgentType, window: String) -> QuotaStatus? {
+        quotaStatuses.first { $0.provider == provider && $0.window == window }
+    }
+
+    /// Samples the current usage windows. Called whenever either provider's
+    /// usage is refetched; an unchanged window just adds a flat sample.
+    @MainActor
+    private func updateQuotaStatuses() {
+        let now = environment.now
+        var statuses: [QuotaStatus] = []
+        if let usage = usageData {
+            statuses.append(quotaTracker.record(usage.fiveHour, provider: .claudeCode, label: "5-hour", at: now))
+            statuses.append(quotaTracker.record(usage.sevenDay, provider: .claudeCode, label: "7-day", at: now))
+        }
+        if let codex = codexUsage {
+            statuses.append(quotaTracker.record(codex.primary, provider: .codex, label: "5-hour", at: now))
+            statuses.append(quotaTracker.record(codex.secondary, provider: .codex, label: "7-day", at: now))
+        }
+        quotaStatuses = statuses
+    }
+
+    // MARK: - Transcripts
+
+    /// Loads the full transcript for `session`, redacted the same way as the
+    /// session list. Parsed once per change to the file; see `TranscriptCache`.
+    func transcript(for session: Session) async -> Transcript? {
+        guard let path = session.jsonlPath else { return nil }
+        return await transcriptCache.transcript(
+            at: path,
+            agentType: session.agentType,
+            redactionPatterns: AppSettings.load().redactionPatterns
+        )
+    }
+
+    /// Images pasted into the session and files its tools touched, in transcript order.
+    func attachments(for session: Session) async -> [MessageAttachment] {
+        guard let transcript = await transcript(for: session) else { return [] }
+        return Self.attachments(in: transcript)
+    }
+
+    /// Sub-agents the session fanned work out to, each with its own tool calls.
+    func children(of session: Session) async -> [SubagentRun] {
+        await transcript(for: session)?.subagents ?? []
+    }
+
+    /// The listed session whose Task call spawned `session`, for sub-agent transcripts.
+    func parent(of session: Session) -> Session? {
+        session.parentSessionId.flatMap { id in sessions.first { $0.id == id } }
+    }
+
+    /// Where the session's time went, phase by phase; a live session's
+    /// current phase runs up to now.
+    func timeline(for session: Session) async -> [TimelineSegment] {
+        guard let transcript = await transcript(for: session) else { return [] }
+        return SessionTimeline.segments(for: transcript, now: session.endedAt == nil ? environment.now : nil)
+    }
+
+    static func attachments(in transcript: Transcript) -> [MessageAttachment] {
+        var seenPaths = Set<String>()
+        return transcript.messages.flatMap(\.attachments).filter { attachment in
+            guard let path = attachment.filePath else { return true }
+            return seenPaths.insert(path).inserted
+        }
+    }
+
+    /// The session in Claude Code's JSONL layout, redacted like any other
+    /// transcript read. Pass an `anonymizer` to strip the home folder and
+    /// user name as well.
+    func claudeJSONL(for session: Session, anonymizer: PathAnonymizer? = nil) async -> String? {
+        guard let transcript = await transcript(for: session) else { return nil }
+        let jsonl = ClaudeTranscriptExporter.jsonl(for: transcript, session: session)
+        return anonymizer?.anonymize(jsonl) ?? jsonl
+    }
+
+    /// A sanitized copy of the session for sharing, as Claude Code JSONL.
+    func shareCopy(for session: Session, options: ShareCopy.Options = ShareCopy.Options()) async -> String? {
+        guard let transcript = await transcript(for: session) else { return nil }
+        return ShareCopy.jsonl(from: transcript, session: session, options: options)
+    }
+
+    func search(in session: Session, query: String) async -> [TranscriptMatch] {
+        guard let transcript = await transcript(for: session) else { return [] }
+        return TranscriptSearch.search(query, in: transcript)
+    }
+
+    /// Sends the listed sessions to the configured OpenTelemetry collector as
+    /// traces and returns how many went out.
+    func exportTelemetry() async throws -> Int {
+        let endpoint = AppSettings.load().otlpEndpoint
+        guard OTLPExporter.tracesURL(for: endpoint) != nil else {
+            throw OTLPExportError.invalidEndpoint(endpoint)
+        }
+        let listed = await listedTranscripts()
+        let headers = OTLPExporter.headers(from: KeychainStore.token(for: OTLPExporter.keychainAccount) ?? "")
+        for batch in OTLPExporter.batches(of: listed) {
+            try await OTLPExporter.send(OTLPExporter.payload(for: batch, now: environment.now), to: endpoint, headers: headers)
+        }
+        return listed.count
+    }
+
+    /// Writes the listed sessions with their metrics, messages and tool calls
+    /// to a SQLite file at `url`, replacing it, and returns how many went in.
+    func exportAnalyticsDatabase(to url: URL) async throws -> Int {
+        let batch = await listedTranscripts()
+        try await Task.detached(priority: .utility) {
+            try AnalyticsDatabaseExporter.export(batch, to: url)
+        }.value
+        return batch.count
+    }
+
+    /// Writes `session` as a Markdown note into the configured notes folder.
+    @discardableResult
+    func saveNote(for session: Session) async throws -> URL? {
+        guard let folder = AppSettings.load().notesFolderURL(),
+              let transcript = await transcript(for: session) else {
+            return nil
+        }
+        let now = environment.now
+        return try await Task.detached(priority: .utility) {
+            try MarkdownNoteExporter.write(session, transcript: transcript, toFolder: folder, now: now)
+        }.value
+    }
+
+    /// Posts the session's report as a comment on its issue; returns the key.
+    @discardableResult
+    func postIssueComment(for session: Session) async throws -> String {
+        let settings = AppSettings.load()
+        guard let tracker = settings.connectedIssueTracker, let issueKey = session.issueKey else {
+            throw IssueTrackerError.notConfigured
+        }
+        guard let transcript = await transcript(for: session) else {
+            throw IssueTrackerError.rejected("The session's transcript could not be read")
+        }
+        let body = SessionReport.markdown(for: session, transcript: transcript, now: environment.now)
+        try await tracker.postComment(body, issueKey: issueKey, settings: settings)
+        return issueKey
+    }
+
+    /// Posts the session's report as a comment on the last pull request it
+    /// links to; returns that pull request.
+    @discardableResult
+    func postPullRequestComment(for session: Session) async throws -> PullRequestLink {
+        guard let transcript = await transcript(for: session) else {
+            throw GitHubError.noPullRequest
+        }
+        guard let pullRequest = GitHubPullRequests.linkedPullRequest(in: transcript, projectPath: session.projectPath) else {
+            throw GitHubError.noPullRequest
+        }
+        let body = SessionReport.markdown(for: session, transcript: transcript, now: environment.now)
+        try await GitHubPullRequests.postComment(body, on: pullRequest)
+        return pullRequest
+    }
+
+    /// Notes waiting for the notes folder to come back.
+    var pendingNoteCount: Int { pendingNoteRetries.count }
+
+    /// A data folder sessions were read from that can't be reached now. A
+    /// folder never read from is just empty or misconfigured, not offline.
+    static func isOffline(_ directory: URL, lastRead: URL?, fileManager: FileManager = .default) -> Bool {
+        guard let lastRead, lastRead == directory else { return false }
+        return !fileManager.isReadableFile(atPath: directory.path)
+    }
+
+    /// `isOffline` on a background queue. A dropped network share can hang
+    /// the check for a long time, so one that hasn't answered within
+    /// `timeout` seconds counts as offline.
+    static func isOffline(_ directory: URL, lastRead: URL?, timeout: TimeInterval) async -> Bool {
+        guard let lastRead, lastRead == directory else { return false }
+        return await withCheckedContinuation { continuation in
+            let resumed = OSAllocatedUnfairLock(initialState: false)
+            let finish: @Sendable (Bool) -> Void = { offline in
+                let first = resumed.withLock { wasResumed in
+                    defer { wasResumed = true }
+                    return !wasResumed
+                }
+                if first { continuation.resume(returning: offline) }
+            }
+            DispatchQueue.global(qos: .utility).async {
+                finish(isOffline(directory, lastRead: lastRead))
+            }
+            DispatchQueue.global(qos: .utility).asyncAfter(deadline: .now() + timeout) {
+                finish(true)
+            }
+        }
+    }
+
+    /// Backs off from 30 seconds, doubling per failed attempt, up to 15 minutes.
+    static func noteRetryDelay(afterAttempt attempt: Int) -> TimeInterval {
+        min(30 * pow(2, Double(max(attempt, 1) - 1)), 900)
+    }
+
+    @MainActor
+    private func writeNote(for session: Session, attempt: Int) async {
+        do {
+            try await saveNote(for: session)
+            pendingNoteRetries[session.id] = nil
+        } catch {
+            guard attempt < Self.maxNoteAttempts else {
+                pendingNoteRetries[session.id] = nil
+                AppLogger.logWarning("Giving up on the note for \(session.name) after \(attempt) attempts: \(error.localizedDescription)", context: "SessionStore")
+                return
+            }
+            pendingNoteRetries[session.id] = (attempt, environment.now.addingTimeInterval(Self.noteRetryDelay(afterAttempt: attempt)))
+            AppLogger.logWarning("Cannot write note for \(session.name), will retry: \(error.localizedDescription)", context: "SessionStore")
+        }
+    }
+
+    @MainActor
+    private func handleCompletedSessions() async {
+        let now = environment.now
+        for (id, retry) in pendingNoteRetries where retry.retryAt <= now {
+            if let session = sessions.first(where: { $0.id == id }) {
+                await writeNote(for: session, attempt: retry.attempts + 1)
+            } else {
+                pendingNoteRetries[id] = nil
+            }
+        }
+
+        guard !sessionsAwaitingCompletion.isEmpty else { return }
+        let ids = sessionsAwaitingCompletion
+        sessionsAwaitingCompletion.removeAll()
+        let settings = AppSettings.load()
+        for session in sessions where ids.contains(session.id) {
+            if settings.writeNotesOnCompletion {
+                await writeNote(for: session, attempt: 1)
+            }
+            // Detected keys can be wrong; only post to issues the user linked or confirmed.
+            if settings.postIssueCommentsOnCompletion, settings.connectedIssueTracker != nil, linkedIssueKeys[session.id] != nil {
+                do {
+                    try await postIssueComment(for: session)
+                } catch {
+                    AppLogger.logWarning("Cannot comment on \(session.issueKey ?? "issue"): \(error.localizedDescription)", context: "SessionStore")
+                }
+            }
+            if settings.postPullRequestCommentsOnCompletion, KeychainStore.hasToken(for: GitHubPullRequests.keychainAccount) {
+                do {
+                    try await postPullRequestComment(for: session)
+                } catch GitHubError.noPullRequest {
+                    // Most sessions never open a pull request.
+                } catch {
+                    AppLogger.logWarning("Cannot comment on the pull request for \(session.name): \(error.localizedDescription)", context: "SessionStore")
+                }
+            }
+        }
+    }
+
+    /// A batch for one pass over many transcripts; see `TranscriptBatch`.
+    func makeTranscriptBatch() -> TranscriptBatch {
+        TranscriptBatch(cache: transcriptCache, redactionPatterns: AppSettings.load().redactionPatterns)
+    }
+
+    private func listedTranscripts() async -> [(session: Session, transcript: Transcript)] {
+        await makeTranscriptBatch().transcripts(for: sessions)
+    }
+
+    // MARK: - Diagnostics
+
+    private static func timed<T>(_ work: () async -> T) async -> (T, TimeInterval) {
+        let start = CFAbsoluteTimeGetCurrent()
+        let result = await work()
+        return (result, CFAbsoluteTimeGetCurrent() - start)
+    }
+
+    private func recordStartupDiagnostics(claudeDuration: TimeInterval, codexDuration: TimeInterval, claudeCount: Int, codexCount: Int) {
+        startupDiagnostics.claudeDiscovery = claudeDuration
+        startupDiagnostics.codexDiscovery = codexDuration
+        startupDiagnostics.claudeSessionCount = claudeCount
+        startupDiagnostics.codexSessionCount = codexCount
+        startupDiagnostics.timeToFirstSessions = CFAbsoluteTimeGetCurrent() - createdAt
+
+        let diagnostics = startupDiagnostics
+        if diagnostics.isSlow {
+            AppLogger.logWarning(
+                "Slow startup: \(StartupDiagnostics.format(diagnostics.timeToFirstSessions ?? 0)) to first sessions "
+                    + "(Claude \(claudeCount) in \(StartupDiagnostics.format(claudeDuration)), "
+                    + "Codex \(codexCount) in \(StartupDiagnostics.format(codexDuration)), "
+                    + "cost cache \(diagnostics.costCacheEntries) in \(StartupDiagnostics.format(diagnostics.costCacheLoad)))",
+                context: "SessionStore"
+            )
+        }
+    }
+
+    /// Installed agents, sign-in state and next steps, for an empty session list.
+    func probeEnvironment() async -> EnvironmentProbe {
+        await Task.detached(priority: .utility) {
+            EnvironmentProbe.run()
+        }.value
+    }
+
+    @MainActor
+    func healthCheck() async -> HealthReport {
+        let settings = AppSettings.load()
+        var directories: [(agent: AgentType, url: URL)] = []
+        if settings.claudeCodeEnabled { directories.append((.claudeCode, settings.claudeDirectoryURL())) }
+        if settings.codexEnabled { directories.append((.codex, settings.codexDirectoryURL())) }
+        let cacheDirectory = Self.cacheFileURL.deletingLastPathComponent()
+        let cachedPaths = Array(Set(costCache.keys).union(flaggedCommandCache.keys))
+        let runningPaths = activeSessions.compactMap(\.jsonlPath)
+        return await Task.detached(priority: .utility) {
+            let capacity = try? cacheDirectory.resourceValues(forKeys: [.volumeAvailableCapacityForImportantUsageKey])
+                .volumeAvailableCapacityForImportantUsage
+            return HealthReport.run(
+                dataDirectories: directories,
+                cacheDirectory: cacheDirectory,
+                cachedPaths: cachedPaths,
+                runningSessionPaths: runningPaths,
+                availableCapacity: capacity
+            )
+        }.value
+    }
+
+    /// Drops cached results for transcripts that no longer exist, then refreshes.
+    @MainActor
+    func repairCaches() async {
+        let fileManager = FileManager.default
+        let before = costCache.count + flaggedCommandCache.count
+        costCache = costCache.filter { fileManager.fileExists(atPath: $0.key) }
+        flaggedCommandCache = flaggedCommandCache.filter { fileManager.fileExists(atPath: $0.key) }
+        let removed = before - costCache.count - flaggedCommandCache.count
+        if removed > 0 {
+            AppLogger.logWarning("Dropped \(removed) cache entries for deleted transcripts", context: "SessionStore")
+            saveCostCache()
+        }
+        await refresh()
+    }
+
+    @MainActor
+    func memoryReport() async -> MemoryReport {
+        var report = MemoryReport(residentBytes: MemoryReport.currentResidentBytes())
+        report.sessionCount = sessions.count
+        report.costCacheEntries = costCache.count
+        report.flaggedCommandEntries = flaggedCommandCache.count
+        report.claudeCacheEntries = await sessionService.cachedEntryCount
+        report.codexCacheEntries = await codexService.cachedEntryCount
+        return report
+    }
+
+    // MARK: - Dangerous Commands
+
+    /// Records a transcript's scan result and raises an alert for each newly
+    /// flagged command in a session that has not ended yet.
+    @MainActor
+    private func applyFlaggedCommands(_ found: [FlaggedCommand], jsonlPath: String, mtime: Int64, sessionId: UUID) {
+        // Risky commands tend to carry credentials (`curl -H "Authorization: …"`).
+        let redactor = SecretRedactor(customPatterns: AppSettings.load().redactionPatterns)
+        let flagged = found.map { redactor.redacted($0) }
+        let previous = flaggedCommandCache[jsonlPath]?.commands ?? []
+        flaggedCommandCache[jsonlPath] = (mtime: mtime, commands: flagged)
+        guard let idx = sessions.firstIndex(where: { $0.id == sessionId }) else { return }
+        sessions[idx].flaggedCommands = flagged
+
+        guard sessions[idx].endedAt == nil else { return }
+        let seen = Set(previous)
+        for command in flagged where !seen.contains(command) {
+            commandAlerts.append(CommandAlert(sessionId: sessionId, sessionName: sessions[idx].name, command: command))
+            AppLogger.logWarning("\(command.rule) in \(sessions[idx].name): \(command.command)", context: "DangerousCommandDetector")
+        }
+    }
+
+    @MainActor
+    func dismissCommandAlerts() {
+        commandAlerts.removeAll()
+    }
+
+    // MARK: - Duration Anomalies
+
+    /// Raises an alert, once per session, for each live session that has run
+    /// `DurationBaseline.anomalyFactor` times its usual duration; drops alerts
+    /// for sessions that have since ended.
+    @MainActor
+    private func checkDurationAnomalies() {
+        let live = Set(sessions.filter { $0.endedAt == nil }.map(\.id))
+        durationAlerts.removeAll { !live.contains($0.sessionId) }
+        for anomaly in DurationBaseline.anomalies(in: sessions, now: environment.now)
+        where durationWarnedSessionIds.insert(anomaly.sessionId).inserted {
+            durationAlerts.append(anomaly)
+            AppLogger.logWarning("\(anomaly.sessionName): \(anomaly.message(asOf: environment.now))", context: "SessionStore")
+        }
+    }
+
+    @MainActor
+    func dismissDurationAlerts() {
+        durationAlerts.removeAll()
+    }
+
+    // MARK: - Popover Visibility
+
+    /// Tracks whether the popover is on screen. While it is closed the view's
+    /// refresh loop is torn down, so the store keeps a slower loop of its own
+    /// to hold the menu bar label current.
+    @MainActor
+    func setPopoverVisible(_ visible: Bool) {
+        isPopoverVisible = visible
+        backgroundRefreshTask?.cancel()
+        backgroundRefreshTask = nil
+
+        if visible {
+            recordFeatureUse(.popoverOpened)
+            Task { await refresh() }
+            return
+        }
+
+        guard !isRunningTests, AppSettings.load().refreshInBackground else { return }
+
+        backgroundRefreshTask = Task { [weak self] in
+            while !Task.isCancelled {
+                try? await Task.sleep(for: .seconds(Self.backgroundRefreshInterval))
+                guard !Task.isCancelled, let self else { return }
+                await self.refresh()
+            }
+        }
+    }
+
+    // MARK: - Insights
+
+    // Each takes the `batch` a view shares across them, so showing them all
+    // reads every transcript once.
+
+    /// When the listed sessions were busy, by weekday and hour; `range`
+    /// limits it to, say, the last 30 days. Only non-idle timeline stretches
+    /// count, so a session left open overnight doesn't fill the small hours.
+    func activityHeatmap(in range: DateInterval? = nil, calendar: Calendar = .current, batch: TranscriptBatch? = nil) async -> ActivityHeatmap {
+        let now = environment.now
+        // Only sessions overlapping the range can count; skip reading the rest.
+        let candidates = sessions.filter { session in
+            guard let range else { return true }
+            return session.startedAt <= range.end && (session.endedAt ?? now) >= range.start
+        }
+        let intervals = await (batch ?? makeTranscriptBatch()).transcripts(for: candidates).flatMap { entry in
+            SessionTimeline.segments(for: entry.transcript, now: entry.session.endedAt == nil ? now : nil)
+                .filter { $0.phase != .idle }
+                .map { DateInterval(start: $0.start, end: $0.end) }
+        }
+        return ActivityHeatmap(intervals: intervals, range: range, calendar: calendar)
+    }
+
+    /// The files agents edited most across the listed sessions, optionally
+    /// for one project.
+    func fileHotspots(projectPath: String? = nil, limit: Int = 10, batch: TranscriptBatch? = nil) async -> [FileHotspot] {
+        let candidates = projectPath.map { path in sessions.filter { $0.projectPath == path } } ?? sessions
+        let entries = await (batch ?? makeTranscriptBatch()).transcripts(for: candidates)
+        return FileHotspot.rank(entries, projectPath: projectPath, limit: limit)
+    }
+
+    /// Efficiency per agent and the recurring failure modes, most widespread
+    /// first, from one read of the listed sessions.
+    func outcomeInsights(
+        clusterLimit: Int = 10,
+        batch: TranscriptBatch? = nil
+    ) async -> (efficiency: [AgentType: EfficiencyMetrics], failureClusters: [FailureCluster]) {
+        let entries = await (batch ?? makeTranscriptBatch()).transcripts(for: sessions)
+        let efficiency = Dictionary(grouping: entries, by: \.session.agentType).mapValues { group in
+            EfficiencyMetrics.combined(group.map { EfficiencyMetrics(session: $0.session, transcript: $0.transcript) })
+        }
+        // Only failed sessions and failed tool calls have anything to cluster.
+        let failing = entries.filter { entry in
+            entry.session.status == .failed || entry.transcript.toolCalls.contains { $0.status == .failed }
+        }
+        return (efficiency, FailureCluster.group(failing, limit: clusterLimit))
+    }
+
+    // MARK: - Usage Analytics
+
+    /// Counts a feature use in the local analytics store; a no-op unless the user opted in.
+    func recordFeatureUse(_ feature: UsageAnalytics.Feature) {
+        guard isAnalyticsEnabled else { return }
+        let analytics = analytics
+        Task { await analytics.record(feature) }
+    }
+
+    func usageInsights() async -> UsageAnalytics.Insights {
+        await analytics.insights()
+    }
+
+    func resetUsageInsights() async {
+        await analytics.reset()
+    }
+
+    @MainActor
+    private func recordSessionOutcomes() async {
+        guard isAnalyticsEnabled else { return }
+        await analytics.recordOutcomes(of: sessions, at: environment.now)
+    }
+
+    private var isAnalyticsEnabled: Bool {
+        FeatureFlags.current.isEnabled(.usageInsights) && AppSettings.load().analyticsEnabled
+    }
+
+    // MARK: - Settings
+
+    @MainActor
+    func applySettingsChange(from old: AppSettings, to new: AppSettings) {
+        if new.affectsSessionDiscovery(comparedTo: old) {
+            Task { await refresh() }
+        }
+        if new.refreshInBackground != old.refreshInBackground && !isPopoverVisible {
+            setPopoverVisible(false)
+        }
+        viewPresets = new.viewPresets
+    }
+
+    // MARK: - Labels
+
+    /// Sets or, with an empty label, clears the user's label on a session.
+    func setLabel(_ label: SessionLabel?, for sessionId: UUID, defaults: UserDefaults = .standard) {
+        let label = label?.isEmpty == false ? label : nil
+        sessionLabels[sessionId] = label
+        if let idx = sessions.firstIndex(where: { $0.id == sessionId }) {
+            sessions[idx].label = label
+        }
+        SessionLabel.saveAll(sessionLabels, to: defaults)
+    }
+
+    // MARK: - Issue Links
+
+    /// Links a session to an issue key or, with `nil`, goes back to the key
+    /// found in its branch or prompt.
+    func linkIssue(_ key: String?, to sessionId: UUID, defaults: UserDefaults = .standard) {
+        let key = key?.trimmingCharacters(in: .whitespaces).uppercased()
+        linkedIssueKeys[sessionId] = key.flatMap { IssueTracker.isValidKey($0) ? $0 : nil }
+        if let idx = sessions.firstIndex(where: { $0.id == sessionId }) {
+            sessions[idx].issueKey = linkedIssueKeys[sessionId]
+                ?? IssueTracker.detectKey(branch: sessions[idx].gitBranch, prompt: sessions[idx].firstPrompt)
+        }
+        IssueTracker.saveLinkedKeys(linkedIssueKeys, to: defaults)
+    }
+
+    // MARK: - View Presets
+
+    /// Adds `preset`, or replaces the saved one with the same name.
+    func saveViewPreset(_ preset: ViewPreset, defaults: UserDefaults = .standard) {
+        let updated = AppSettings.update(in: defaults) { settings in
+            if let index = settings.viewPresets.firstIndex(where: { $0.name == preset.name }) {
+                settings.viewPresets[index] = preset
+            } else {
+                settings.viewPresets.append(preset)
+            }
+        }
+        viewPresets = updated.viewPresets
+    }
+
+    func renameViewPreset(named name: String, to newName: String, defaults: UserDefaults = .standard) {
+        let trimmed = newName.trimmingCharacters(in: .whitespaces)
+        guard !trimmed.isEmpty, !viewPresets.contains(where: { $0.name == trimmed }) else { return }
+        let updated = AppSettings.update(in: defaults) { settings in
+            guard let index = settings.viewPresets.firstIndex(where: { $0.name == name }) else { return }
+            settings.viewPresets[index].name = trimmed
+        }
+        viewPresets = updated.viewPresets
+    }
+
+    func deleteViewPreset(named name: String, defaults: UserDefaults = .standard) {
+        let updated = AppSettings.update(in: defaults) { settings in
+            settings.viewPresets.removeAll { $0.name == name }
+        }
+        viewPresets = updated.viewPresets
+    }
+
     // MARK: - Error Handling
 
     func clearError() {
@@ -333,6 +1100,29 @@ final class SessionStore {
 
     // MARK: - Private Helpers
 
+    /// Fills in each session's configured MCP servers, rereading agent config
+    /// only when `~/.claude.json` or `~/.codex/config.toml` changed.
+    private func applyConfiguredMCPServers(to sessions: inout [Session]) {
+        let home = FileUtilities.realHomeDirectory()
+        let stamp = [".claude.json", ".codex/config.toml"].map {
+            FileUtilities.modificationTime(atPath: (home as NSString).appendingPathComponent($0))
+        }
+        if stamp != mcpConfigStamp {
+            mcpConfigStamp = stamp
+            mcpServerCache.removeAll()
+        }
+        for i in sessions.indices {
+            let key = "\(sessions[i].agentType.rawValue)|\(sessions[i].projectPath ?? "")"
+            if let cached = mcpServerCache[key] {
+                sessions[i].configuredMCPServers = cached
+            } else {
+                let servers = MCPServers.configured(agentType: sessions[i].agentType, projectPath: sessions[i].projectPath)
+                mcpServerCache[key] = servers
+                sessions[i].configuredMCPServers = servers
+            }
+        }
+    }
+
     private func applyTokenSummary(_ summary: SessionTokenSummary, to session: inout Session) {
         session.metrics.inputTokens = summary.inputTokens
         session.metrics.outputTokens = summary.outputTokens
@@ -342,6 +1132,20 @@ final class SessionStore {
         session.metrics.cost = summary.cost
         session.metrics.modelName = summary.modelName
         session.metrics.apiCalls = summary.apiCalls
+        session.metrics.isEstimated = summary.isEstimated ?? false
+        session.metrics.modelIds = summary.modelIds ?? []
+        session.metrics.contextTokens = summary.contextTokens ?? 0
+        session.mcpToolsUsed = summary.mcpTools ?? []
+        if let window = summary.contextWindow {
+            session.metrics.contextWindowMax = window
+        }
+        if session.endedAt == nil, session.metrics.isNearContextLimit,
+           contextWarnedSessionIds.insert(session.id).inserted {
+            AppLogger.logWarning(
+                "\(session.name) is at \(Int(session.metrics.contextWindowUsage * 100))% of its context window",
+                context: "SessionStore"
+            )
+        }
     }
 
     private func loadCostCache() {
@@ -1,6 +1,8 @@
 import SwiftUI
 
 struct MenuBarView: View {
+    @Environment(SessionStore.self) private var sessionStore
+    @Environment(AppLock.self) private var appLock
     @State private var currentPage: MenuBarPage = .main
 
     enum MenuBarPage {
@@ -9,17 +11,101 @@ struct MenuBarView: View {
     }
 
     var body: some View {
-        switch currentPage {
-        case .main:
-            MenuBarMainView(navigateToSettings: { currentPage = .settings })
-        case .settings:
-            MenuBarSettingsView(navigateBack: { currentPage = .main })
+        Group {
+            if appLock.isLocked {
+                MenuBarLockedView()
+            } else {
+                switch currentPage {
+                case .main:
+                    MenuBarMainView(navigateToSettings: { currentPage = .settings })
+                case .settings:
+                    MenuBarSettingsView(navigateBack: { currentPage = .main })
+                }
+            }
+        }
+        .onAppear {
+            appLock.lockIfIdle()
+            sessionStore.setPopoverVisible(true)
+        }
+        .onDisappear {
+            appLock.noteActivity()
+            sessionStore.setPopoverVisible(false)
+        }
+    }
+}
+
+// MARK: - Locked
+
+struct MenuBarLockedView: View {
+    @Environment(AppLock.self) private var appLock
+
+    var body: some View {
+        VStack(spacing: 12) {
+            Image(systemName: "lock.fill")
+                .font(.title)
+                .foregroundStyle(.secondary)
+            Text("Agents Monitor is locked")
+                .font(.headline)
+            if let error = appLock.lastError {
+                Text(error)
+                    .font(.caption)
+                    .foregroundStyle(AppTheme.statusColor(for: .failed))
+                    .multilineTextAlignment(.center)
+            }
+            HStack {
+                Button("Unlock") {
+                    Task { await appLock.unlock() }
+                }
+                .keyboardShortcut(.defaultAction)
+                .accessibilityIdentifier("menuBar.lock.unlock")
+
+                Button("Quit") {
+                    NSApplication.shared.terminate(nil)
+                }
+                .accessibilityIdentifier("menuBar.lock.quit")
+            }
+        }
+        .padding(24)
+        .frame(width: 320)
+        .task {
+            await appLock.unlock()
         }
+        .accessibilityIdentifier("menuBar.locked")
     }
 }
 
 // MARK: - Shared Components
 
+/// Menu bar icon with a badge counting sessions that are waiting on the user or failed.
+struct MenuBarStatusLabel: View {
+    let stats: QuickStats
+
+    var body: some View {
+        let count = stats.needsAttentionCount
+        Group {
+            if count > 0 {
+                Label("\(count)", systemImage: "cpu")
+                    .labelStyle(.titleAndIcon)
+            } else {
+                Image(systemName: "cpu")
+            }
+        }
+        .accessibilityLabel("Agents Monitor")
+        .accessibilityValue(accessibilitySummary)
+    }
+
+    private var accessibilitySummary: String {
+        var parts = ["\(stats.runningCount) running"]
+        if stats.waitingCount > 0 {
+            parts.append("\(stats.waitingCount) waiting")
+        }
+        if stats.failedCount > 0 {
+            parts.append("\(stats.failedCount) failed")
+        }
+        return parts.joined(separator: ", ")
+    }
+}
+
 struct MenuBarSessionRow: View {
     let session: Session
     @Environment(\.appEnvironment) private var appEnvironment
@@ -108,5 +194,6 @@ struct MenuBarButton: View {
 #Preview {
     MenuBarView()
         .environment(SessionStore(environment: .current))
+        .environment(AppLock(isEnabled: { false }))
         .environment(\.appEnvironment, .current)
 }
@@ -1,5 +1,6 @@
 import Foundation
 import SwiftUI
+import UniformTypeIdentifiers
 
 struct MenuBarMainView: View {
     @Environment(SessionStore.self) private var sessionStore
@@ -12,6 +13,9 @@ struct MenuBarMainView: View {
 
     @State private var expandedSessionId: UUID?
     @State private var selectedSourceTab: SessionSourceTab = .all
+    @State private var activePresetName: String?
+    @State private var queryText = ""
+    @State private var environmentProbe: EnvironmentProbe?
     private let usageRefreshInterval: Double = 60.0
 
     private var availableSourceTabs: [SessionSourceTab] {
@@ -25,12 +29,26 @@ struct MenuBarMainView: View {
         return tabs
     }
 
+    private var activePreset: ViewPreset? {
+        sessionStore.viewPresets.first { $0.name == activePresetName }
+    }
+
     private var filteredSessions: [Session] {
-        sessionStore.visibleSessions(
-            for: selectedSourceTab,
+        let sessions = sessionStore.visibleSessions(
+            for: activePreset?.source ?? selectedSourceTab,
             codexEnabled: codexEnabled,
             claudeCodeEnabled: claudeCodeEnabled
         )
+        let presetSessions = activePreset?.apply(to: sessions, now: appEnvironment.now) ?? sessions
+        guard case .success(let query?) = parsedQuery else { return presetSessions }
+        return query.filter(presetSessions, now: appEnvironment.now)
+    }
+
+    /// `nil` while the filter field is empty.
+    private var parsedQuery: Result<SessionQuery?, Error> {
+        let text = queryText.trimmingCharacters(in: .whitespaces)
+        guard !text.isEmpty else { return .success(nil) }
+        return Result { try SessionQuery(text) }
     }
 
     private var filteredRunningCount: Int {
@@ -51,6 +69,11 @@ struct MenuBarMainView: View {
         sevenDaySessions.reduce(0) { $0 + $1.metrics.cost }
     }
 
+    private var pendingApprovals: [Session] {
+        let visibleIds = Set(filteredSessions.map(\.id))
+        return sessionStore.pendingPermissionSessions.filter { visibleIds.contains($0.id) }
+    }
+
     private var areAllSourcesDisabled: Bool {
         !codexEnabled && !claudeCodeEnabled
     }
@@ -79,11 +102,27 @@ struct MenuBarMainView: View {
                         .foregroundStyle(.secondary)
                         .accessibilityIdentifier("menuBar.header.activeCount")
                 }
+                viewPresetMenu
             }
             .padding()
 
             Divider()
 
+            if let alert = sessionStore.commandAlerts.last {
+                commandAlertBanner(alert, count: sessionStore.commandAlerts.count)
+                Divider()
+            }
+
+            if let anomaly = sessionStore.durationAlerts.last {
+                durationAlertBanner(anomaly, count: sessionStore.durationAlerts.count)
+                Divider()
+            }
+
+            if !sessionStore.offlineSources.isEmpty || sessionStore.pendingNoteCount > 0 {
+                storageOfflineBanner
+                Divider()
+            }
+
             if availableSourceTabs.count > 1 {
                 HStack(spacing: 6) {
                     ForEach(availableSourceTabs) { tab in
@@ -95,8 +134,17 @@ struct MenuBarMainView: View {
                 .padding(.bottom, 2)
             }
 
+            queryField
+
             ScrollView {
                 VStack(alignment: .leading, spacing: 0) {
+                    if !pendingApprovals.isEmpty {
+                        approvalsSection
+
+                        Divider()
+                            .padding(.vertical, 4)
+                    }
+
                     if showUsageSection {
                         // Usage Limits
                         usageLimitsSection
@@ -115,6 +163,7 @@ struct MenuBarMainView: View {
             // Actions
             VStack(spacing: 0) {
                 MenuBarButton(title: "Refresh", icon: "arrow.clockwise", identifier: "menuBar.action.refresh") {
+                    sessionStore.recordFeatureUse(.manualRefresh)
                     Task {
                         await sessionStore.refreshAll()
                     }
@@ -123,6 +172,7 @@ struct MenuBarMainView: View {
                 Divider()
 
                 MenuBarButton(title: "Settings...", icon: "gearshape", identifier: "menuBar.action.settings") {
+                    sessionStore.recordFeatureUse(.settingsOpened)
                     navigateToSettings()
                 }
 
@@ -146,6 +196,14 @@ struct MenuBarMainView: View {
                 await sessionStore.fetchUsageData()
             }
         }
+        .onChange(of: sessionStore.requestedSessionId, initial: true) { _, id in
+            guard let id else { return }
+            selectedSourceTab = .all
+            activePresetName = nil
+            queryText = ""
+            expandedSessionId = id
+            sessionStore.requestedSessionId = nil
+        }
         .onChange(of: codexEnabled) { _, _ in
             syncSelectedTabWithAvailability()
         }
@@ -154,10 +212,47 @@ struct MenuBarMainView: View {
         }
         .onChange(of: selectedSourceTab) { _, _ in
             expandedSessionId = nil
+            sessionStore.recordFeatureUse(.sourceTabChanged)
         }
         .accessibilityIdentifier("menuBar.view")
     }
 
+    // MARK: - Approvals Section
+
+    private var approvalsSection: some View {
+        VStack(alignment: .leading, spacing: 6) {
+            Text("AWAITING APPROVAL")
+                .font(.caption2)
+                .foregroundStyle(.secondary)
+                .padding(.horizontal)
+                .padding(.top, 8)
+
+            ForEach(pendingApprovals) { session in
+                if let request = session.pendingPermission {
+                    HStack(alignment: .top, spacing: 8) {
+                        Image(systemName: "hand.raised.fill")
+                            .font(.caption)
+                            .foregroundStyle(AppTheme.statusColor(for: .waiting))
+                        VStack(alignment: .leading, spacing: 2) {
+                            Text(session.name)
+                                .font(.caption)
+                                .lineLimit(1)
+                            Text("\(request.toolName): \(request.input)")
+                                .font(.system(.caption2, design: .monospaced))
+                                .foregroundStyle(.secondary)
+                                .lineLimit(2)
+                        }
+                    }
+                    .padding(.horizontal)
+                    .accessibilityElement(children: .combine)
+                    .accessibilityLabel("\(session.name) is waiting to run \(request.toolName)")
+                    .accessibilityIdentifier("menuBar.approval")
+                }
+            }
+        }
+        .padding(.bottom, 4)
+    }
+
     // MARK: - Usage Limits Section
 
     @ViewBuilder
@@ -173,8 +268,10 @@ struct MenuBarMainView: View {
                 // Claude Code usage
                 if selectedSourceTab == .all || selectedSourceTab == .claudeCode {
                     if let usage = sessionStore.usageData {
-                        usageBar(label: "5-hour", utilization: usage.fiveHour.utilization, resetsAt: usage.fiveHour.resetsAt)
-                        usageBar(label: "7-day", utilization: usage.sevenDay.utilization, resetsAt: usage.sevenDay.resetsAt)
+                        usageBar(label: "5-hour", utilization: usage.fiveHour.utilization, resetsAt: usage.fiveHour.resetsAt,
+                                 quota: sessionStore.quotaStatus(for: .claudeCode, window: "5-hour"))
+                        usageBar(label: "7-day", utilization: usage.sevenDay.utilization, resetsAt: usage.sevenDay.resetsAt,
+                                 quota: sessionStore.quotaStatus(for: .claudeCode, window: "7-day"))
                         if let sonnet = usage.sevenDaySonnet {
                             usageBar(label: "Sonnet 7d", utilization: sonnet.utilization, resetsAt: sonnet.resetsAt)
                         }
@@ -202,8 +299,10 @@ struct MenuBarMainView: View {
                 // Codex usage
                 if selectedSourceTab == .all || selectedSourceTab == .codex {
                     if let codex = sessionStore.codexUsage {
-                        usageBar(label: "Codex 5hr", utilization: codex.primary.utilization, resetsAt: codex.primary.resetsAt, tint: AppTheme.agentTypeColor(for: .codex))
-                        usageBar(label: "Codex 7d", utilization: codex.secondary.utilization, resetsAt: codex.secondary.resetsAt, tint: AppTheme.agentTypeColor(for: .codex))
+                        usageBar(label: "Codex 5hr", utilization: codex.primary.utilization, resetsAt: codex.primary.resetsAt, tint: AppTheme.agentTypeColor(for: .codex),
+                                 quota: sessionStore.quotaStatus(for: .codex, window: "5-hour"))
+                        usageBar(label: "Codex 7d", utilization: codex.secondary.utilization, resetsAt: codex.secondary.resetsAt, tint: AppTheme.agentTypeColor(for: .codex),
+                                 quota: sessionStore.quotaStatus(for: .codex, window: "7-day"))
                     }
                 }
             }
@@ -227,7 +326,7 @@ struct MenuBarMainView: View {
         }
     }
 
-    private func usageBar(label: String, utilization: Double, resetsAt: String?, tint: Color? = nil) -> some View {
+    private func usageBar(label: String, utilization: Double, resetsAt: String?, tint: Color? = nil, quota: QuotaStatus? = nil) -> some View {
         let clampedUtilization = min(max(utilization, 0), 1)
         let barColor = tint ?? utilizationColor(utilization)
         return VStack(alignment: .leading, spacing: 2) {
@@ -255,6 +354,12 @@ struct MenuBarMainView: View {
                 }
             }
             .frame(height: 4)
+            if let quota, quota.willHitLimitBeforeReset, let limitAt = quota.projectedLimitAt {
+                Text("Limit \(formatResetTime(limitAt)) at this rate")
+                    .font(.caption2)
+                    .foregroundStyle(.orange)
+                    .accessibilityIdentifier("menuBar.usage.projection")
+            }
         }
     }
 
@@ -273,7 +378,10 @@ struct MenuBarMainView: View {
         guard let date = fractional.date(from: iso) ?? plain.date(from: iso) else {
             return iso
         }
+        return formatResetTime(date)
+    }
 
+    private func formatResetTime(_ date: Date) -> String {
         let formatter = RelativeDateTimeFormatter()
         formatter.unitsStyle = .abbreviated
         return formatter.localizedString(for: date, relativeTo: Date())
@@ -310,9 +418,24 @@ struct MenuBarMainView: View {
                 Text(emptyStateSubtitle)
                     .font(.caption2)
                     .foregroundStyle(.tertiary)
+                if let steps = environmentProbe?.nextSteps, !steps.isEmpty {
+                    VStack(alignment: .leading, spacing: 2) {
+                        ForEach(steps, id: \.self) { step in
+                            Label(step, systemImage: "arrow.right.circle")
+                                .font(.caption2)
+                                .foregroundStyle(.secondary)
+                                .textSelection(.enabled)
+                        }
+                    }
+                    .padding(.horizontal)
+                    .accessibilityIdentifier("menuBar.emptyState.nextSteps")
+                }
             }
             .frame(maxWidth: .infinity)
             .padding(.vertical, 16)
+            .task {
+                environmentProbe = await sessionStore.probeEnvironment()
+            }
         } else {
             Text("SESSIONS")
                 .font(.caption2)
@@ -343,10 +466,244 @@ struct MenuBarMainView: View {
         }
     }
 
+    private func commandAlertBanner(_ alert: CommandAlert, count: Int) -> some View {
+        HStack(alignment: .top, spacing: 8) {
+            Image(systemName: "exclamationmark.triangle.fill")
+                .foregroundStyle(AppTheme.statusColor(for: .failed))
+            VStack(alignment: .leading, spacing: 2) {
+                Text(count > 1 ? "\(alert.command.rule) (+\(count - 1) more)" : alert.command.rule)
+                    .font(.caption.weight(.semibold))
+                Text(alert.command.command)
+                    .font(.system(.caption2, design: .monospaced))
+                    .lineLimit(2)
+                Text(alert.sessionName)
+                    .font(.caption2)
+                    .foregroundStyle(.secondary)
+                    .lineLimit(1)
+            }
+            Spacer()
+            Button("Dismiss") {
+                sessionStore.dismissCommandAlerts()
+            }
+            .buttonStyle(.borderless)
+            .font(.caption)
+            .accessibilityIdentifier("menuBar.commandAlert.dismiss")
+        }
+        .padding(.horizontal)
+        .padding(.vertical, 8)
+        .background(AppTheme.statusColor(for: .failed).opacity(0.1))
+        .accessibilityIdentifier("menuBar.commandAlert")
+    }
+
+    private func durationAlertBanner(_ anomaly: DurationAnomaly, count: Int) -> some View {
+        HStack(alignment: .top, spacing: 8) {
+            Image(systemName: "clock.badge.exclamationmark")
+                .foregroundStyle(.orange)
+            VStack(alignment: .leading, spacing: 2) {
+                Text(count > 1 ? "\(anomaly.sessionName) (+\(count - 1) more)" : anomaly.sessionName)
+                    .font(.caption.weight(.semibold))
+                    .lineLimit(1)
+                Text(anomaly.message(asOf: appEnvironment.now))
+                    .font(.caption2)
+                    .foregroundStyle(.secondary)
+            }
+            Spacer()
+            Button("Dismiss") {
+                sessionStore.dismissDurationAlerts()
+            }
+            .buttonStyle(.borderless)
+            .font(.caption)
+            .accessibilityIdentifier("menuBar.durationAlert.dismiss")
+        }
+        .padding(.horizontal)
+        .padding(.vertical, 8)
+        .background(Color.orange.opacity(0.1))
+        .accessibilityIdentifier("menuBar.durationAlert")
+    }
+
+    private var storageOfflineBanner: some View {
+        HStack(alignment: .top, spacing: 8) {
+            Image(systemName: "externaldrive.badge.exclamationmark")
+                .foregroundStyle(.orange)
+            VStack(alignment: .leading, spacing: 2) {
+                Text("Storage offline")
+                    .font(.caption.weight(.semibold))
+                ForEach(AgentType.allCases.filter(sessionStore.offlineSources.contains), id: \.self) { agent in
+                    Text("\(agent.rawValue) data can't be reached; showing the last sessions read")
+                        .font(.caption2)
+                        .foregroundStyle(.secondary)
+                }
+                if sessionStore.pendingNoteCount > 0 {
+                    Text(sessionStore.pendingNoteCount == 1 ? "1 note will be written when the notes folder is back" : "\(sessionStore.pendingNoteCount) notes will be written when the notes folder is back")
+                        .font(.caption2)
+                        .foregroundStyle(.secondary)
+                }
+            }
+            Spacer()
+        }
+        .padding(.horizontal)
+        .padding(.vertical, 8)
+        .background(Color.orange.opacity(0.1))
+        .accessibilityElement(children: .combine)
+        .accessibilityIdentifier("menuBar.storageOffline")
+    }
+
+    private var viewPresetMenu: some View {
+        Menu {
+            ForEach(sessionStore.viewPresets) { preset in
+                Button {
+                    activePresetName = preset.name
+                } label: {
+                    if preset.name == activePresetName {
+                        Label(preset.name, systemImage: "checkmark")
+                    } else {
+                        Text(preset.name)
+                    }
+                }
+            }
+            if !sessionStore.viewPresets.isEmpty {
+                Divider()
+            }
+            Button("Save Current View...", action: saveCurrentView)
+            if let activePresetName {
+                Button("Edit \u{201C}\(activePresetName)\u{201D}...") { editActivePreset() }
+                Button("Delete \u{201C}\(activePresetName)\u{201D}") {
+                    sessionStore.deleteViewPreset(named: activePresetName)
+                    self.activePresetName = nil
+                }
+                Button("Show All Sessions") { self.activePresetName = nil }
+            }
+        } label: {
+            Image(systemName: activePresetName == nil
+                  ? "line.3.horizontal.decrease.circle"
+                  : "line.3.horizontal.decrease.circle.fill")
+        }
+        .menuStyle(.borderlessButton)
+        .menuIndicator(.hidden)
+        .fixedSize()
+        .help(activePresetName.map { "View: \($0)" } ?? "Saved views")
+        .accessibilityLabel("Saved views")
+        .accessibilityIdentifier("menuBar.header.viewPresets")
+    }
+
+    private func saveCurrentView() {
+        var current = activePreset ?? ViewPreset(name: "", source: selectedSourceTab)
+        let typed = queryText.trimmingCharacters(in: .whitespaces)
+        if !typed.isEmpty, case .success = parsedQuery {
+            current.query = current.query.map { "(\($0)) AND (\(typed))" } ?? typed
+        }
+        guard let preset = promptForPreset(
+            title: "Save Current View",
+            message: "Saving over an existing name replaces that view.",
+            initial: current
+        ) else { return }
+        if preset.query != activePreset?.query { queryText = "" }
+        sessionStore.saveViewPreset(preset)
+        activePresetName = preset.name
+    }
+
+    private func editActivePreset() {
+        guard let current = activePreset,
+              let preset = promptForPreset(title: "Edit View", message: "Names must be unique.", initial: current) else { return }
+        if preset.name != current.name {
+            sessionStore.renameViewPreset(named: current.name, to: preset.name)
+            guard sessionStore.viewPresets.contains(where: { $0.name == preset.name }) else { return }
+        }
+        sessionStore.saveViewPreset(preset)
+        activePresetName = preset.name
+    }
+
+    /// The name, filters and order of a saved view, edited in one alert.
+    /// The source and query carry over from `initial` unchanged.
+    private func promptForPreset(title: String, message: String, initial: ViewPreset) -> ViewPreset? {
+        let alert = NSAlert()
+        alert.messageText = title
+        alert.informativeText = message
+
+        let nameField = NSTextField(frame: NSRect(x: 0, y: 0, width: 240, height: 24))
+        nameField.stringValue = initial.name
+        nameField.placeholderString = "Failed this week"
+
+        let statusBoxes = SessionStatus.allCases.map { status in
+            let box = NSButton(checkboxWithTitle: status.rawValue, target: nil, action: nil)
+            box.state = initial.statuses.contains(status) ? .on : .off
+            return box
+        }
+        let statusRows = stride(from: 0, to: statusBoxes.count, by: 3).map { start in
+            NSStackView(views: Array(statusBoxes[start..<min(start + 3, statusBoxes.count)]))
+        }
+
+        let projects = Array(Set(sessionStore.sessions.compactMap(\.projectPath) + [initial.projectPath].compactMap { $0 })).sorted()
+        let projectPopup = NSPopUpButton()
+        projectPopup.addItem(withTitle: "Any project")
+        for path in projects {
+            projectPopup.addItem(withTitle: URL(fileURLWithPath: path).lastPathComponent)
+            projectPopup.lastItem?.toolTip = path
+        }
+        projectPopup.selectItem(at: initial.projectPath.flatMap { projects.firstIndex(of: $0) }.map { $0 + 1 } ?? 0)
+
+        let dayOptions: [(title: String, days: Int?)] = [("Any time", nil), ("Last day", 1), ("Last 7 days", 7), ("Last 30 days", 30), ("Last 90 days", 90)]
+        let daysPopup = NSPopUpButton()
+        daysPopup.addItems(withTitles: dayOptions.map(\.title))
+        if let days = initial.withinDays, !dayOptions.contains(where: { $0.days == days }) {
+            daysPopup.addItem(withTitle: "Last \(days) days")
+        }
+        daysPopup.selectItem(at: dayOptions.firstIndex { $0.days == initial.withinDays } ?? (initial.withinDays == nil ? 0 : dayOptions.count))
+
+        let sorts = ViewPreset.Sort.allCases
+        let sortPopup = NSPopUpButton()
+        sortPopup.addItems(withTitles: sorts.map { "Sort by \($0.rawValue)" })
+        sortPopup.selectItem(at: sorts.firstIndex(of: initial.sort) ?? 0)
+
+        let groupBox = NSButton(checkboxWithTitle: "Group by project", target: nil, action: nil)
+        groupBox.state = initial.groupByProject ? .on : .off
+
+        let stack = NSStackView(views: [nameField] + statusRows + [projectPopup, daysPopup, sortPopup, groupBox])
+        stack.orientation = .vertical
+        stack.alignment = .leading
+        stack.spacing = 6
+        stack.frame = NSRect(origin: .zero, size: NSSize(width: 240, height: stack.fittingSize.height))
+        alert.accessoryView = stack
+        alert.window.initialFirstResponder = nameField
+        alert.addButton(withTitle: "Save")
+        alert.addButton(withTitle: "Cancel")
+        guard alert.runModal() == .alertFirstButtonReturn else { return nil }
+
+        let name = nameField.stringValue.trimmingCharacters(in: .whitespaces)
+        guard !name.isEmpty else { return nil }
+        var preset = initial
+        preset.name = name
+        preset.statuses = Set(zip(SessionStatus.allCases, statusBoxes).filter { $0.1.state == .on }.map(\.0))
+        preset.projectPath = projectPopup.indexOfSelectedItem > 0 ? projects[projectPopup.indexOfSelectedItem - 1] : nil
+        let dayIndex = daysPopup.indexOfSelectedItem
+        preset.withinDays = dayIndex < dayOptions.count ? dayOptions[max(dayIndex, 0)].days : initial.withinDays
+        preset.sort = sorts[max(sortPopup.indexOfSelectedItem, 0)]
+        preset.groupByProject = groupBox.state == .on
+        return preset
+    }
+
+    private var queryField: some View {
+        VStack(alignment: .leading, spacing: 2) {
+            TextField("status=failed AND tokens>100k", text: $queryText)
+                .textFieldStyle(.roundedBorder)
+                .font(.caption)
+                .accessibilityIdentifier("menuBar.query")
+            if case .failure(let error) = parsedQuery {
+                Text(error.localizedDescription)
+                    .font(.caption2)
+                    .foregroundStyle(.red)
+                    .accessibilityIdentifier("menuBar.query.error")
+            }
+        }
+        .padding(.horizontal)
+        .padding(.vertical, 4)
+    }
+
     private func sourceTabButton(for tab: SessionSourceTab) -> some View {
-        let isSelected = selectedSourceTab == tab
+        let isSelected = activePresetName == nil && selectedSourceTab == tab
         return Button {
             selectedSourceTab = tab
+            activePresetName = nil
         } label: {
             Text(tab.title)
                 .font(.caption)
@@ -406,7 +763,20 @@ struct MenuBarExpandableSessionRow: View {
     let session: Session
     let isExpanded: Bool
     let onToggle: () -> Void
+    @Environment(SessionStore.self) private var sessionStore
     @Environment(\.appEnvironment) private var appEnvironment
+    @State private var searchQuery = ""
+    @State private var searchResults: [TranscriptMatch] = []
+    @State private var attachments: [MessageAttachment] = []
+    @State private var compactions: [Compaction] = []
+    @State private var subagents: [SubagentRun] = []
+    @State private var links: [MentionedLink] = []
+    @State private var pullRequest: PullRequestLink?
+    @State private var transcriptIssues: [TranscriptIssue] = []
+    @State private var timeline: [TimelineSegment] = []
+    @AppStorage("notesFolder") private var notesFolder = ""
+    @AppStorage("issueTracker") private var issueTracker = ""
+    @State private var commentStatus: String?
 
     var body: some View {
         VStack(alignment: .leading, spacing: 0) {
@@ -422,9 +792,45 @@ struct MenuBarExpandableSessionRow: View {
 
                     VStack(alignment: .leading, spacing: 2) {
                         HStack(spacing: 4) {
+                            if let color = session.label?.color {
+                                Circle()
+                                    .fill(AppTheme.labelColor(for: color))
+                                    .frame(width: 6, height: 6)
+                                    .accessibilityLabel("\(color.rawValue.capitalized) label")
+                                    .accessibilityIdentifier("menuBar.session.labelColor")
+                            }
+                            if let emoji = session.label?.emoji, !emoji.isEmpty {
+                                Text(emoji)
+                                    .font(.caption2)
+                                    .accessibilityIdentifier("menuBar.session.labelEmoji")
+                            }
                             Text(session.name)
                                 .lineLimit(1)
                                 .accessibilityIdentifier("menuBar.session.name")
+                            if !session.flaggedCommands.isEmpty {
+                                Image(systemName: "exclamationmark.triangle.fill")
+                                    .font(.caption2)
+                                    .foregroundStyle(AppTheme.statusColor(for: .failed))
+                                    .help(session.flaggedCommands.map(\.rule).joined(separator: ", "))
+                                    .accessibilityLabel("Ran \(session.flaggedCommands.count) dangerous commands")
+                                    .accessibilityIdentifier("menuBar.session.flaggedCommands")
+                            }
+                            if session.endedAt == nil && session.metrics.isNearContextLimit {
+                                Image(systemName: "gauge.with.dots.needle.67percent")
+                                    .font(.caption2)
+                                    .foregroundStyle(AppTheme.statusColor(for: .waiting))
+                                    .help("Context \(Int(session.metrics.contextWindowUsage * 100))% full")
+                                    .accessibilityLabel("Context window nearly full")
+                                    .accessibilityIdentifier("menuBar.session.contextWarning")
+                            }
+                            if session.endedAt == nil, let mode = session.operatingMode, mode != .ask {
+                                Image(systemName: mode.icon)
+                                    .font(.caption2)
+                                    .foregroundStyle(mode == .fullAuto ? AppTheme.statusColor(for: .failed) : Color.secondary)
+                                    .help(mode.label)
+                                    .accessibilityLabel(mode.label)
+                                    .accessibilityIdentifier("menuBar.session.operatingMode")
+                            }
                             Text(session.agentType == .codex ? "CX" : "CC")
                                 .font(.system(size: 8, weight: .semibold, design: .monospaced))
                                 .foregroundStyle(AppTheme.agentTypeColor(for: session.agentType))
@@ -441,6 +847,15 @@ struct MenuBarExpandableSessionRow: View {
                                     .foregroundStyle(.tertiary)
                                     .lineLimit(1)
                             }
+                            if session.endedAt == nil, let subdirectory = session.currentSubdirectory {
+                                Label(subdirectory, systemImage: "folder")
+                                    .font(.caption2)
+                                    .foregroundStyle(.tertiary)
+                                    .lineLimit(1)
+                                    .truncationMode(.head)
+                                    .help("Working in \(session.workingDirectory?.path ?? subdirectory)")
+                                    .accessibilityIdentifier("menuBar.session.workingDirectory")
+                            }
                             if let branch = session.gitBranch {
                                 Text(branch)
                                     .font(.caption2)
@@ -450,6 +865,21 @@ struct MenuBarExpandableSessionRow: View {
                                     .cornerRadius(3)
                                     .lineLimit(1)
                             }
+                            if let issueKey = session.issueKey {
+                                Label(issueKey, systemImage: "ticket")
+                                    .font(.caption2)
+                                    .foregroundStyle(.secondary)
+                                    .lineLimit(1)
+                                    .accessibilityIdentifier("menuBar.session.issueKey")
+                            }
+                            if let parent = sessionStore.parent(of: session) {
+                                Label(parent.name, systemImage: "arrow.turn.left.up")
+                                    .font(.caption2)
+                                    .foregroundStyle(.secondary)
+                                    .lineLimit(1)
+                                    .help("Sub-agent of \(parent.name)")
+                                    .accessibilityIdentifier("menuBar.session.parent")
+                            }
                         }
                     }
 
@@ -464,15 +894,94 @@ struct MenuBarExpandableSessionRow: View {
                 .contentShape(Rectangle())
             }
             .buttonStyle(.plain)
+            .contextMenu {
+                labelMenu
+                Divider()
+                issueMenu
+            }
             .accessibilityIdentifier("menuBar.sessionRow")
 
             if isExpanded {
-                expandedMetrics
-                    .padding(.leading, 32)
-                    .padding(.trailing)
-                    .padding(.bottom, 8)
-                    .transition(.opacity.combined(with: .move(edge: .top)))
-                    .accessibilityIdentifier("menuBar.session.expandedMetrics")
+                VStack(alignment: .leading, spacing: 6) {
+                    expandedMetrics
+                        .accessibilityIdentifier("menuBar.session.expandedMetrics")
+                    if session.jsonlPath != nil {
+                        if !transcriptIssues.isEmpty {
+                            metricItem(
+                                icon: "exclamationmark.triangle",
+                                text: transcriptIssues.count == 1 ? "Partially loaded: 1 line skipped" : "Partially loaded: \(transcriptIssues.count) lines skipped"
+                            )
+                            .font(.caption)
+                            .foregroundStyle(.orange)
+                            .help(transcriptIssues.prefix(5).map(\.description).joined(separator: "\n"))
+                            .accessibilityIdentifier("menuBar.session.transcriptIssues")
+                        }
+                        if let last = compactions.last {
+                            metricItem(icon: "arrow.down.right.and.arrow.up.left", text: compactionSummary(last))
+                                .font(.caption)
+                                .foregroundStyle(.secondary)
+                                .help("Behavior can change after a compaction: earlier instructions now live only in the summary")
+                                .accessibilityIdentifier("menuBar.session.compactions")
+                        }
+                        if timeline.count > 1 {
+                            timelineBar
+                        }
+                        if !subagents.isEmpty {
+                            subagentList
+                        }
+                        if !attachments.isEmpty {
+                            attachmentList
+                        }
+                        if !links.isEmpty {
+                            linkList
+                        }
+                        transcriptSearch
+                        HStack(spacing: 12) {
+                            Button("Export as Claude JSONL...") { exportTranscript(sanitized: false) }
+                                .accessibilityIdentifier("menuBar.session.exportTranscript")
+                            Button("Share Copy...") { exportTranscript(sanitized: true) }
+                                .help("Secrets masked, paths anonymized, long tool output trimmed, images left out")
+                                .accessibilityIdentifier("menuBar.session.shareCopy")
+                            if !notesFolder.isEmpty {
+                                Button("Save Note") { saveNote() }
+                                    .help("Writes this session as a Markdown note into \(notesFolder)")
+                                    .accessibilityIdentifier("menuBar.session.saveNote")
+                            }
+                            if let tracker = IssueTracker(rawValue: issueTracker), let issueKey = session.issueKey {
+                                Button("Comment on \(issueKey)") { postIssueComment() }
+                                    .help("Posts status, cost and files changed to \(issueKey) in \(tracker.displayName)")
+                                    .accessibilityIdentifier("menuBar.session.postIssueComment")
+                            }
+                            if let pullRequest {
+                                Button("Comment on PR #\(pullRequest.number)") { postPullRequestComment() }
+                                    .help("Posts what changed, the tests run and the cost to \(pullRequest.owner)/\(pullRequest.repo)#\(pullRequest.number)")
+                                    .accessibilityIdentifier("menuBar.session.postPullRequestComment")
+                            }
+                        }
+                        .buttonStyle(.link)
+                        .font(.caption2)
+                        if let commentStatus {
+                            Text(commentStatus)
+                                .font(.caption2)
+                                .foregroundStyle(.secondary)
+                                .lineLimit(2)
+                        }
+                    }
+                }
+                .padding(.leading, 32)
+                .padding(.trailing)
+                .padding(.bottom, 8)
+                .transition(.opacity.combined(with: .move(edge: .top)))
+                .task(id: session.fileMtime) {
+                    guard let transcript = await sessionStore.transcript(for: session) else { return }
+                    attachments = SessionStore.attachments(in: transcript)
+                    compactions = transcript.compactions
+                    subagents = transcript.subagents
+                    links = TranscriptSearch.links(in: transcript)
+                    pullRequest = GitHubPullRequests.linkedPullRequest(in: transcript, projectPath: session.projectPath)
+                    transcriptIssues = transcript.issues
+                    timeline = await sessionStore.timeline(for: session)
+                }
             }
         }
     }
@@ -489,7 +998,7 @@ struct MenuBarExpandableSessionRow: View {
 
             VStack(alignment: .leading, spacing: 4) {
                 HStack {
-                    metricItem(icon: "number", text: m.formattedTokens)
+                    metricItem(icon: "number", text: m.isEstimated ? "~\(m.formattedTokens)" : m.formattedTokens)
                     Spacer()
                     metricItem(icon: "arrow.up.arrow.down", text: "\(m.apiCalls) calls")
                 }
@@ -497,9 +1006,24 @@ struct MenuBarExpandableSessionRow: View {
                     metricItem(icon: "dollarsign.circle", text: m.formattedCost)
                     Spacer()
                     if !m.modelName.isEmpty {
-                        metricItem(icon: "cpu", text: m.modelName)
+                        metricItem(icon: "cpu", text: m.modelIds.count > 1 ? "\(m.modelName) +\(m.modelIds.count - 1)" : m.modelName)
+                            .help(m.modelIds.joined(separator: ", "))
+                            .accessibilityIdentifier("menuBar.session.model")
                     }
                 }
+                if m.contextTokens > 0 {
+                    metricItem(icon: "gauge.with.dots.needle.50percent", text: "Context \(m.formattedContextWindow)")
+                        .foregroundStyle(m.isNearContextLimit ? AppTheme.statusColor(for: .waiting) : Color.secondary)
+                }
+                if !session.configuredMCPServers.isEmpty || !session.mcpToolsUsed.isEmpty {
+                    metricItem(icon: "puzzlepiece.extension", text: mcpSummary)
+                        .help(session.mcpToolsUsed.isEmpty ? "No MCP tools called" : session.mcpToolsUsed.joined(separator: "\n"))
+                        .accessibilityIdentifier("menuBar.session.mcpServers")
+                }
+                if let request = session.pendingPermission {
+                    metricItem(icon: "hand.raised", text: "Waiting on \(request.toolName): \(request.input)")
+                        .foregroundStyle(AppTheme.statusColor(for: .waiting))
+                }
                 if let prompt = session.firstPrompt, !prompt.isEmpty {
                     Text(prompt)
                         .font(.caption2)
@@ -517,6 +1041,309 @@ struct MenuBarExpandableSessionRow: View {
         }
     }
 
+    private var attachmentList: some View {
+        VStack(alignment: .leading, spacing: 2) {
+            let images = attachments.filter { $0.kind == .image }
+            let files = attachments.filter { $0.kind == .file }
+            Text([images.isEmpty ? nil : "\(images.count) images", files.isEmpty ? nil : "\(files.count) files"]
+                .compactMap { $0 }
+                .joined(separator: ", "))
+                .font(.caption2.weight(.semibold))
+                .foregroundStyle(.secondary)
+
+            ForEach((images + files).prefix(5)) { attachment in
+                Button {
+                    open(attachment)
+                } label: {
+                    Label(attachment.displayName, systemImage: attachment.kind == .image ? "photo" : "doc")
+                        .font(.caption2)
+                        .lineLimit(1)
+                }
+                .buttonStyle(.plain)
+                .foregroundStyle(.secondary)
+                .accessibilityHint(attachment.filePath == nil ? "Opens the image in its default app" : "Shows the file in Finder")
+                .accessibilityIdentifier("menuBar.session.attachment")
+            }
+        }
+        .accessibilityIdentifier("menuBar.session.attachments")
+    }
+
+    @ViewBuilder
+    private var labelMenu: some View {
+        let current = session.label ?? SessionLabel()
+        Menu("Label Color") {
+            ForEach(SessionLabel.Color.allCases, id: \.self) { color in
+                Button(color.rawValue.capitalized) {
+                    var label = current
+                    label.color = color
+                    sessionStore.setLabel(label, for: session.id)
+                }
+            }
+        }
+        Menu("Label Emoji") {
+            ForEach(SessionLabel.suggestedEmoji, id: \.self) { emoji in
+                Button(emoji) {
+                    var label = current
+                    label.emoji = emoji
+                    sessionStore.setLabel(label, for: session.id)
+                }
+            }
+        }
+        if session.label != nil {
+            Button("Remove Label") {
+                sessionStore.setLabel(nil, for: session.id)
+            }
+        }
+    }
+
+    @ViewBuilder
+    private var issueMenu: some View {
+        Button(session.issueKey == nil ? "Link Issue..." : "Change Issue...") {
+            let alert = NSAlert()
+            alert.messageText = "Link Issue"
+            alert.informativeText = "The issue key this session works on, like ENG-142."
+            let field = NSTextField(frame: NSRect(x: 0, y: 0, width: 220, height: 24))
+            field.stringValue = session.issueKey ?? ""
+            field.placeholderString = "ENG-142"
+            alert.accessoryView = field
+            alert.addButton(withTitle: "Link")
+            alert.addButton(withTitle: "Cancel")
+            guard alert.runModal() == .alertFirstButtonReturn else { return }
+            sessionStore.linkIssue(field.stringValue, to: session.id)
+        }
+        if let detected = session.issueKey, sessionStore.linkedIssueKeys[session.id] == nil {
+            Button("Confirm \(detected)") {
+                sessionStore.linkIssue(detected, to: session.id)
+            }
+            .help("Detected from the branch or prompt; confirming lets it be commented on automatically")
+        }
+        if sessionStore.linkedIssueKeys[session.id] != nil {
+            Button("Unlink Issue") {
+                sessionStore.linkIssue(nil, to: session.id)
+            }
+        }
+    }
+
+    private var mcpSummary: String {
+        let used = session.usedMCPServers
+        let configured = Set(session.configuredMCPServers).union(used)
+        guard !used.isEmpty else { return "MCP: \(configured.count) available, none used" }
+        return "MCP: \(used.joined(separator: ", ")) (\(used.count) of \(configured.count) used)"
+    }
+
+    private var timelineBar: some View {
+        let total = timeline.reduce(0) { $0 + $1.duration }
+        let totals = SessionTimeline.totals(timeline)
+        return VStack(alignment: .leading, spacing: 3) {
+            GeometryReader { proxy in
+                HStack(spacing: 0) {
+                    ForEach(timeline, id: \.self) { segment in
+                        Rectangle()
+                            .fill(AppTheme.timelinePhaseColor(for: segment.phase))
+                            .frame(width: total > 0 ? proxy.size.width * CGFloat(segment.duration / total) : 0)
+                            .help("\(segment.phase.rawValue): \(SessionStore.formatDuration(segment.duration))")
+                    }
+                }
+            }
+            .frame(height: 6)
+            .clipShape(RoundedRectangle(cornerRadius: 2))
+
+            HStack(spacing: 8) {
+                ForEach(totals.indices, id: \.self) { index in
+                    HStack(spacing: 3) {
+                        Circle()
+                            .fill(AppTheme.timelinePhaseColor(for: totals[index].phase))
+                            .frame(width: 6, height: 6)
+                        Text("\(totals[index].phase.rawValue) \(SessionStore.formatDuration(totals[index].duration))")
+                    }
+                }
+            }
+            .font(.caption2)
+            .foregroundStyle(.secondary)
+        }
+        .accessibilityElement(children: .combine)
+        .accessibilityLabel("Timeline: " + totals.map { "\($0.phase.rawValue) \(SessionStore.formatDuration($0.duration))" }.joined(separator: ", "))
+        .accessibilityIdentifier("menuBar.session.timeline")
+    }
+
+    private var subagentList: some View {
+        VStack(alignment: .leading, spacing: 2) {
+            Text(subagents.count == 1 ? "1 sub-agent" : "\(subagents.count) sub-agents")
+                .font(.caption2.weight(.semibold))
+                .foregroundStyle(.secondary)
+
+            ForEach(subagents.prefix(5)) { run in
+                HStack(spacing: 4) {
+                    Image(systemName: run.status.icon)
+                        .foregroundStyle(AppTheme.toolCallStatusColor(for: run.status))
+                    Text(run.description)
+                        .lineLimit(1)
+                    Spacer()
+                    Text("\(run.toolCalls.count) tools")
+                    if let duration = run.duration {
+                        Text(SessionStore.formatDuration(duration))
+                    }
+                }
+                .font(.caption2)
+                .foregroundStyle(.secondary)
+                .help(run.agentType.map { "\($0): \(run.prompt)" } ?? run.prompt)
+                .accessibilityIdentifier("menuBar.session.subagent")
+            }
+        }
+        .accessibilityIdentifier("menuBar.session.subagents")
+    }
+
+    private var linkList: some View {
+        VStack(alignment: .leading, spacing: 2) {
+            Text(links.count == 1 ? "1 link" : "\(links.count) links")
+                .font(.caption2.weight(.semibold))
+                .foregroundStyle(.secondary)
+
+            ForEach(links.prefix(5)) { link in
+                Button {
+                    // Only web links: a file: or app-scheme URL from the transcript could launch something.
+                    guard link.url.scheme == "http" || link.url.scheme == "https" else { return }
+                    NSWorkspace.shared.open(link.url)
+                } label: {
+                    Label(link.url.host ?? link.url.absoluteString, systemImage: "link")
+                        .font(.caption2)
+                        .lineLimit(1)
+                }
+                .buttonStyle(.plain)
+                .foregroundStyle(.secondary)
+                .help("\(link.url.absoluteString)\n\(link.source.label): \(link.snippet)")
+                .accessibilityHint("Opens the link in your browser")
+                .accessibilityIdentifier("menuBar.session.link")
+            }
+        }
+        .accessibilityIdentifier("menuBar.session.links")
+    }
+
+    private func compactionSummary(_ last: Compaction) -> String {
+        let time = last.timestamp.formatted(date: .omitted, time: .shortened)
+        let count = compactions.count == 1 ? "Compacted" : "Compacted \(compactions.count)×"
+        return "\(count), last at \(time)"
+    }
+
+    /// Pasted images are written by the app as image files and open in a
+    /// viewer. Files the agent touched are only revealed in Finder: opening
+    /// one would run any `.app` or `.command` the agent wrote.
+    private func open(_ attachment: MessageAttachment) {
+        do {
+            let url = try AttachmentStore.fileURL(for: attachment, sessionId: session.id)
+            if attachment.filePath == nil {
+                NSWorkspace.shared.open(url)
+            } else {
+                NSWorkspace.shared.activateFileViewerSelecting([url])
+            }
+        } catch {
+            AppLogger.logWarning("Cannot open attachment: \(error.localizedDescription)", context: "MenuBarExpandableSessionRow")
+        }
+    }
+
+    private func exportTranscript(sanitized: Bool) {
+        let panel = NSSavePanel()
+        panel.nameFieldStringValue = sanitized ? "session-share.jsonl" : "\(session.id.uuidString.lowercased()).jsonl"
+        panel.allowedContentTypes = [UTType(filenameExtension: "jsonl") ?? .json]
+        // The share copy always anonymizes paths.
+        let anonymizeToggle = NSButton(checkboxWithTitle: "Hide home folder and user name", target: nil, action: nil)
+        if !sanitized { panel.accessoryView = anonymizeToggle }
+        guard panel.runModal() == .OK, let url = panel.url else { return }
+
+        let anonymizer = !sanitized && anonymizeToggle.state == .on ? PathAnonymizer() : nil
+        Task {
+            let jsonl = sanitized
+                ? await sessionStore.shareCopy(for: session)
+                : await sessionStore.claudeJSONL(for: session, anonymizer: anonymizer)
+            guard let jsonl else { return }
+            do {
+                try Data(jsonl.utf8).write(to: url, options: .atomic)
+            } catch {
+                AppLogger.logError(error, context: "exportTranscript")
+            }
+        }
+    }
+
+    private func saveNote() {
+        Task {
+            do {
+                if let url = try await sessionStore.saveNote(for: session) {
+                    NSWorkspace.shared.activateFileViewerSelecting([url])
+                }
+            } catch {
+                AppLogger.logError(error, context: "saveNote")
+            }
+        }
+    }
+
+    private func postIssueComment() {
+        commentStatus = "Posting..."
+        Task {
+            do {
+                let key = try await sessionStore.postIssueComment(for: session)
+                commentStatus = "Commented on \(key)"
+            } catch {
+                AppLogger.logError(error, context: "postIssueComment")
+                commentStatus = error.localizedDescription
+            }
+        }
+    }
+
+    private func postPullRequestComment() {
+        commentStatus = "Posting..."
+        Task {
+            do {
+                let pullRequest = try await sessionStore.postPullRequestComment(for: session)
+                commentStatus = "Commented on PR #\(pullRequest.number)"
+            } catch {
+                AppLogger.logError(error, context: "postPullRequestComment")
+                commentStatus = error.localizedDescription
+            }
+        }
+    }
+
+    private var transcriptSearch: some View {
+        VStack(alignment: .leading, spacing: 4) {
+            TextField("Find in session", text: $searchQuery)
+                .textFieldStyle(.roundedBorder)
+                .font(.caption)
+                .accessibilityIdentifier("menuBar.session.search")
+
+            ForEach(searchResults.prefix(5)) { match in
+                VStack(alignment: .leading, spacing: 1) {
+                    Text(match.sourceLabel)
+                        .font(.caption2.weight(.semibold))
+                        .foregroundStyle(.secondary)
+                    Text(match.snippet)
+                        .font(.caption2)
+                        .foregroundStyle(.tertiary)
+                        .lineLimit(2)
+                }
+                .accessibilityElement(children: .combine)
+                .accessibilityIdentifier("menuBar.session.searchResult")
+            }
+
+            if searchResults.count > 5 {
+                Text("+\(searchResults.count - 5) more matches")
+                    .font(.caption2)
+                    .foregroundStyle(.tertiary)
+            } else if !searchQuery.isEmpty && searchResults.isEmpty {
+                Text("No matches")
+                    .font(.caption2)
+                    .foregroundStyle(.tertiary)
+            }
+        }
+        .task(id: searchQuery) {
+            guard !searchQuery.trimmingCharacters(in: .whitespaces).isEmpty else {
+                searchResults = []
+                return
+            }
+            try? await Task.sleep(for: .milliseconds(300))
+            guard !Task.isCancelled else { return }
+            searchResults = await sessionStore.search(in: session, query: searchQuery)
+        }
+    }
+
     private func metricItem(icon: String, text: String) -> some View {
         HStack(spacing: 4) {
             Image(systemName: icon)
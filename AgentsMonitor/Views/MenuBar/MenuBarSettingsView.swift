@@ -1,15 +1,50 @@
 import SwiftUI
+import UniformTypeIdentifiers
 
 struct MenuBarSettingsView: View {
     @Environment(SessionStore.self) private var sessionStore
     @AppStorage("launchAtLogin") private var launchAtLogin = false
     @AppStorage("notificationsEnabled") private var notificationsEnabled = true
     @AppStorage("refreshInterval") private var refreshInterval: Double = 5.0
+    @AppStorage("refreshInBackground") private var refreshInBackground = true
     @AppStorage("appearance") private var appearance: String = "system"
     @AppStorage("activeOnly") private var activeOnly = false
     @AppStorage("showSidechains") private var showSidechains = false
     @AppStorage("codexEnabled") private var codexEnabled = true
     @AppStorage("claudeCodeEnabled") private var claudeCodeEnabled = true
+    @AppStorage("claudeDirectory") private var claudeDirectory = ""
+    @AppStorage("codexDirectory") private var codexDirectory = ""
+    @AppStorage("analyticsEnabled") private var analyticsEnabled = false
+    @AppStorage("appLockEnabled") private var appLockEnabled = false
+    @AppStorage("otlpEndpoint") private var otlpEndpoint = ""
+    @AppStorage("notesFolder") private var notesFolder = ""
+    @AppStorage("writeNotesOnCompletion") private var writeNotesOnCompletion = false
+    @AppStorage("issueTracker") private var issueTracker = ""
+    @AppStorage("jiraBaseURL") private var jiraBaseURL = ""
+    @AppStorage("jiraEmail") private var jiraEmail = ""
+    @AppStorage("postIssueCommentsOnCompletion") private var postIssueCommentsOnCompletion = false
+    @AppStorage("postPullRequestCommentsOnCompletion") private var postPullRequestCommentsOnCompletion = false
+
+    @State private var transferError: String?
+    @State private var memoryReport: MemoryReport?
+    @State private var healthReport: HealthReport?
+    @State private var telemetryStatus: String?
+    @State private var databaseExportStatus: String?
+    @State private var trackerToken = ""
+    @State private var trackerTokenStatus: String?
+    @State private var gitHubToken = ""
+    @State private var gitHubTokenStatus: String?
+    @State private var otlpHeaders = ""
+    @State private var otlpHeadersStatus: String?
+    @State private var isSendingTelemetry = false
+    @State private var insightsDays = 30
+    @State private var activityHeatmap = ActivityHeatmap()
+    @State private var insightsBatch: TranscriptBatch?
+    @State private var hotspotProject = ""
+    @State private var hotspots: [FileHotspot]?
+    @State private var failureClusters: [FailureCluster] = []
+    @State private var efficiency: [AgentType: EfficiencyMetrics] = [:]
+    @State private var usageInsights: UsageAnalytics.Insights?
 
     let navigateBack: () -> Void
 
@@ -44,6 +79,10 @@ struct MenuBarSettingsView: View {
                         Toggle("Notifications", isOn: $notificationsEnabled)
                         Toggle("Active only", isOn: $activeOnly)
                         Toggle("Show sidechains", isOn: $showSidechains)
+                        Toggle("Require unlock", isOn: $appLockEnabled)
+                            .accessibilityLabel("Require unlock")
+                            .accessibilityHint("Asks for Touch ID or your password before showing sessions, and again after five idle minutes")
+                            .accessibilityIdentifier("menuBar.settings.appLock")
                         Toggle("Enable Codex", isOn: $codexEnabled)
                             .accessibilityLabel("Enable Codex")
                             .accessibilityHint("Shows Codex sessions and refreshes Codex data when enabled")
@@ -66,6 +105,132 @@ struct MenuBarSettingsView: View {
                             .labelsHidden()
                             .frame(width: 100)
                         }
+
+                        if FeatureFlags.current.isEnabled(.usageInsights) {
+                            Toggle("Local usage insights", isOn: $analyticsEnabled)
+                                .accessibilityLabel("Local usage insights")
+                                .accessibilityHint("Records feature use and session outcomes on this Mac only")
+                                .accessibilityIdentifier("menuBar.settings.analytics")
+                        }
+
+                        Toggle("Refresh while closed", isOn: $refreshInBackground)
+                            .accessibilityLabel("Refresh while closed")
+                            .accessibilityHint("Keeps checking sessions every minute while the popover is closed")
+                            .accessibilityIdentifier("menuBar.settings.refreshInBackground")
+                    }
+
+                    if FeatureFlags.current.isEnabled(.usageInsights) && analyticsEnabled {
+                        settingsSection("USAGE INSIGHTS") {
+                            if let usageInsights {
+                                usageInsightsRows(usageInsights)
+                            }
+                            Button("Reset Usage Insights", action: resetUsageInsights)
+                                .disabled(usageInsights == UsageAnalytics.Insights())
+                                .accessibilityHint("Deletes the recorded feature use and session outcomes")
+                                .accessibilityIdentifier("menuBar.settings.resetUsageInsights")
+                        }
+                        .task {
+                            usageInsights = await sessionStore.usageInsights()
+                        }
+                        .accessibilityIdentifier("menuBar.settings.usageInsights")
+                    }
+
+                    // Insights
+                    settingsSection("INSIGHTS") {
+                        HStack {
+                            Text("Activity")
+                            Spacer()
+                            Picker("", selection: $insightsDays) {
+                                Text("7 days").tag(7)
+                                Text("30 days").tag(30)
+                                Text("All").tag(0)
+                            }
+                            .labelsHidden()
+                            .frame(width: 100)
+                            .accessibilityIdentifier("menuBar.settings.insightsRange")
+                        }
+                        activityHeatmapView(activityHeatmap)
+                            .task(id: insightsDays) {
+                                activityHeatmap = await sessionStore.activityHeatmap(in: insightsRange, batch: sharedInsightsBatch())
+                            }
+
+                        HStack {
+                            Text("Most-edited files")
+                            Spacer()
+                            Picker("", selection: $hotspotProject) {
+                                Text("All projects").tag("")
+                                ForEach(insightProjects, id: \.self) { path in
+                                    Text(URL(fileURLWithPath: path).lastPathComponent).tag(path)
+                                }
+                            }
+                            .labelsHidden()
+                            .frame(width: 120)
+                            .accessibilityIdentifier("menuBar.settings.hotspotProject")
+                        }
+                        if let hotspots {
+                            if hotspots.isEmpty {
+                                Text("No file edits in the listed sessions")
+                                    .font(.caption)
+                                    .foregroundStyle(.secondary)
+                            }
+                            ForEach(hotspots.prefix(5)) { hotspot in
+                                HStack(spacing: 4) {
+                                    Text(hotspot.path)
+                                        .lineLimit(1)
+                                        .truncationMode(.head)
+                                    Spacer()
+                                    Text("\(hotspot.edits) edits")
+                                    if hotspot.failedEdits + hotspot.failedSessions > 0 {
+                                        Image(systemName: "exclamationmark.triangle.fill")
+                                            .foregroundStyle(.orange)
+                                            .help("\(hotspot.failedEdits) failed edits, \(hotspot.failedSessions) failed sessions")
+                                    }
+                                }
+                                .font(.caption2)
+                                .foregroundStyle(.secondary)
+                                .help("\(hotspot.sessions) sessions, +\(hotspot.additions) −\(hotspot.deletions)")
+                                .accessibilityIdentifier("menuBar.settings.hotspot")
+                            }
+                        }
+
+                        if !efficiency.isEmpty {
+                            Text("Efficiency")
+                            ForEach(AgentType.allCases.filter { efficiency[$0] != nil }, id: \.self) { agent in
+                                if let metrics = efficiency[agent] {
+                                    HStack(spacing: 4) {
+                                        Text(agent.rawValue)
+                                        Spacer()
+                                        Text(efficiencySummary(metrics))
+                                    }
+                                    .font(.caption2)
+                                    .foregroundStyle(.secondary)
+                                    .help("\(metrics.sessions) sessions, \(metrics.filesChanged) files and \(metrics.linesChanged) lines changed")
+                                    .accessibilityIdentifier("menuBar.settings.efficiency")
+                                }
+                            }
+                        }
+
+                        if !failureClusters.isEmpty {
+                            Text("Recurring failures")
+                            ForEach(failureClusters.prefix(5)) { cluster in
+                                HStack(alignment: .firstTextBaseline, spacing: 4) {
+                                    Text(cluster.example)
+                                        .lineLimit(1)
+                                    Spacer()
+                                    Text(cluster.sessionIds.count == 1 ? "1 session" : "\(cluster.sessionIds.count) sessions")
+                                }
+                                .font(.caption2)
+                                .foregroundStyle(.secondary)
+                                .help("\(cluster.occurrences)× \(cluster.tools.isEmpty ? "" : "in \(cluster.tools.sorted().joined(separator: ", ")) ")· \(cluster.example)")
+                                .accessibilityIdentifier("menuBar.settings.failureCluster")
+                            }
+                        }
+                    }
+                    .task(id: hotspotProject) {
+                        hotspots = await sessionStore.fileHotspots(projectPath: hotspotProject.isEmpty ? nil : hotspotProject, batch: sharedInsightsBatch())
+                    }
+                    .task {
+                        (efficiency, failureClusters) = await sessionStore.outcomeInsights(batch: sharedInsightsBatch())
                     }
 
                     // Appearance
@@ -83,18 +248,480 @@ struct MenuBarSettingsView: View {
                             .frame(width: 180)
                         }
                     }
+
+                    // Data locations
+                    settingsSection("DATA LOCATIONS") {
+                        directoryField("Claude Code", text: $claudeDirectory, placeholder: "~/.claude")
+                            .accessibilityIdentifier("menuBar.settings.claudeDirectory")
+                        directoryField("Codex", text: $codexDirectory, placeholder: "~/.codex")
+                            .accessibilityIdentifier("menuBar.settings.codexDirectory")
+                    }
+
+                    // Notes
+                    settingsSection("NOTES") {
+                        HStack {
+                            Text("Vault folder")
+                            Spacer()
+                            TextField("~/Notes/Agents", text: $notesFolder)
+                                .textFieldStyle(.roundedBorder)
+                                .frame(width: 160)
+                                .accessibilityLabel("Notes vault folder")
+                                .accessibilityHint("Leave empty to turn session notes off")
+                                .accessibilityIdentifier("menuBar.settings.notesFolder")
+                        }
+                        Toggle("Write a note when a session ends", isOn: $writeNotesOnCompletion)
+                            .disabled(notesFolder.isEmpty)
+                            .accessibilityHint("Saves each finished session as Markdown with frontmatter in the vault folder")
+                            .accessibilityIdentifier("menuBar.settings.writeNotesOnCompletion")
+                    }
+
+                    // Issue tracker
+                    settingsSection("ISSUE TRACKER") {
+                        HStack {
+                            Text("Tracker")
+                            Spacer()
+                            Picker("", selection: $issueTracker) {
+                                Text("None").tag("")
+                                ForEach(IssueTracker.allCases, id: \.self) { tracker in
+                                    Text(tracker.displayName).tag(tracker.rawValue)
+                                }
+                            }
+                            .labelsHidden()
+                            .frame(width: 120)
+                            .accessibilityIdentifier("menuBar.settings.issueTracker")
+                        }
+                        if let tracker = IssueTracker(rawValue: issueTracker) {
+                            if tracker == .jira {
+                                trackerField("Site", text: $jiraBaseURL, placeholder: "https://acme.atlassian.net")
+                                    .accessibilityIdentifier("menuBar.settings.jiraBaseURL")
+                                trackerField("Email", text: $jiraEmail, placeholder: "you@acme.com")
+                                    .accessibilityIdentifier("menuBar.settings.jiraEmail")
+                            }
+                            HStack {
+                                SecureField(KeychainStore.hasToken(for: tracker.keychainAccount) ? "Token saved" : "API token", text: $trackerToken)
+                                    .textFieldStyle(.roundedBorder)
+                                    .accessibilityLabel("\(tracker.displayName) API token")
+                                    .accessibilityIdentifier("menuBar.settings.issueTrackerToken")
+                                Button("Save") { saveTrackerToken(for: tracker) }
+                                    .disabled(trackerToken.isEmpty)
+                                    .accessibilityHint("Stores the token in your keychain")
+                            }
+                            if let trackerTokenStatus {
+                                Text(trackerTokenStatus)
+                                    .font(.caption)
+                                    .foregroundStyle(.secondary)
+                            }
+                            Toggle("Comment when a session ends", isOn: $postIssueCommentsOnCompletion)
+                                .help("Only for issues you linked or confirmed on the session")
+                                .accessibilityHint("Posts the session's status, cost and files changed to its linked issue")
+                                .accessibilityIdentifier("menuBar.settings.postIssueComments")
+                        }
+                    }
+
+                    // GitHub
+                    settingsSection("GITHUB") {
+                        HStack {
+                            SecureField(KeychainStore.hasToken(for: GitHubPullRequests.keychainAccount) ? "Token saved" : "Personal access token", text: $gitHubToken)
+                                .textFieldStyle(.roundedBorder)
+                                .accessibilityLabel("GitHub personal access token")
+                                .accessibilityIdentifier("menuBar.settings.gitHubToken")
+                            Button("Save", action: saveGitHubToken)
+                                .disabled(gitHubToken.isEmpty)
+                                .accessibilityHint("Stores the token in your keychain")
+                        }
+                        if let gitHubTokenStatus {
+                            Text(gitHubTokenStatus)
+                                .font(.caption)
+                                .foregroundStyle(.secondary)
+                        }
+                        Toggle("Comment on the pull request when a session ends", isOn: $postPullRequestCommentsOnCompletion)
+                            .accessibilityHint("Posts what changed, the tests run and the cost to the last pull request the session links to")
+                            .accessibilityIdentifier("menuBar.settings.postPullRequestComments")
+                    }
+
+                    // Backup
+                    settingsSection("BACKUP") {
+                        HStack {
+                            Button("Export Settings...", action: exportSettings)
+                                .accessibilityHint("Saves all settings to a JSON file")
+                                .accessibilityIdentifier("menuBar.settings.export")
+                            Button("Import...", action: importSettings)
+                                .accessibilityHint("Loads settings from a previously exported JSON file")
+                                .accessibilityIdentifier("menuBar.settings.import")
+                        }
+                        Button("Export Analytics Database...", action: exportAnalyticsDatabase)
+                            .accessibilityHint("Saves the listed sessions, messages and tool calls to a SQLite file")
+                            .accessibilityIdentifier("menuBar.settings.exportAnalyticsDatabase")
+                        if let databaseExportStatus {
+                            Text(databaseExportStatus)
+                                .font(.caption)
+                                .foregroundStyle(.secondary)
+                                .lineLimit(2)
+                        }
+                        if let transferError {
+                            HStack(spacing: 4) {
+                                Image(systemName: "exclamationmark.triangle.fill")
+                                    .foregroundStyle(.orange)
+                                Text(transferError)
+                                    .foregroundStyle(.secondary)
+                                    .lineLimit(2)
+                            }
+                            .font(.caption)
+                        }
+                    }
+
+                    // Telemetry
+                    settingsSection("TELEMETRY") {
+                        HStack {
+                            Text("OTLP endpoint")
+                            Spacer()
+                            TextField("http://localhost:4318", text: $otlpEndpoint)
+                                .textFieldStyle(.roundedBorder)
+                                .frame(width: 160)
+                                .accessibilityLabel("OpenTelemetry collector endpoint")
+                                .accessibilityIdentifier("menuBar.settings.otlpEndpoint")
+                        }
+                        HStack {
+                            SecureField(KeychainStore.hasToken(for: OTLPExporter.keychainAccount) ? "Headers saved" : "Headers or token", text: $otlpHeaders)
+                                .textFieldStyle(.roundedBorder)
+                                .help("key=value pairs separated by commas, or a bearer token")
+                                .accessibilityLabel("OpenTelemetry collector headers")
+                                .accessibilityIdentifier("menuBar.settings.otlpHeaders")
+                            Button("Save", action: saveOTLPHeaders)
+                                .disabled(otlpHeaders.isEmpty)
+                                .accessibilityHint("Stores the headers in your keychain")
+                        }
+                        if let otlpHeadersStatus {
+                            Text(otlpHeadersStatus)
+                                .font(.caption)
+                                .foregroundStyle(.secondary)
+                        }
+                        HStack {
+                            Button("Send to Collector", action: sendTelemetry)
+                                .disabled(isSendingTelemetry || OTLPExporter.tracesURL(for: otlpEndpoint) == nil)
+                                .accessibilityHint("Sends the listed sessions and their tool calls as OpenTelemetry traces")
+                                .accessibilityIdentifier("menuBar.settings.sendTelemetry")
+                            if isSendingTelemetry {
+                                ProgressView()
+                                    .controlSize(.small)
+                            }
+                        }
+                        if let telemetryStatus {
+                            Text(telemetryStatus)
+                                .font(.caption)
+                                .foregroundStyle(.secondary)
+                                .lineLimit(2)
+                        }
+                    }
+
+                    // Diagnostics
+                    settingsSection("DIAGNOSTICS") {
+                        if let memoryReport {
+                            diagnosticsRow("Memory", value: memoryReport.formattedResidentSize)
+                            diagnosticsRow("Sessions", value: "\(memoryReport.sessionCount)")
+                            diagnosticsRow("Cached files", value: "\(memoryReport.cachedEntryCount)")
+                        }
+                        let startup = sessionStore.startupDiagnostics
+                        if let firstSessions = startup.timeToFirstSessions {
+                            diagnosticsRow("Startup", value: StartupDiagnostics.format(firstSessions))
+                            diagnosticsRow(
+                                "Claude scan",
+                                value: "\(startup.claudeSessionCount) in \(StartupDiagnostics.format(startup.claudeDiscovery))"
+                            )
+                            diagnosticsRow(
+                                "Codex scan",
+                                value: "\(startup.codexSessionCount) in \(StartupDiagnostics.format(startup.codexDiscovery))"
+                            )
+                        }
+                    }
+                    .accessibilityIdentifier("menuBar.settings.diagnostics")
+
+                    // Health
+                    settingsSection("HEALTH") {
+                        if let healthReport {
+                            ForEach(healthReport.checks) { check in
+                                HStack(alignment: .top, spacing: 4) {
+                                    Image(systemName: check.isHealthy ? "checkmark.circle" : "exclamationmark.triangle.fill")
+                                        .foregroundStyle(check.isHealthy ? AppTheme.statusColor(for: .completed) : .orange)
+                                    VStack(alignment: .leading, spacing: 1) {
+                                        Text(check.name)
+                                        Text(check.detail)
+                                            .foregroundStyle(.secondary)
+                                            .lineLimit(2)
+                                    }
+                                }
+                                .font(.caption)
+                            }
+                        }
+                        HStack {
+                            Button("Run Checks") {
+                                Task { healthReport = await sessionStore.healthCheck() }
+                            }
+                            .accessibilityIdentifier("menuBar.settings.healthCheck")
+                            if healthReport?.canRepair == true {
+                                Button("Repair") {
+                                    Task {
+                                        await sessionStore.repairCaches()
+                                        healthReport = await sessionStore.healthCheck()
+                                    }
+                                }
+                                .accessibilityHint("Drops cached results for deleted transcripts")
+                                .accessibilityIdentifier("menuBar.settings.healthRepair")
+                            }
+                        }
+                    }
+                    .accessibilityIdentifier("menuBar.settings.health")
                 }
                 .padding()
             }
         }
+        .task {
+            memoryReport = await sessionStore.memoryReport()
+        }
         .frame(width: 300)
-        .onChange(of: codexEnabled) { _, _ in
-            refreshSessions()
+        .accessibilityIdentifier("menuBar.settings.view")
+    }
+
+    private func exportSettings() {
+        let panel = NSSavePanel()
+        panel.nameFieldStringValue = "AgentsMonitor-settings.json"
+        panel.allowedContentTypes = [.json]
+        let anonymizeToggle = NSButton(checkboxWithTitle: "Hide home folder and user name", target: nil, action: nil)
+        anonymizeToggle.state = .on
+        panel.accessoryView = anonymizeToggle
+        guard panel.runModal() == .OK, let url = panel.url else { return }
+
+        do {
+            let anonymizer = anonymizeToggle.state == .on ? PathAnonymizer() : nil
+            try AppSettings.load().exportData(anonymizer: anonymizer).write(to: url, options: .atomic)
+            transferError = nil
+            sessionStore.recordFeatureUse(.settingsExported)
+        } catch {
+            AppLogger.logError(error, context: "exportSettings")
+            transferError = error.localizedDescription
         }
-        .onChange(of: claudeCodeEnabled) { _, _ in
-            refreshSessions()
+    }
+
+    private func exportAnalyticsDatabase() {
+        let panel = NSSavePanel()
+        panel.nameFieldStringValue = "AgentsMonitor-analytics.sqlite"
+        panel.allowedContentTypes = [UTType(filenameExtension: "sqlite") ?? .database]
+        guard panel.runModal() == .OK, let url = panel.url else { return }
+
+        databaseExportStatus = "Exporting..."
+        Task {
+            do {
+                let count = try await sessionStore.exportAnalyticsDatabase(to: url)
+                databaseExportStatus = count == 1 ? "Exported 1 session" : "Exported \(count) sessions"
+            } catch {
+                AppLogger.logError(error, context: "exportAnalyticsDatabase")
+                databaseExportStatus = error.localizedDescription
+            }
         }
-        .accessibilityIdentifier("menuBar.settings.view")
+    }
+
+    private func saveGitHubToken() {
+        do {
+            try KeychainStore.setToken(gitHubToken.trimmingCharacters(in: .whitespacesAndNewlines), for: GitHubPullRequests.keychainAccount)
+            gitHubToken = ""
+            gitHubTokenStatus = "Saved to your keychain"
+        } catch {
+            AppLogger.logError(error, context: "saveGitHubToken")
+            gitHubTokenStatus = error.localizedDescription
+        }
+    }
+
+    private func saveOTLPHeaders() {
+        do {
+            try KeychainStore.setToken(otlpHeaders.trimmingCharacters(in: .whitespacesAndNewlines), for: OTLPExporter.keychainAccount)
+            otlpHeaders = ""
+            otlpHeadersStatus = "Saved to your keychain"
+        } catch {
+            AppLogger.logError(error, context: "saveOTLPHeaders")
+            otlpHeadersStatus = error.localizedDescription
+        }
+    }
+
+    private func saveTrackerToken(for tracker: IssueTracker) {
+        do {
+            try KeychainStore.setToken(trackerToken.trimmingCharacters(in: .whitespacesAndNewlines), for: tracker.keychainAccount)
+            trackerToken = ""
+            trackerTokenStatus = "Saved to your keychain"
+        } catch {
+            AppLogger.logError(error, context: "saveTrackerToken")
+            trackerTokenStatus = error.localizedDescription
+        }
+    }
+
+    private func importSettings() {
+        let panel = NSOpenPanel()
+        panel.allowedContentTypes = [.json]
+        panel.allowsMultipleSelection = false
+        guard panel.runModal() == .OK, let url = panel.url else { return }
+
+        let alert = NSAlert()
+        alert.messageText = "Import Settings"
+        alert.informativeText = "Keep the settings you have already changed on this Mac, or replace everything with the imported values?"
+        alert.addButton(withTitle: "Keep My Changes")
+        alert.addButton(withTitle: "Replace All")
+        alert.addButton(withTitle: "Cancel")
+
+        let strategy: AppSettings.ImportStrategy
+        switch alert.runModal() {
+        case .alertFirstButtonReturn: strategy = .keepLocalChanges
+        case .alertSecondButtonReturn: strategy = .replaceAll
+        default: return
+        }
+
+        do {
+            let data = try Data(contentsOf: url)
+            try AppSettings.importing(data, over: AppSettings.load(), strategy: strategy).save()
+            transferError = nil
+            sessionStore.recordFeatureUse(.settingsImported)
+        } catch {
+            AppLogger.logError(error, context: "importSettings")
+            transferError = error.localizedDescription
+        }
+    }
+
+    private func sendTelemetry() {
+        isSendingTelemetry = true
+        Task {
+            do {
+                let count = try await sessionStore.exportTelemetry()
+                telemetryStatus = count == 1 ? "Sent 1 session" : "Sent \(count) sessions"
+            } catch {
+                AppLogger.logError(error, context: "sendTelemetry")
+                telemetryStatus = error.localizedDescription
+            }
+            isSendingTelemetry = false
+        }
+    }
+
+    private func directoryField(_ title: String, text: Binding<String>, placeholder: String) -> some View {
+        DirectoryField(title: title, value: text, placeholder: placeholder)
+    }
+
+    private func efficiencySummary(_ metrics: EfficiencyMetrics) -> String {
+        var parts: [String] = []
+        if let tokensPerFile = metrics.tokensPerFile {
+            parts.append("\(Int(tokensPerFile).formatted(.number.notation(.compactName))) tokens/file")
+        }
+        if let costPerLine = metrics.costPerChangedLine {
+            parts.append(String(format: "$%.3f/line", costPerLine))
+        }
+        parts.append(metrics.retries == 1 ? "1 retry" : "\(metrics.retries) retries")
+        return parts.joined(separator: " · ")
+    }
+
+    private var insightProjects: [String] {
+        Array(Set(sessionStore.sessions.compactMap(\.projectPath))).sorted()
+    }
+
+    /// The last `insightsDays` days, or everything when it is 0.
+    /// One batch for every INSIGHTS row while Settings is open, so changing
+    /// the range or project doesn't read the transcripts again.
+    private func sharedInsightsBatch() -> TranscriptBatch {
+        if let insightsBatch { return insightsBatch }
+        let batch = sessionStore.makeTranscriptBatch()
+        insightsBatch = batch
+        return batch
+    }
+
+    private var insightsRange: DateInterval? {
+        guard insightsDays > 0 else { return nil }
+        let now = Date()
+        return DateInterval(start: now.addingTimeInterval(-Double(insightsDays) * 86_400), end: now)
+    }
+
+    private func activityHeatmapView(_ heatmap: ActivityHeatmap) -> some View {
+        let symbols = Calendar.current.veryShortWeekdaySymbols
+        let peak = heatmap.peak
+        return VStack(alignment: .leading, spacing: 2) {
+            if let busiest = heatmap.busiestSlot {
+                ForEach(0..<7, id: \.self) { weekday in
+                    HStack(spacing: 1) {
+                        Text(symbols[weekday])
+                            .font(.system(size: 8))
+                            .foregroundStyle(.secondary)
+                            .frame(width: 10, alignment: .leading)
+                        ForEach(0..<24, id: \.self) { hour in
+                            let value = heatmap.seconds[weekday][hour]
+                            Rectangle()
+                                .fill(Color.accentColor.opacity(value > 0 ? 0.15 + 0.85 * value / peak : 0.05))
+                                .frame(height: 8)
+                                .help("\(Calendar.current.weekdaySymbols[weekday]) \(hour):00 · \(SessionStore.formatDuration(value))")
+                        }
+                    }
+                }
+                Text("Busiest: \(Calendar.current.weekdaySymbols[busiest.weekday]) around \(busiest.hour):00")
+                    .font(.caption2)
+                    .foregroundStyle(.secondary)
+            } else {
+                Text("No session activity in this range")
+                    .font(.caption)
+                    .foregroundStyle(.secondary)
+            }
+        }
+        .accessibilityElement(children: .ignore)
+        .accessibilityLabel(heatmap.busiestSlot.map { "Activity heatmap. Busiest: \(Calendar.current.weekdaySymbols[$0.weekday]) around \($0.hour):00" } ?? "No session activity")
+        .accessibilityIdentifier("menuBar.settings.activityHeatmap")
+    }
+
+    private func trackerField(_ title: String, text: Binding<String>, placeholder: String) -> some View {
+        HStack {
+            Text(title)
+            Spacer()
+            TextField(placeholder, text: text)
+                .textFieldStyle(.roundedBorder)
+                .frame(width: 160)
+                .accessibilityLabel("Jira \(title.lowercased())")
+        }
+    }
+
+    @ViewBuilder
+    private func usageInsightsRows(_ insights: UsageAnalytics.Insights) -> some View {
+        if let since = insights.trackingSince {
+            diagnosticsRow("Tracking since", value: since.formatted(date: .abbreviated, time: .omitted))
+        }
+        diagnosticsRow("Sessions", value: "\(insights.sessionCount)")
+        if insights.sessionCount > 0 {
+            diagnosticsRow("Failure rate", value: insights.failureRate.formatted(.percent.precision(.fractionLength(0))))
+            diagnosticsRow("Average duration", value: SessionStore.formatDuration(insights.averageDuration))
+            diagnosticsRow("Total cost", value: SessionStore.formatCost(insights.totalCost))
+        }
+        ForEach(AgentType.allCases.filter { insights.sessionsByAgent[$0] != nil }, id: \.self) { agent in
+            diagnosticsRow(agent.rawValue, value: "\(insights.sessionsByAgent[agent] ?? 0) sessions")
+        }
+        let features = insights.featureCounts.sorted { ($0.value, $1.key.rawValue) > ($1.value, $0.key.rawValue) }
+        if let top = features.first {
+            diagnosticsRow("Most used", value: "\(top.key.rawValue) (\(top.value)×)")
+        }
+    }
+
+    private func resetUsageInsights() {
+        let alert = NSAlert()
+        alert.messageText = "Reset Usage Insights"
+        alert.informativeText = "Delete the feature use and session outcomes recorded on this Mac?"
+        alert.addButton(withTitle: "Reset")
+        alert.addButton(withTitle: "Cancel")
+        guard alert.runModal() == .alertFirstButtonReturn else { return }
+
+        Task {
+            await sessionStore.resetUsageInsights()
+            usageInsights = await sessionStore.usageInsights()
+        }
+    }
+
+    private func diagnosticsRow(_ title: String, value: String) -> some View {
+        HStack {
+            Text(title)
+            Spacer()
+            Text(value)
+                .foregroundStyle(.secondary)
+                .monospacedDigit()
+        }
+        .font(.caption)
     }
 
     private func settingsSection(_ title: String, @ViewBuilder content: () -> some View) -> some View {
@@ -107,10 +734,40 @@ struct MenuBarSettingsView: View {
             }
         }
     }
+}
 
-    private func refreshSessions() {
-        Task {
-            await sessionStore.refresh()
+/// Edits a data directory without writing it on every keystroke: each write
+/// rescans the sessions, so the value is stored on Return or when the field
+/// loses focus.
+private struct DirectoryField: View {
+    let title: String
+    @Binding var value: String
+    let placeholder: String
+
+    @State private var draft = ""
+    @FocusState private var isFocused: Bool
+
+    var body: some View {
+        HStack {
+            Text(title)
+            Spacer()
+            TextField(placeholder, text: $draft)
+                .textFieldStyle(.roundedBorder)
+                .frame(width: 160)
+                .focused($isFocused)
+                .onSubmit(commit)
+                .onChange(of: isFocused) { _, focused in
+                    if !focused { commit() }
+                }
+                .accessibilityLabel("\(title) data directory")
+                .accessibilityHint("Leave empty to use the default location, press Return to apply")
         }
+        .onAppear { draft = value }
+        .onDisappear(perform: commit)
+    }
+
+    private func commit() {
+        let trimmed = draft.trimmingCharacters(in: .whitespacesAndNewlines)
+        if trimmed != value { value = trimmed }
     }
 }
@@ -0,0 +1,318 @@
+import XCTest
+@testable import AgentsMonitor
+
+final class AppSettingsTests: XCTestCase {
+
+    private var defaults: UserDefaults!
+    private var suiteName: String!
+
+    override func setUp() {
+        super.setUp()
+        suiteName = "AppSettingsTests.\(UUID().uuidString)"
+        defaults = UserDefaults(suiteName: suiteName)
+        defaults.removePersistentDomain(forName: suiteName)
+    }
+
+    override func tearDown() {
+        defaults.removePersistentDomain(forName: suiteName)
+        defaults = nil
+        super.tearDown()
+    }
+
+    func testLoadReturnsDefaultsWhenUnset() {
+        XCTAssertEqual(AppSettings.load(from: defaults), AppSettings.defaults)
+    }
+
+    func testSaveAndLoadRoundtrip() {
+        var settings = AppSettings()
+        settings.activeOnly = true
+        settings.codexEnabled = false
+        settings.refreshInterval = 30
+        settings.appearance = "dark"
+        settings.save(to: defaults)
+
+        XCTAssertEqual(AppSettings.load(from: defaults), settings)
+    }
+
+    func testValidationSnapsRefreshIntervalToNearestAllowedValue() {
+        var settings = AppSettings()
+        settings.refreshInterval = 7
+        XCTAssertEqual(settings.validated().refreshInterval, 5)
+
+        settings.refreshInterval = -3
+        XCTAssertEqual(settings.validated().refreshInterval, 0)
+    }
+
+    func testValidationResetsUnknownAppearance() {
+        defaults.set("sepia", forKey: AppSettings.Key.appearance)
+        XCTAssertEqual(AppSettings.load(from: defaults).appearance, "system")
+    }
+
+    func testUpdatePersistsChange() {
+        let updated = AppSettings.update(in: defaults) { $0.showSidechains = true }
+        XCTAssertTrue(updated.showSidechains)
+        XCTAssertTrue(defaults.bool(forKey: AppSettings.Key.showSidechains))
+    }
+
+    func testAffectsSessionDiscovery() {
+        let base = AppSettings()
+        var appearanceOnly = base
+        appearanceOnly.appearance = "dark"
+        XCTAssertFalse(appearanceOnly.affectsSessionDiscovery(comparedTo: base))
+
+        var sidechains = base
+        sidechains.showSidechains = true
+        XCTAssertTrue(sidechains.affectsSessionDiscovery(comparedTo: base))
+    }
+
+    func testClaudeDirectoryDefaultsToHome() {
+        let url = AppSettings().claudeDirectoryURL(environment: [:], home: "/Users/test")
+        XCTAssertEqual(url.path, "/Users/test/.claude")
+    }
+
+    func testClaudeDirectoryHonorsEnvironment() {
+        let url = AppSettings().claudeDirectoryURL(environment: ["CLAUDE_CONFIG_DIR": "/opt/claude"], home: "/Users/test")
+        XCTAssertEqual(url.path, "/opt/claude")
+    }
+
+    func testConfiguredDirectoryWinsOverEnvironmentAndExpandsTilde() {
+        var settings = AppSettings()
+        settings.codexDirectory = "~/Sync/codex"
+        let url = settings.codexDirectoryURL(environment: ["CODEX_HOME": "/opt/codex"], home: "/Users/test")
+        XCTAssertEqual(url.path, "/Users/test/Sync/codex")
+    }
+
+    func testDirectoryChangeAffectsSessionDiscovery() {
+        var moved = AppSettings()
+        moved.claudeDirectory = "/Volumes/Shared/claude"
+        XCTAssertTrue(moved.affectsSessionDiscovery(comparedTo: AppSettings()))
+    }
+
+    // MARK: - Import / Export
+
+    func testExportImportRoundtrip() throws {
+        var settings = AppSettings()
+        settings.activeOnly = true
+        settings.refreshInterval = 10
+        settings.codexDirectory = "/data/codex"
+
+        let data = try settings.exportData()
+        let imported = try AppSettings.importing(data, over: AppSettings(), strategy: .replaceAll)
+        XCTAssertEqual(imported, settings)
+    }
+
+    func testAnonymizedExportHidesHomeFolder() throws {
+        var settings = AppSettings()
+        settings.claudeDirectory = "/Users/alice/work/.claude"
+        settings.codexDirectory = "/Volumes/alice-backup/codex"
+
+        let anonymizer = PathAnonymizer(homeDirectory: "/Users/alice", userName: "alice")
+        let data = try settings.exportData(anonymizer: anonymizer)
+        let imported = try AppSettings.importing(data, over: AppSettings(), strategy: .replaceAll)
+        XCTAssertEqual(imported.claudeDirectory, "~/work/.claude")
+        XCTAssertEqual(imported.codexDirectory, "/Volumes/<user>-backup/codex")
+    }
+
+    func testNotesFolderExpandsTildeAndIsOffWhenEmpty() {
+        var settings = AppSettings()
+        XCTAssertNil(settings.notesFolderURL(home: "/Users/alice"))
+
+        settings.notesFolder = " ~/Vault/Agents "
+        XCTAssertEqual(settings.validated().notesFolderURL(home: "/Users/alice")?.path, "/Users/alice/Vault/Agents")
+    }
+
+    func testViewPresetsRoundtripThroughDefaultsAndExport() throws {
+        var settings = AppSettings()
+        settings.viewPresets = [
+            ViewPreset(name: "Failed this week", statuses: [.failed], projectPath: "/repo/api", withinDays: 7, sort: .cost),
+            ViewPreset(name: "Codex", source: .codex, groupByProject: true)
+        ]
+        settings.save(to: defaults)
+        XCTAssertEqual(AppSettings.load(from: defaults), settings)
+
+        let imported = try AppSettings.importing(settings.exportData(), over: AppSettings(), strategy: .replaceAll)
+        XCTAssertEqual(imported.viewPresets, settings.viewPresets)
+    }
+
+    func testValidationDropsUnnamedAndDuplicateViewPresets() {
+        var settings = AppSettings()
+        settings.viewPresets = [ViewPreset(name: "Mine"), ViewPreset(name: " "), ViewPreset(name: "Mine", source: .codex)]
+        XCTAssertEqual(settings.validated().viewPresets, [ViewPreset(name: "Mine")])
+    }
+
+    func testImportKeepLocalChangesPreservesCustomizedValues() throws {
+        var remote = AppSettings()
+        remote.appearance = "light"
+        remote.showSidechains = true
+
+        var local = AppSettings()
+        local.appearance = "dark"
+
+        let merged = try AppSettings.importing(remote.exportData(), over: local, strategy: .keepLocalChanges)
+        XCTAssertEqual(merged.appearance, "dark")
+        XCTAssertTrue(merged.showSidechains)
+    }
+
+    func testImportRejectsUnrelatedJSON() {
+        let data = Data(#"{"hello":"world"}"#.utf8)
+        XCTAssertThrowsError(try AppSettings.importing(data, over: AppSettings(), strategy: .replaceAll))
+    }
+
+    func testImportRejectsUnknownVersion() {
+        let data = Data(#"{"version":99,"settings":{}}"#.utf8)
+        XCTAssertThrowsError(try AppSettings.importing(data, over: AppSettings(), strategy: .replaceAll)) { error in
+            guard case SettingsTransferError.unsupportedVersion(99) = error else {
+                return XCTFail("Unexpected error \(error)")
+            }
+        }
+    }
+}
+
+// MARK: - Settings Watcher Tests
+
+final class SettingsWatcherTests: XCTestCase {
+
+    func testWatcherReportsChangedSettings() {
+        let suiteName = "SettingsWatcherTests.\(UUID().uuidString)"
+        let defaults = UserDefaults(suiteName: suiteName)!
+        defer { defaults.removePersistentDomain(forName: suiteName) }
+
+        let changed = expectation(description: "change reported")
+        var reported: (old: AppSettings, new: AppSettings)?
+        let watcher = SettingsWatcher(defaults: defaults) { old, new in
+            reported = (old, new)
+            changed.fulfill()
+        }
+
+        defaults.set(30.0, forKey: AppSettings.Key.refreshInterval)
+        wait(for: [changed], timeout: 1)
+
+        XCTAssertEqual(reported?.old.refreshInterval, 5)
+        XCTAssertEqual(reported?.new.refreshInterval, 30)
+        XCTAssertEqual(watcher.current.refreshInterval, 30)
+    }
+
+    func testWatcherIgnoresWritesThatDoNotChangeSettings() {
+        let suiteName = "SettingsWatcherTests.\(UUID().uuidString)"
+        let defaults = UserDefaults(suiteName: suiteName)!
+        defer { defaults.removePersistentDomain(forName: suiteName) }
+
+        let changed = expectation(description: "change reported")
+        changed.isInverted = true
+        let watcher = SettingsWatcher(defaults: defaults) { _, _ in changed.fulfill() }
+
+        defaults.set(false, forKey: AppSettings.Key.activeOnly)
+        wait(for: [changed], timeout: 0.3)
+        XCTAssertFalse(watcher.current.activeOnly)
+    }
+}
+
+// MARK: - Feature Flag Tests
+
+final class FeatureFlagsTests: XCTestCase {
+
+    private var defaults: UserDefaults!
+    private var suiteName: String!
+
+    override func setUp() {
+        super.setUp()
+        suiteName = "FeatureFlagsTests.\(UUID().uuidString)"
+        defaults = UserDefaults(suiteName: suiteName)
+    }
+
+    override func tearDown() {
+        defaults.removePersistentDomain(forName: suiteName)
+        defaults = nil
+        super.tearDown()
+    }
+
+    func testFlagsFallBackToBuiltInDefault() {
+        let flags = FeatureFlags(environment: [:], defaults: defaults)
+        for flag in FeatureFlag.allCases {
+            XCTAssertEqual(flags.isEnabled(flag), flag.defaultValue)
+        }
+    }
+
+    func testDefaultsOverrideBuiltInValue() {
+        defaults.set(true, forKey: FeatureFlag.usageInsights.defaultsKey)
+        let flags = FeatureFlags(environment: [:], defaults: defaults)
+        XCTAssertTrue(flags.isEnabled(.usageInsights))
+    }
+
+    func testEnvironmentOverridesDefaults() {
+        defaults.set(true, forKey: FeatureFlag.usageInsights.defaultsKey)
+        let flags = FeatureFlags(environment: ["AGENTS_MONITOR_FEATURE_USAGE_INSIGHTS": "off"], defaults: defaults)
+        XCTAssertFalse(flags.isEnabled(.usageInsights))
+    }
+
+    func testUnrecognizedEnvironmentValueIsIgnored() {
+        defaults.set(true, forKey: FeatureFlag.usageInsights.defaultsKey)
+        let flags = FeatureFlags(environment: ["AGENTS_MONITOR_FEATURE_USAGE_INSIGHTS": "maybe"], defaults: defaults)
+        XCTAssertTrue(flags.isEnabled(.usageInsights))
+    }
+
+    func testKeysAreDerivedFromFlagName() {
+        XCTAssertEqual(FeatureFlag.usageInsights.defaultsKey, "feature.usageInsights")
+        XCTAssertEqual(FeatureFlag.usageInsights.environmentKey, "AGENTS_MONITOR_FEATURE_USAGE_INSIGHTS")
+    }
+}
+
+// MARK: - View Preset Tests
+
+final class ViewPresetTests: XCTestCase {
+
+    private let now = ISO8601DateFormatter().date(from: "2026-03-10T12:00:00Z")!
+
+    private func session(_ name: String, status: SessionStatus, project: String?, daysAgo: Double, cost: Double = 0) -> Session {
+        Session(
+            name: name,
+            status: status,
+            startedAt: now.addingTimeInterval(-daysAgo * 86_400),
+            metrics: SessionMetrics(cost: cost),
+            projectPath: project
+        )
+    }
+
+    func testFiltersByStatusProjectAndAge() {
+        let sessions = [
+            session("match", status: .failed, project: "/repo/api", daysAgo: 2),
+            session("nested", status: .failed, project: "/repo/api/worker", daysAgo: 1),
+            session("sibling", status: .failed, project: "/repo/api-old", daysAgo: 1),
+            session("old", status: .failed, project: "/repo/api", daysAgo: 9),
+            session("ok", status: .completed, project: "/repo/api", daysAgo: 1)
+        ]
+        let preset = ViewPreset(name: "Failed this week", statuses: [.failed], projectPath: "/repo/api/", withinDays: 7)
+
+        XCTAssertEqual(preset.apply(to: sessions, now: now).map(\.name), ["nested", "match"])
+    }
+
+    func testSortsByCostWithinProjectGroups() {
+        let sessions = [
+            session("b-cheap", status: .completed, project: "/b", daysAgo: 1, cost: 1),
+            session("a-cheap", status: .completed, project: "/a", daysAgo: 1, cost: 1),
+            session("b-pricey", status: .completed, project: "/b", daysAgo: 1, cost: 5),
+            session("a-pricey", status: .completed, project: "/a", daysAgo: 1, cost: 3)
+        ]
+        let preset = ViewPreset(name: "By cost", sort: .cost, groupByProject: true)
+
+        XCTAssertEqual(preset.apply(to: sessions, now: now).map(\.name), ["a-pricey", "a-cheap", "b-pricey", "b-cheap"])
+    }
+
+    func testQueryFiltersAndRoundtrips() {
+        let sessions = [
+            Session(name: "big", status: .failed, startedAt: now, metrics: SessionMetrics(totalTokens: 200_000)),
+            Session(name: "small", status: .failed, startedAt: now, metrics: SessionMetrics(totalTokens: 1_000))
+        ]
+        let preset = ViewPreset(name: "Heavy", query: "tokens>100k")
+
+        XCTAssertEqual(preset.apply(to: sessions, now: now).map(\.name), ["big"])
+        XCTAssertEqual(ViewPreset(dictionary: preset.dictionaryRepresentation), preset)
+        XCTAssertTrue(ViewPreset(name: "Broken", query: "tokens>>").apply(to: sessions, now: now).isEmpty)
+    }
+
+    func testUnknownStoredValuesFallBackToDefaults() {
+        let preset = ViewPreset(dictionary: ["name": "Mine", "source": "cursor", "sort": "random", "statuses": ["Failed", "Exploded"]])
+        XCTAssertEqual(preset, ViewPreset(name: "Mine", statuses: [.failed]))
+        XCTAssertNil(ViewPreset(dictionary: ["source": "codex"]))
+    }
+}
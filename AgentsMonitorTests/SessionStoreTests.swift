@@ -74,6 +74,16 @@ final class SessionStoreTests: XCTestCase {
         XCTAssertNil(store.error)
     }
 
+    // MARK: - Popover Visibility Tests
+
+    func testPopoverVisibilityTracked() async throws {
+        XCTAssertFalse(store.isPopoverVisible)
+        store.setPopoverVisible(true)
+        XCTAssertTrue(store.isPopoverVisible)
+        store.setPopoverVisible(false)
+        XCTAssertFalse(store.isPopoverVisible)
+    }
+
     // MARK: - Loading State Tests
 
     func testInitialLoadingCompletes() async throws {
@@ -176,6 +186,68 @@ final class SessionStoreTests: XCTestCase {
         defaults.set(false, forKey: "codexEnabled")
         XCTAssertFalse(SessionStore.boolPreference(forKey: "codexEnabled", defaultValue: true, defaults: defaults))
     }
+
+    // MARK: - Label Tests
+
+    func testSetLabelUpdatesSessionAndPersists() throws {
+        let suiteName = "SessionStoreTests.labels.\(UUID().uuidString)"
+        let defaults = try XCTUnwrap(UserDefaults(suiteName: suiteName))
+        defer { defaults.removePersistentDomain(forName: suiteName) }
+        let target = try XCTUnwrap(store.sessions.first)
+
+        store.setLabel(SessionLabel(color: .purple, emoji: "🧪"), for: target.id, defaults: defaults)
+        XCTAssertEqual(store.sessions.first?.label, SessionLabel(color: .purple, emoji: "🧪"))
+        XCTAssertEqual(SessionLabel.loadAll(from: defaults), [target.id: SessionLabel(color: .purple, emoji: "🧪")])
+
+        store.setLabel(SessionLabel(), for: target.id, defaults: defaults)
+        XCTAssertNil(store.sessions.first?.label)
+        XCTAssertTrue(SessionLabel.loadAll(from: defaults).isEmpty)
+    }
+
+    func testLinkIssueOverridesDetectedKeyAndPersists() throws {
+        let suiteName = "SessionStoreTests.issues.\(UUID().uuidString)"
+        let defaults = try XCTUnwrap(UserDefaults(suiteName: suiteName))
+        defer { defaults.removePersistentDomain(forName: suiteName) }
+        let target = try XCTUnwrap(store.sessions.first)
+
+        store.linkIssue(" eng-142 ", to: target.id, defaults: defaults)
+        XCTAssertEqual(store.sessions.first?.issueKey, "ENG-142")
+        XCTAssertEqual(IssueTracker.loadLinkedKeys(from: defaults), [target.id: "ENG-142"])
+
+        store.linkIssue(nil, to: target.id, defaults: defaults)
+        XCTAssertEqual(store.sessions.first?.issueKey, IssueTracker.detectKey(branch: target.gitBranch, prompt: target.firstPrompt))
+        XCTAssertTrue(IssueTracker.loadLinkedKeys(from: defaults).isEmpty)
+    }
+
+    func testDataFolderIsOfflineOnlyAfterBeingRead() throws {
+        let folder = FileManager.default.temporaryDirectory.appendingPathComponent("offline-\(UUID().uuidString)")
+        try FileManager.default.createDirectory(at: folder, withIntermediateDirectories: true)
+
+        XCTAssertFalse(SessionStore.isOffline(folder, lastRead: folder))
+        try FileManager.default.removeItem(at: folder)
+        XCTAssertTrue(SessionStore.isOffline(folder, lastRead: folder))
+        XCTAssertFalse(SessionStore.isOffline(folder, lastRead: nil))
+        XCTAssertFalse(SessionStore.isOffline(folder, lastRead: folder.deletingLastPathComponent()))
+    }
+
+    func testOfflineCheckRunsOffTheMainActor() async throws {
+        let folder = FileManager.default.temporaryDirectory.appendingPathComponent("offline-\(UUID().uuidString)")
+        try FileManager.default.createDirectory(at: folder, withIntermediateDirectories: true)
+        defer { try? FileManager.default.removeItem(at: folder) }
+
+        let present = await SessionStore.isOffline(folder, lastRead: folder, timeout: 5)
+        XCTAssertFalse(present)
+        let unread = await SessionStore.isOffline(folder.appendingPathComponent("missing"), lastRead: nil, timeout: 5)
+        XCTAssertFalse(unread)
+        let missing = await SessionStore.isOffline(folder.appendingPathComponent("missing"), lastRead: folder.appendingPathComponent("missing"), timeout: 5)
+        XCTAssertTrue(missing)
+    }
+
+    func testNoteRetryDelayDoublesUpToCap() {
+        XCTAssertEqual(SessionStore.noteRetryDelay(afterAttempt: 1), 30)
+        XCTAssertEqual(SessionStore.noteRetryDelay(afterAttempt: 3), 120)
+        XCTAssertEqual(SessionStore.noteRetryDelay(afterAttempt: 10), 900)
+    }
 }
 
 actor UsageServiceSpy: UsageServiceProviding {
@@ -251,6 +323,56 @@ final class SessionStoreUsageRefreshTests: XCTestCase {
     }
 }
 
+// MARK: - Quota Tracking Tests
+
+final class QuotaTrackerTests: XCTestCase {
+
+    private let start = Date(timeIntervalSince1970: 1_800_000_000)
+
+    private func window(_ utilization: Double, resetsIn interval: TimeInterval? = nil) -> AnthropicUsage.UsageWindow {
+        let formatter = ISO8601DateFormatter()
+        return .init(utilization: utilization, resetsAt: interval.map { formatter.string(from: start.addingTimeInterval($0)) })
+    }
+
+    func testProjectsRunOutFromRecentPace() {
+        var tracker = QuotaTracker()
+        _ = tracker.record(window(0.50), provider: .claudeCode, label: "5-hour", at: start)
+        let status = tracker.record(window(0.60, resetsIn: 4 * 3600), provider: .claudeCode, label: "5-hour", at: start.addingTimeInterval(600))
+
+        XCTAssertEqual(status.ratePerHour ?? 0, 0.6, accuracy: 0.0001)
+        // 40% left at 10% per 10 minutes.
+        XCTAssertEqual(status.projectedLimitAt?.timeIntervalSince(start) ?? 0, 600 + 2400, accuracy: 1)
+        XCTAssertTrue(status.willHitLimitBeforeReset)
+    }
+
+    func testNoProjectionWithoutEnoughHistoryOrGrowth() {
+        var tracker = QuotaTracker()
+        _ = tracker.record(window(0.50), provider: .codex, label: "5-hour", at: start)
+        let tooSoon = tracker.record(window(0.55), provider: .codex, label: "5-hour", at: start.addingTimeInterval(60))
+        XCTAssertNil(tooSoon.projectedLimitAt)
+
+        let flat = tracker.record(window(0.50), provider: .codex, label: "7-day", at: start.addingTimeInterval(900))
+        XCTAssertNil(flat.ratePerHour)
+        XCTAssertFalse(flat.willHitLimitBeforeReset)
+    }
+
+    func testResetWindowDropsEarlierSamples() {
+        var tracker = QuotaTracker()
+        _ = tracker.record(window(0.80), provider: .claudeCode, label: "5-hour", at: start)
+        _ = tracker.record(window(0.05), provider: .claudeCode, label: "5-hour", at: start.addingTimeInterval(600))
+        let status = tracker.record(window(0.10), provider: .claudeCode, label: "5-hour", at: start.addingTimeInterval(1200))
+        XCTAssertEqual(status.ratePerHour ?? 0, 0.3, accuracy: 0.0001)
+    }
+
+    func testLimitAfterResetIsNotAWarning() {
+        var tracker = QuotaTracker()
+        _ = tracker.record(window(0.10), provider: .claudeCode, label: "5-hour", at: start)
+        let status = tracker.record(window(0.11, resetsIn: 1800), provider: .claudeCode, label: "5-hour", at: start.addingTimeInterval(600))
+        XCTAssertNotNil(status.projectedLimitAt)
+        XCTAssertFalse(status.willHitLimitBeforeReset)
+    }
+}
+
 // MARK: - Aggregate Stats Tests
 
 @MainActor
@@ -314,11 +436,39 @@ final class SessionStoreAggregateTests: XCTestCase {
         XCTAssertFalse(store.formattedAverageDuration.isEmpty)
     }
 
+    func testQuickStatsCountsStatuses() async throws {
+        let stats = store.quickStats
+        XCTAssertEqual(stats.runningCount, store.sessions.filter { $0.status == .running }.count)
+        XCTAssertEqual(stats.waitingCount, store.sessions.filter { $0.status == .waiting }.count)
+        XCTAssertEqual(stats.failedCount, store.sessions.filter { $0.status == .failed }.count)
+        XCTAssertEqual(stats.needsAttentionCount, stats.waitingCount + stats.failedCount)
+    }
+
+    func testQuickStatsOnlyIncludesTodaysUsage() async throws {
+        let startOfToday = Calendar.current.startOfDay(for: Date())
+        let today = store.sessions.filter { $0.startedAt >= startOfToday }
+        let stats = store.quickStats
+        XCTAssertEqual(stats.todayTokens, today.reduce(0) { $0 + $1.metrics.totalTokens })
+        XCTAssertEqual(stats.todayCost, today.reduce(0.0) { $0 + $1.metrics.cost }, accuracy: 0.0001)
+    }
+
+    func testQuickStatsEmptyAfterClearAll() async throws {
+        store.clearAllSessions()
+        XCTAssertEqual(store.quickStats, QuickStats())
+    }
+
     func testAggregateCostUpdatesAfterClearAll() async throws {
         XCTAssertGreaterThan(store.aggregateCost, 0)
         store.clearAllSessions()
         XCTAssertEqual(store.aggregateCost, 0)
     }
+
+    func testMemoryReportCountsSessions() async throws {
+        let report = await store.memoryReport()
+        XCTAssertEqual(report.sessionCount, store.sessions.count)
+        XCTAssertNotNil(report.residentBytes)
+        XCTAssertGreaterThan(report.residentBytes ?? 0, 0)
+    }
 }
 
 // MARK: - Clear All Tests
@@ -547,6 +697,50 @@ final class MessageModelTests: XCTestCase {
         XCTAssertEqual(message1, message2)
         XCTAssertNotEqual(message1, message3)
     }
+
+    func testExtractsFencedCodeBlocks() {
+        let markdown = """
+        Try this:
+
+        ```swift Sources/App.swift
+        let x = 1
+        ```
+
+        Then run:
+
+        ~~~
+        make test
+        ~~~
+        """
+        let blocks = CodeBlock.extract(from: markdown)
+
+        XCTAssertEqual(blocks, [
+            CodeBlock(language: "swift", content: "let x = 1", filePath: "Sources/App.swift"),
+            CodeBlock(content: "make test")
+        ])
+    }
+
+    func testCodeBlockTargetFromColonOrLeadingComment() {
+        let colon = CodeBlock.extract(from: "```python:tools/run.py\nprint(1)\n```")
+        XCTAssertEqual(colon.first?.language, "python")
+        XCTAssertEqual(colon.first?.filePath, "tools/run.py")
+
+        let comment = CodeBlock.extract(from: "```ts\n// src/index.ts\nexport {}\n```")
+        XCTAssertEqual(comment.first?.filePath, "src/index.ts")
+
+        let heading = CodeBlock.extract(from: "```sh\n# setup.sh\n./configure\n```")
+        XCTAssertNil(heading.first?.filePath)
+    }
+
+    func testCodeBlockNeedsMatchingFence() {
+        let blocks = CodeBlock.extract(from: "````md\n```js\nnested\n```\n````")
+        XCTAssertEqual(blocks.count, 1)
+        XCTAssertEqual(blocks.first?.content, "```js\nnested\n```")
+
+        let unterminated = CodeBlock.extract(from: "```go\nfunc main() {")
+        XCTAssertEqual(unterminated.first?.content, "func main() {")
+        XCTAssertTrue(CodeBlock.extract(from: "Inline ``` fences ``` are not blocks").isEmpty)
+    }
 }
 
 // MARK: - SessionMetrics Tests
@@ -577,6 +771,8 @@ final class SessionMetricsTests: XCTestCase {
         XCTAssertEqual(metrics.apiCalls, 0)
         XCTAssertEqual(metrics.cost, 0.0)
         XCTAssertEqual(metrics.modelName, "")
+        XCTAssertFalse(metrics.isEstimated)
+        XCTAssertEqual(metrics.modelIds, [])
     }
 
     func testCostAndModelName() {
@@ -641,6 +837,19 @@ final class SessionMetricsTests: XCTestCase {
         XCTAssertEqual(metrics.formattedContextWindow, "50.0K / 200K")
     }
 
+    func testMeasuredContextTakesPrecedenceOverTotals() {
+        let metrics = SessionMetrics(totalTokens: 900_000, contextWindowMax: 200_000, contextTokens: 170_000)
+        XCTAssertEqual(metrics.contextWindowUsage, 0.85, accuracy: 0.001)
+        XCTAssertEqual(metrics.formattedContextWindow, "170.0K / 200K")
+        XCTAssertEqual(metrics.formattedRemaining, "30.0K")
+        XCTAssertTrue(metrics.isNearContextLimit)
+    }
+
+    func testLifetimeTotalsAloneNeverWarn() {
+        let metrics = SessionMetrics(totalTokens: 900_000, contextWindowMax: 200_000)
+        XCTAssertFalse(metrics.isNearContextLimit)
+    }
+
     func testSessionMetricsDecodesWithoutContextWindowMax() throws {
         let json = """
         {"totalTokens":100,"inputTokens":50,"outputTokens":50,"toolCallCount":1,"errorCount":0,"apiCalls":1}
@@ -741,6 +950,41 @@ final class TokenCostCalculatorTests: XCTestCase {
         XCTAssertEqual(summary?.cost ?? 0, 3.0, accuracy: 0.001)
     }
 
+    func testRecordsEveryModelMostUsedFirst() {
+        let tempDir = FileManager.default.temporaryDirectory
+        let testFile = tempDir.appendingPathComponent("test_models_\(UUID().uuidString).jsonl")
+
+        let jsonl = [
+            #"{"type":"assistant","message":{"model":"claude-haiku-4-5","usage":{"input_tokens":10,"output_tokens":5}}}"#,
+            #"{"type":"assistant","message":{"model":"claude-opus-4-1","usage":{"input_tokens":10,"output_tokens":5}}}"#,
+            #"{"type":"assistant","message":{"model":"claude-opus-4-1","usage":{"input_tokens":10,"output_tokens":5}}}"#,
+            #"{"type":"assistant","message":{"model":"<synthetic>","usage":{"input_tokens":0,"output_tokens":0}}}"#
+        ].joined(separator: "\n")
+
+        try? jsonl.data(using: .utf8)?.write(to: testFile)
+        defer { try? FileManager.default.removeItem(at: testFile) }
+
+        let summary = TokenCostCalculator.calculate(jsonlPath: testFile.path)
+        XCTAssertEqual(summary?.modelIds, ["claude-opus-4-1", "claude-haiku-4-5"])
+        XCTAssertEqual(summary?.modelName, "Opus 4")
+    }
+
+    func testContextComesFromTheLastMainlineCall() {
+        let tempDir = FileManager.default.temporaryDirectory
+        let testFile = tempDir.appendingPathComponent("test_context_\(UUID().uuidString).jsonl")
+
+        let jsonl = [
+            #"{"type":"assistant","message":{"model":"claude-sonnet-4-5","usage":{"input_tokens":10,"output_tokens":5,"cache_read_input_tokens":1000}}}"#,
+            #"{"type":"assistant","message":{"model":"claude-sonnet-4-5","usage":{"input_tokens":20,"output_tokens":30,"cache_creation_input_tokens":500,"cache_read_input_tokens":4000}}}"#,
+            #"{"type":"assistant","isSidechain":true,"message":{"model":"claude-sonnet-4-5","usage":{"input_tokens":3,"output_tokens":3}}}"#
+        ].joined(separator: "\n")
+
+        try? jsonl.data(using: .utf8)?.write(to: testFile)
+        defer { try? FileManager.default.removeItem(at: testFile) }
+
+        XCTAssertEqual(TokenCostCalculator.calculate(jsonlPath: testFile.path)?.contextTokens, 4550)
+    }
+
     func testNonExistentFileReturnsNil() {
         let summary = TokenCostCalculator.calculate(jsonlPath: "/nonexistent/path.jsonl")
         XCTAssertNil(summary)
@@ -907,7 +1151,8 @@ final class ClaudeSessionEntryTests: XCTestCase {
         let data = makeEntryJSON(
             sessionId: "550e8400-e29b-41d4-a716-446655440000",
             firstPrompt: nil,
-            summary: nil
+            summary: nil,
+            projectPath: nil
         )
         let entry = try JSONDecoder().decode(ClaudeSessionEntry.self, from: data)
         XCTAssertEqual(entry.sessionName, "Session 550e8400")
@@ -923,12 +1168,27 @@ final class ClaudeSessionEntryTests: XCTestCase {
         let data = makeEntryJSON(
             sessionId: "abcdef01-0000-0000-0000-000000000000",
             firstPrompt: "",
-            summary: ""
+            summary: "",
+            projectPath: nil
         )
         let entry = try JSONDecoder().decode(ClaudeSessionEntry.self, from: data)
         XCTAssertEqual(entry.sessionName, "Session abcdef01")
     }
 
+    func testSessionNameUsesFirstPromptLineOnly() throws {
+        let data = makeEntryJSON(firstPrompt: "\n  Fix   the login bug\nStack trace follows", summary: nil)
+        let entry = try JSONDecoder().decode(ClaudeSessionEntry.self, from: data)
+        XCTAssertEqual(entry.sessionName, "Fix the login bug")
+    }
+
+    func testSessionNameFallsBackToFeatureBranchThenFolder() throws {
+        let branch = makeEntryJSON(firstPrompt: "No prompt", summary: nil, gitBranch: "feature/oauth")
+        XCTAssertEqual(try JSONDecoder().decode(ClaudeSessionEntry.self, from: branch).sessionName, "feature/oauth")
+
+        let folder = makeEntryJSON(firstPrompt: "<command-name>/clear</command-name>", summary: nil, gitBranch: "main")
+        XCTAssertEqual(try JSONDecoder().decode(ClaudeSessionEntry.self, from: folder).sessionName, "project")
+    }
+
     // MARK: - Date parsing
 
     func testStartDateParsingWithFractionalSeconds() throws {
@@ -1103,3 +1363,617 @@ final class AnthropicUsageParsingTests: XCTestCase {
         XCTAssertEqual(AnthropicUsageService.normalizedUtilization(0.42), 0.42, accuracy: 0.0001)
     }
 }
+
+// MARK: - Single Instance Tests
+
+final class SingleInstanceGuardTests: XCTestCase {
+
+    func testForwardedArgumentsDropsExecutablePath() {
+        let forwarded = SingleInstanceGuard.forwardedArguments(["/Applications/AgentsMonitor.app/Contents/MacOS/AgentsMonitor", "--status-item"])
+        XCTAssertEqual(forwarded, ["--status-item"])
+    }
+
+    func testForwardedArgumentsEmptyWhenNoArguments() {
+        XCTAssertTrue(SingleInstanceGuard.forwardedArguments([]).isEmpty)
+    }
+
+    func testRequestedSessionIdFromLinkOrFlag() {
+        let id = UUID()
+        XCTAssertEqual(SingleInstanceGuard.requestedSessionId(in: ["agentsmonitor://session/\(id.uuidString)"]), id)
+        XCTAssertEqual(SingleInstanceGuard.requestedSessionId(in: ["--status-item", "--session", id.uuidString]), id)
+        XCTAssertNil(SingleInstanceGuard.requestedSessionId(in: ["--session"]))
+        XCTAssertNil(SingleInstanceGuard.requestedSessionId(in: ["https://example.com/session/\(id.uuidString)"]))
+    }
+}
+
+// MARK: - App Lock Tests
+
+final class AppLockTests: XCTestCase {
+
+    func testStartsLockedOnlyWhenEnabled() {
+        XCTAssertTrue(AppLock(isEnabled: { true }).isLocked)
+        XCTAssertFalse(AppLock(isEnabled: { false }).isLocked)
+    }
+
+    func testLocksAfterIdleInterval() {
+        var enabled = false
+        let lock = AppLock(isEnabled: { enabled })
+        enabled = true
+        let closedAt = Date(timeIntervalSince1970: 1_700_000_000)
+        lock.noteActivity(at: closedAt)
+
+        lock.lockIfIdle(now: closedAt.addingTimeInterval(60))
+        XCTAssertFalse(lock.isLocked)
+
+        lock.lockIfIdle(now: closedAt.addingTimeInterval(AppLock.idleInterval + 1))
+        XCTAssertTrue(lock.isLocked)
+    }
+
+    func testDisablingSettingUnlocksOnNextOpen() {
+        var enabled = true
+        let lock = AppLock(isEnabled: { enabled })
+        XCTAssertTrue(lock.isLocked)
+
+        enabled = false
+        lock.lockIfIdle()
+        XCTAssertFalse(lock.isLocked)
+    }
+
+    func testLockIsNoOpWhenDisabled() {
+        let lock = AppLock(isEnabled: { false })
+        lock.lock()
+        XCTAssertFalse(lock.isLocked)
+    }
+}
+
+// MARK: - Session Service Cache Tests
+
+final class ClaudeSessionServiceCacheTests: XCTestCase {
+
+    private var claudeDir: URL!
+    private var transcript: URL!
+
+    override func setUp() {
+        super.setUp()
+        claudeDir = FileManager.default.temporaryDirectory.appendingPathComponent("claude_\(UUID().uuidString)")
+        let projectDir = claudeDir.appendingPathComponent("projects/-tmp-app")
+        try! FileManager.default.createDirectory(at: projectDir, withIntermediateDirectories: true)
+        transcript = projectDir.appendingPathComponent("\(UUID().uuidString).jsonl")
+    }
+
+    override func tearDown() {
+        try? FileManager.default.removeItem(at: claudeDir)
+        super.tearDown()
+    }
+
+    private func writeTranscript(prompt: String, modifiedAt date: Date) throws {
+        let line = #"{"type":"user","timestamp":"2026-01-01T10:00:00.000Z","message":{"content":"\#(prompt)"}}"#
+        try line.write(to: transcript, atomically: true, encoding: .utf8)
+        try FileManager.default.setAttributes([.modificationDate: date], ofItemAtPath: transcript.path)
+    }
+
+    func testUnchangedTranscriptIsNotReparsed() async throws {
+        let service = ClaudeSessionService(claudeDir: claudeDir)
+        let modified = Date(timeIntervalSince1970: 1_700_000_000)
+
+        try writeTranscript(prompt: "first", modifiedAt: modified)
+        let initial = await service.discoverSessions(showAll: true, showSidechains: false)
+        XCTAssertEqual(initial.first?.firstPrompt, "first")
+
+        try writeTranscript(prompt: "second", modifiedAt: modified)
+        let cached = await service.discoverSessions(showAll: true, showSidechains: false)
+        XCTAssertEqual(cached.first?.firstPrompt, "first")
+
+        try writeTranscript(prompt: "second", modifiedAt: modified.addingTimeInterval(60))
+        let refreshed = await service.discoverSessions(showAll: true, showSidechains: false)
+        XCTAssertEqual(refreshed.first?.firstPrompt, "second")
+    }
+}
+
+// MARK: - Diagnostics Tests
+
+final class StartupDiagnosticsTests: XCTestCase {
+
+    func testFormatUsesMillisecondsBelowOneSecond() {
+        XCTAssertEqual(StartupDiagnostics.format(0.25), "250 ms")
+        XCTAssertEqual(StartupDiagnostics.format(3.04), "3.0 s")
+    }
+
+    func testSlowOnlyOnceFirstSessionsExceedThreshold() {
+        var diagnostics = StartupDiagnostics()
+        XCTAssertFalse(diagnostics.isSlow)
+        diagnostics.timeToFirstSessions = 0.5
+        XCTAssertFalse(diagnostics.isSlow)
+        diagnostics.timeToFirstSessions = StartupDiagnostics.slowThreshold
+        XCTAssertTrue(diagnostics.isSlow)
+    }
+}
+
+// MARK: - Environment Probe Tests
+
+final class EnvironmentProbeTests: XCTestCase {
+
+    private var root: URL!
+
+    override func setUpWithError() throws {
+        root = URL(fileURLWithPath: NSTemporaryDirectory()).appendingPathComponent("EnvironmentProbeTests-\(UUID().uuidString)")
+        try FileManager.default.createDirectory(at: root, withIntermediateDirectories: true)
+    }
+
+    override func tearDownWithError() throws {
+        try? FileManager.default.removeItem(at: root)
+    }
+
+    private func settings() -> AppSettings {
+        var settings = AppSettings()
+        settings.claudeDirectory = root.appendingPathComponent("claude").path
+        settings.codexDirectory = root.appendingPathComponent("codex").path
+        return settings
+    }
+
+    private func makeExecutable(_ name: String) throws {
+        let bin = root.appendingPathComponent("bin")
+        try FileManager.default.createDirectory(at: bin, withIntermediateDirectories: true)
+        let path = bin.appendingPathComponent(name).path
+        FileManager.default.createFile(atPath: path, contents: Data("#!/bin/sh\n".utf8), attributes: [.posixPermissions: 0o755])
+    }
+
+    func testFreshMacSuggestsInstallingBothAgents() {
+        let probe = EnvironmentProbe.run(settings: settings(), home: root.path, searchPaths: [root.appendingPathComponent("bin").path])
+
+        XCTAssertEqual(probe.agents.map(\.isInstalled), [false, false])
+        XCTAssertTrue(probe.nextSteps.contains("Install Claude Code: npm install -g @anthropic-ai/claude-code"))
+        XCTAssertTrue(probe.nextSteps.contains("Install Codex: npm install -g @openai/codex"))
+    }
+
+    func testInstalledAgentsGetSignInOrFirstRunSteps() throws {
+        try makeExecutable("claude")
+        try makeExecutable("codex")
+        let probe = EnvironmentProbe.run(settings: settings(), home: root.path, searchPaths: [root.appendingPathComponent("bin").path])
+
+        XCTAssertNil(probe.agents[0].isAuthenticated)
+        XCTAssertEqual(probe.agents[1].isAuthenticated, false)
+        XCTAssertEqual(Array(probe.nextSteps.prefix(2)), [
+            "Run claude in a project folder to start a session",
+            "Sign in to Codex: run codex login"
+        ])
+    }
+
+    func testDisabledAgentWithSessionsIsPointedAtSettings() throws {
+        let sessions = root.appendingPathComponent("codex/sessions/2026")
+        try FileManager.default.createDirectory(at: sessions, withIntermediateDirectories: true)
+        var settings = settings()
+        settings.codexEnabled = false
+        settings.claudeCodeEnabled = false
+
+        let probe = EnvironmentProbe.run(settings: settings, home: root.path, searchPaths: [])
+        XCTAssertTrue(probe.agents[1].hasSessions)
+        XCTAssertEqual(probe.nextSteps.first, "Turn on Codex in Settings to see its sessions")
+    }
+}
+
+// MARK: - Health Report Tests
+
+final class HealthReportTests: XCTestCase {
+
+    func testFlagsMissingDataLowDiskAndOrphanedCacheEntries() throws {
+        let root = URL(fileURLWithPath: NSTemporaryDirectory()).appendingPathComponent("HealthReportTests-\(UUID().uuidString)")
+        try FileManager.default.createDirectory(at: root, withIntermediateDirectories: true)
+        defer { try? FileManager.default.removeItem(at: root) }
+        let transcript = root.appendingPathComponent("live.jsonl")
+        try Data("{}".utf8).write(to: transcript)
+
+        let report = HealthReport.run(
+            dataDirectories: [(.claudeCode, root), (.codex, root.appendingPathComponent("missing"))],
+            cacheDirectory: root,
+            cachedPaths: [transcript.path, root.appendingPathComponent("deleted.jsonl").path],
+            runningSessionPaths: [transcript.path],
+            availableCapacity: 200_000_000
+        )
+
+        XCTAssertFalse(report.isHealthy)
+        XCTAssertEqual(report.checks.filter { !$0.isHealthy }.map(\.name), ["Codex data", "Free disk space", "Caches"])
+        XCTAssertEqual(report.orphanedCacheEntries, 1)
+        XCTAssertTrue(report.canRepair)
+    }
+
+    func testHealthyWhenEverythingMatches() throws {
+        let root = URL(fileURLWithPath: NSTemporaryDirectory())
+        let report = HealthReport.run(
+            dataDirectories: [(.claudeCode, root)],
+            cacheDirectory: root,
+            cachedPaths: [],
+            runningSessionPaths: [],
+            availableCapacity: HealthReport.lowDiskSpace
+        )
+
+        XCTAssertTrue(report.isHealthy)
+        XCTAssertFalse(report.canRepair)
+    }
+}
+
+// MARK: - Automation Tests
+
+final class SessionFilterTests: XCTestCase {
+
+    func testWaitingIncludesPermissionPrompts() {
+        var prompting = Session(name: "Prompting", status: .running)
+        prompting.pendingPermission = PermissionRequest(toolUseId: "toolu_1", toolName: "Bash", input: "rm -rf build", requestedAt: nil)
+        let sessions = [
+            Session(name: "Waiting", status: .waiting),
+            prompting,
+            Session(name: "Running", status: .running),
+            Session(name: "Failed", status: .failed)
+        ]
+
+        XCTAssertEqual(sessions.filter(SessionFilter.waiting.includes).map(\.name), ["Waiting", "Prompting"])
+        XCTAssertEqual(sessions.filter(SessionFilter.active.includes).map(\.name), ["Waiting", "Prompting", "Running"])
+        XCTAssertEqual(sessions.filter(SessionFilter.failed.includes).map(\.name), ["Failed"])
+        XCTAssertEqual(SessionEntity(session: prompting).waitingOn, "Bash")
+    }
+}
+
+// MARK: - Issue Tracker Tests
+
+final class IssueTrackerTests: XCTestCase {
+
+    func testDetectsKeyInBranchBeforePrompt() {
+        XCTAssertEqual(IssueTracker.detectKey(branch: "eng-142-fix-login", prompt: "Fix OPS-9"), "ENG-142")
+        XCTAssertEqual(IssueTracker.detectKey(branch: nil, prompt: "Please look at OPS-9 today"), "OPS-9")
+        XCTAssertNil(IssueTracker.detectKey(branch: "main", prompt: "Read it as utf-8 and hash with SHA-256"))
+        XCTAssertNil(IssueTracker.detectKey(branch: "main", prompt: "List the top-10 slow tests on node-18 and x86-64"))
+        XCTAssertTrue(IssueTracker.isValidKey("ENG-142"))
+        XCTAssertFalse(IssueTracker.isValidKey("ENG-142 and more"))
+    }
+
+    func testLinearRequestUsesGraphQLMutation() throws {
+        let request = try IssueTracker.linear.commentRequest(body: "Done", issueKey: "ENG-142", token: "lin_api_x", settings: AppSettings())
+
+        XCTAssertEqual(request.url?.absoluteString, "https://api.linear.app/graphql")
+        XCTAssertEqual(request.value(forHTTPHeaderField: "Authorization"), "lin_api_x")
+        let json = try XCTUnwrap(JSONSerialization.jsonObject(with: XCTUnwrap(request.httpBody)) as? [String: Any])
+        let input = (json["variables"] as? [String: Any])?["input"] as? [String: String]
+        XCTAssertEqual(input, ["issueId": "ENG-142", "body": "Done"])
+    }
+
+    func testJiraRequestUsesSiteAndBasicAuth() throws {
+        var settings = AppSettings()
+        settings.jiraBaseURL = "https://acme.atlassian.net/"
+        settings.jiraEmail = "dev@acme.com"
+        let request = try IssueTracker.jira.commentRequest(body: "Done", issueKey: "OPS-9", token: "secret", settings: settings)
+
+        XCTAssertEqual(request.url?.absoluteString, "https://acme.atlassian.net/rest/api/2/issue/OPS-9/comment")
+        XCTAssertEqual(request.value(forHTTPHeaderField: "Authorization"), "Basic " + Data("dev@acme.com:secret".utf8).base64EncodedString())
+
+        settings.jiraBaseURL = "acme.atlassian.net"
+        XCTAssertThrowsError(try IssueTracker.jira.commentRequest(body: "Done", issueKey: "OPS-9", token: "secret", settings: settings))
+    }
+
+    func testReportListsOutcomeAndFileChanges() {
+        let transcript = TranscriptReader.parse([
+            #"{"type":"assistant","timestamp":"2026-01-01T10:00:05.000Z","message":{"content":[{"type":"tool_use","id":"toolu_1","name":"Edit","input":{"file_path":"/tmp/api/Login.swift","old_string":"a < b","new_string":"a > b"}}]}}"#,
+            #"{"type":"user","timestamp":"2026-01-01T10:00:20.000Z","message":{"content":[{"type":"tool_result","tool_use_id":"toolu_1","content":"ok"}]}}"#
+        ].joined(separator: "\n"), agentType: .claudeCode)
+        var session = Session(name: "Fix login", status: .completed, startedAt: Date(timeIntervalSince1970: 0), endedAt: Date(timeIntervalSince1970: 300), projectPath: "/tmp/api")
+        session.metrics.cost = 1.5
+
+        let report = SessionReport.markdown(for: session, transcript: transcript)
+        XCTAssertTrue(report.contains("- Status: Completed"))
+        XCTAssertTrue(report.contains("- Cost: $1.50"))
+        XCTAssertTrue(report.contains("**Files changed (1, +1 −1)**"))
+        XCTAssertTrue(report.contains("- `Login.swift` +1 −1"))
+    }
+
+    func testReportListsTestRunsWithLastOutcome() {
+        let transcript = TranscriptReader.parse([
+            #"{"type":"assistant","timestamp":"2026-01-01T10:00:05.000Z","message":{"content":[{"type":"tool_use","id":"toolu_1","name":"Bash","input":{"command":"swift test --filter Login"}}]}}"#,
+            #"{"type":"user","timestamp":"2026-01-01T10:00:20.000Z","message":{"content":[{"type":"tool_result","tool_use_id":"toolu_1","content":"1 failure","is_error":true}]}}"#,
+            #"{"type":"assistant","timestamp":"2026-01-01T10:00:30.000Z","message":{"content":[{"type":"tool_use","id":"toolu_2","name":"Bash","input":{"command":"git status"}}]}}"#,
+            #"{"type":"user","timestamp":"2026-01-01T10:00:31.000Z","message":{"content":[{"type":"tool_result","tool_use_id":"toolu_2","content":"clean"}]}}"#,
+            #"{"type":"assistant","timestamp":"2026-01-01T10:00:40.000Z","message":{"content":[{"type":"tool_use","id":"toolu_3","name":"Bash","input":{"command":"swift test --filter Login"}}]}}"#,
+            #"{"type":"user","timestamp":"2026-01-01T10:00:50.000Z","message":{"content":[{"type":"tool_result","tool_use_id":"toolu_3","content":"ok"}]}}"#
+        ].joined(separator: "\n"), agentType: .claudeCode)
+
+        let runs = SessionReport.testRuns(in: transcript)
+        XCTAssertEqual(runs.map(\.command), ["swift test --filter Login"])
+        XCTAssertEqual(runs.map(\.passed), [true])
+        let report = SessionReport.markdown(for: Session(name: "Fix login", status: .completed), transcript: transcript)
+        XCTAssertTrue(report.contains("- ✅ `swift test --filter Login`"))
+    }
+}
+
+// MARK: - GitHub Pull Request Tests
+
+final class GitHubPullRequestsTests: XCTestCase {
+
+    private func message(_ text: String) -> Message {
+        Message(role: .assistant, content: text, timestamp: Date())
+    }
+
+    func testPullRequestLinkParsesGitHubURLs() {
+        let link = PullRequestLink(url: URL(string: "https://github.com/acme/api/pull/42/files")!)
+        XCTAssertEqual(link, PullRequestLink(owner: "acme", repo: "api", number: 42))
+        XCTAssertNil(PullRequestLink(url: URL(string: "https://github.com/acme/api/issues/42")!))
+        XCTAssertNil(PullRequestLink(url: URL(string: "https://gitlab.com/acme/api/pull/42")!))
+    }
+
+    func testRepositoryParsesRemoteURLForms() {
+        let expected = GitHubRepository(owner: "acme", repo: "api")
+        XCTAssertEqual(GitHubRepository(remoteURL: "git@github.com:acme/api.git"), expected)
+        XCTAssertEqual(GitHubRepository(remoteURL: "ssh://git@github.com/acme/api.git"), expected)
+        XCTAssertEqual(GitHubRepository(remoteURL: "https://github.com/acme/api"), expected)
+        XCTAssertNil(GitHubRepository(remoteURL: "git@gitlab.com:acme/api.git"))
+        XCTAssertEqual(
+            GitHubRepository.originURL(inConfig: "[remote \"upstream\"]\n\turl = git@github.com:other/api.git\n[remote \"origin\"]\n\turl = git@github.com:acme/api.git\n"),
+            "git@github.com:acme/api.git"
+        )
+    }
+
+    func testReadsOriginFromProjectGitConfig() throws {
+        let project = FileManager.default.temporaryDirectory.appendingPathComponent("origin-\(UUID().uuidString)")
+        defer { try? FileManager.default.removeItem(at: project) }
+        try FileManager.default.createDirectory(at: project.appendingPathComponent(".git"), withIntermediateDirectories: true)
+        try "[remote \"origin\"]\n\turl = https://github.com/acme/api.git\n".write(to: project.appendingPathComponent(".git/config"), atomically: true, encoding: .utf8)
+
+        XCTAssertEqual(GitHubRepository.origin(ofProject: project.path), GitHubRepository(owner: "acme", repo: "api"))
+    }
+
+    func testLinkedPullRequestIgnoresOtherRepositories() {
+        let transcript = Transcript(messages: [
+            message("Upstream fixed this in https://github.com/apple/swift/pull/70000"),
+            message("Opened https://github.com/acme/api/pull/7"),
+            message("See also https://github.com/someone/fork/pull/3")
+        ])
+
+        XCTAssertEqual(GitHubPullRequests.linkedPullRequest(in: transcript, origin: GitHubRepository(owner: "acme", repo: "api"))?.number, 7)
+        XCTAssertNil(GitHubPullRequests.linkedPullRequest(in: transcript, origin: nil))
+    }
+
+    func testLinkedPullRequestPrefersGhPrCreateOutput() {
+        let transcript = TranscriptReader.parse([
+            #"{"type":"assistant","timestamp":"2026-01-01T10:00:05.000Z","message":{"content":[{"type":"tool_use","id":"toolu_1","name":"Bash","input":{"command":"gh pr create --fill"}}]}}"#,
+            #"{"type":"user","timestamp":"2026-01-01T10:00:20.000Z","message":{"content":[{"type":"tool_result","tool_use_id":"toolu_1","content":"https://github.com/acme/api-fork/pull/12"}]}}"#,
+            #"{"type":"assistant","timestamp":"2026-01-01T10:00:30.000Z","message":{"content":[{"type":"text","text":"Compare https://github.com/acme/api/pull/9"}]}}"#
+        ].joined(separator: "\n"), agentType: .claudeCode)
+
+        let pullRequest = GitHubPullRequests.linkedPullRequest(in: transcript, origin: GitHubRepository(owner: "acme", repo: "api"))
+        XCTAssertEqual(pullRequest, PullRequestLink(owner: "acme", repo: "api-fork", number: 12))
+    }
+
+    func testPullRequestCommentRequestUsesIssuesEndpoint() throws {
+        let request = try GitHubPullRequests.commentRequest(body: "Done", pullRequest: PullRequestLink(owner: "acme", repo: "api", number: 42), token: "ghp_x")
+
+        XCTAssertEqual(request.httpMethod, "POST")
+        XCTAssertEqual(request.url?.absoluteString, "https://api.github.com/repos/acme/api/issues/42/comments")
+        XCTAssertEqual(request.value(forHTTPHeaderField: "Authorization"), "Bearer ghp_x")
+        XCTAssertEqual(request.value(forHTTPHeaderField: "Accept"), "application/vnd.github+json")
+        let json = try XCTUnwrap(JSONSerialization.jsonObject(with: XCTUnwrap(request.httpBody)) as? [String: String])
+        XCTAssertEqual(json, ["body": "Done"])
+    }
+}
+
+// MARK: - Session Insights Tests
+
+final class SessionInsightsTests: XCTestCase {
+    private let calendar: Calendar = {
+        var calendar = Calendar(identifier: .gregorian)
+        calendar.timeZone = TimeZone(identifier: "UTC")!
+        return calendar
+    }()
+
+    private func date(_ string: String) -> Date {
+        ISO8601DateFormatter().date(from: string)!
+    }
+
+    func testHeatmapSplitsSessionsAcrossHourSlots() {
+        // 2026-01-05 is a Monday.
+        let session = DateInterval(start: date("2026-01-05T09:30:00Z"), end: date("2026-01-05T11:15:00Z"))
+        let heatmap = ActivityHeatmap(intervals: [session], calendar: calendar)
+
+        XCTAssertEqual(heatmap.seconds[1][9], 1800)
+        XCTAssertEqual(heatmap.seconds[1][10], 3600)
+        XCTAssertEqual(heatmap.seconds[1][11], 900)
+        XCTAssertEqual(heatmap.busiestSlot?.weekday, 1)
+        XCTAssertEqual(heatmap.busiestSlot?.hour, 10)
+    }
+
+    func testHeatmapCountsOnlyTimeInsideRange() {
+        let session = DateInterval(start: date("2026-01-05T09:30:00Z"), end: date("2026-01-05T11:15:00Z"))
+        let range = DateInterval(start: date("2026-01-05T11:00:00Z"), end: date("2026-01-12T00:00:00Z"))
+        let heatmap = ActivityHeatmap(intervals: [session], range: range, calendar: calendar)
+
+        XCTAssertEqual(heatmap.seconds[1][9], 0)
+        XCTAssertEqual(heatmap.seconds[1][11], 900)
+        XCTAssertTrue(ActivityHeatmap(intervals: [session], range: DateInterval(start: date("2026-02-01T00:00:00Z"), duration: 60), calendar: calendar).isEmpty)
+    }
+
+    func testHotspotsRankFilesByEditsWithinProject() {
+        func edit(_ id: String, _ path: String, failed: Bool = false) -> String {
+            [
+                #"{"type":"assistant","timestamp":"2026-01-01T10:00:00.000Z","message":{"content":[{"type":"tool_use","id":"\#(id)","name":"Edit","input":{"file_path":"\#(path)","old_string":"a","new_string":"b"}}]}}"#,
+                #"{"type":"user","timestamp":"2026-01-01T10:00:01.000Z","message":{"content":[{"type":"tool_result","tool_use_id":"\#(id)","content":"done","is_error":\#(failed)}]}}"#
+            ].joined(separator: "\n")
+        }
+        let first = TranscriptReader.parse([edit("t1", "/tmp/api/Login.swift"), edit("t2", "/tmp/api/Login.swift"), edit("t3", "/tmp/api/README.md")].joined(separator: "\n"), agentType: .claudeCode)
+        let second = TranscriptReader.parse(edit("t4", "/tmp/api/Login.swift", failed: true), agentType: .claudeCode)
+        let other = TranscriptReader.parse(edit("t5", "/tmp/web/App.tsx"), agentType: .claudeCode)
+        let batch: [(session: Session, transcript: Transcript)] = [
+            (Session(name: "a", status: .completed, projectPath: "/tmp/api"), first),
+            (Session(name: "b", status: .failed, projectPath: "/tmp/api"), second),
+            (Session(name: "c", status: .completed, projectPath: "/tmp/web"), other)
+        ]
+
+        let hotspots = FileHotspot.rank(batch, projectPath: "/tmp/api")
+        XCTAssertEqual(hotspots.map(\.path), ["Login.swift", "README.md"])
+        XCTAssertEqual(hotspots[0].edits, 3)
+        XCTAssertEqual(hotspots[0].failedEdits, 1)
+        XCTAssertEqual(hotspots[0].sessions, 2)
+        XCTAssertEqual(hotspots[0].failedSessions, 1)
+        XCTAssertEqual(FileHotspot.rank(batch).count, 3)
+    }
+
+    func testFailuresClusterBySignatureAcrossSessions() {
+        func failedBash(_ id: String, _ output: String) -> String {
+            [
+                #"{"type":"assistant","timestamp":"2026-01-01T10:00:00.000Z","message":{"content":[{"type":"tool_use","id":"\#(id)","name":"Bash","input":{"command":"make"}}]}}"#,
+                #"{"type":"user","timestamp":"2026-01-01T10:00:01.000Z","message":{"content":[{"type":"tool_result","tool_use_id":"\#(id)","content":"\#(output)","is_error":true}]}}"#
+            ].joined(separator: "\n")
+        }
+        let apiError = #"{"type":"assistant","timestamp":"2026-01-01T10:01:00.000Z","message":{"content":[{"type":"text","text":"API Error: 401 OAuth token has expired"}]}}"#
+        let first = TranscriptReader.parse([
+            failedBash("t1", "error: cannot open /tmp/a/x.swift at line 12"),
+            failedBash("t2", "error: cannot open /tmp/a/z.swift at line 7"),
+            failedBash("t3", "The user doesn't want to proceed with this tool use."),
+            apiError
+        ].joined(separator: "\n"), agentType: .claudeCode)
+        let second = TranscriptReader.parse([failedBash("t4", "error: cannot open /tmp/b/y.swift at line 40"), apiError].joined(separator: "\n"), agentType: .claudeCode)
+
+        let clusters = FailureCluster.group([(Session(name: "a"), first), (Session(name: "b"), second)])
+        XCTAssertEqual(clusters.map(\.signature), ["error: cannot open <path> at line <n>", "api error: 401 oauth token has expired"])
+        XCTAssertEqual(clusters[0].occurrences, 3)
+        XCTAssertEqual(clusters[0].sessionIds.count, 2)
+        XCTAssertEqual(clusters[0].tools, ["Bash"])
+        XCTAssertEqual(clusters[0].example, "error: cannot open /tmp/a/x.swift at line 12")
+        XCTAssertNotEqual(FailureCluster.signature(of: "HTTP 401 Unauthorized"), FailureCluster.signature(of: "HTTP 500 Internal Server Error"))
+    }
+
+    func testFailureClustersLookPastExitCodeHeaders() {
+        func failedBash(_ id: String, _ output: String) -> String {
+            [
+                #"{"type":"assistant","timestamp":"2026-01-01T10:00:00.000Z","message":{"content":[{"type":"tool_use","id":"\#(id)","name":"Bash","input":{"command":"npm test"}}]}}"#,
+                #"{"type":"user","timestamp":"2026-01-01T10:00:01.000Z","message":{"content":[{"type":"tool_result","tool_use_id":"\#(id)","content":"\#(output)","is_error":true}]}}"#
+            ].joined(separator: "\n")
+        }
+        let transcript = TranscriptReader.parse([
+            failedBash("t1", #"Exit code 1\nsh: jest: command not found"#),
+            failedBash("t2", #"Exit code 2\nerror[E0425]: cannot find value `x` in this scope"#),
+            failedBash("t3", #"Exit code 1\nTraceback (most recent call last):\n  File \"app.py\", line 3, in <module>\nModuleNotFoundError: No module named 'requests'"#)
+        ].joined(separator: "\n"), agentType: .claudeCode)
+
+        let signatures = FailureCluster.group([(Session(name: "a"), transcript)]).map(\.signature)
+        XCTAssertEqual(signatures.count, 3)
+        XCTAssertFalse(signatures.contains { $0.hasPrefix("exit code") })
+        XCTAssertTrue(signatures.contains("modulenotfounderror: no module named <str>"))
+        XCTAssertEqual(FailureCluster.errorLine(in: "Exit code 1"), "Exit code 1")
+    }
+
+    func testEfficiencyMetricsRatiosAndRetries() {
+        let transcript = TranscriptReader.parse([
+            #"{"type":"assistant","timestamp":"2026-01-01T10:00:00.000Z","message":{"content":[{"type":"tool_use","id":"t1","name":"Bash","input":{"command":"swift test"}}]}}"#,
+            #"{"type":"user","timestamp":"2026-01-01T10:00:01.000Z","message":{"content":[{"type":"tool_result","tool_use_id":"t1","content":"1 failure","is_error":true}]}}"#,
+            #"{"type":"assistant","timestamp":"2026-01-01T10:00:02.000Z","message":{"content":[{"type":"tool_use","id":"t2","name":"Edit","input":{"file_path":"/tmp/api/Login.swift","old_string":"a < b","new_string":"a > b"}}]}}"#,
+            #"{"type":"user","timestamp":"2026-01-01T10:00:03.000Z","message":{"content":[{"type":"tool_result","tool_use_id":"t2","content":"ok"}]}}"#,
+            #"{"type":"assistant","timestamp":"2026-01-01T10:00:04.000Z","message":{"content":[{"type":"tool_use","id":"t3","name":"Bash","input":{"command":"swift test"}}]}}"#,
+            #"{"type":"user","timestamp":"2026-01-01T10:00:05.000Z","message":{"content":[{"type":"tool_result","tool_use_id":"t3","content":"ok"}]}}"#
+        ].joined(separator: "\n"), agentType: .claudeCode)
+        var session = Session(name: "Fix login", projectPath: "/tmp/api")
+        session.metrics.totalTokens = 3000
+        session.metrics.cost = 0.3
+
+        let metrics = EfficiencyMetrics(session: session, transcript: transcript)
+        XCTAssertEqual(metrics.tokensPerFile, 3000)
+        XCTAssertEqual(metrics.costPerChangedLine ?? 0, 0.15, accuracy: 0.0001)
+        XCTAssertEqual(metrics.retriesByTool, ["Bash": 1])
+
+        let combined = EfficiencyMetrics.combined([metrics, EfficiencyMetrics(session: Session(name: "Idle"), transcript: Transcript())])
+        XCTAssertEqual(combined.sessions, 2)
+        XCTAssertEqual(combined.filesChanged, 1)
+        XCTAssertEqual(combined.retries, 1)
+        XCTAssertNil(EfficiencyMetrics().tokensPerFile)
+    }
+
+    func testDurationAnomalyNeedsBaselineAndThreeTimesMedian() {
+        let now = date("2026-01-05T12:00:00Z")
+        func ended(minutes: Double, project: String = "/tmp/nightly") -> Session {
+            Session(name: "run", status: .completed, startedAt: now.addingTimeInterval(-86_400), endedAt: now.addingTimeInterval(-86_400 + minutes * 60), projectPath: project)
+        }
+        let live = Session(name: "Nightly", status: .running, startedAt: now.addingTimeInterval(-2 * 3600), projectPath: "/tmp/nightly")
+        let history = [ended(minutes: 10), ended(minutes: 12), ended(minutes: 14)]
+
+        XCTAssertEqual(DurationBaseline.medians(of: history, now: now)["Claude Code|/tmp/nightly"], 12 * 60)
+        let anomalies = DurationBaseline.anomalies(in: history + [live], now: now)
+        XCTAssertEqual(anomalies.map(\.sessionId), [live.id])
+        XCTAssertEqual(anomalies.first?.typical, 12 * 60)
+        XCTAssertEqual(anomalies.first?.elapsed(asOf: now), 2 * 3600)
+        XCTAssertEqual(anomalies.first?.elapsed(asOf: now.addingTimeInterval(600)), 2 * 3600 + 600)
+
+        XCTAssertTrue(DurationBaseline.anomalies(in: Array(history.prefix(2)) + [live], now: now).isEmpty)
+        let onTime = Session(name: "Nightly", status: .running, startedAt: now.addingTimeInterval(-20 * 60), projectPath: "/tmp/nightly")
+        XCTAssertTrue(DurationBaseline.anomalies(in: history + [onTime], now: now).isEmpty)
+    }
+}
+
+// MARK: - Session Query Tests
+
+final class SessionQueryTests: XCTestCase {
+
+    private let now = ISO8601DateFormatter().date(from: "2026-03-10T12:00:00Z")!
+
+    private var sessions: [Session] {
+        var labelled = Session(
+            name: "Fix login",
+            status: .failed,
+            agentType: .codex,
+            startedAt: now.addingTimeInterval(-2 * 86_400),
+            endedAt: now.addingTimeInterval(-2 * 86_400 + 3600),
+            metrics: SessionMetrics(totalTokens: 250_000, cost: 4.5, modelIds: ["gpt-5-codex"]),
+            projectPath: "/repo/api",
+            gitBranch: "eng-142-login"
+        )
+        labelled.label = SessionLabel(color: .red, emoji: "🐛")
+        return [
+            labelled,
+            Session(
+                name: "Old codex run",
+                status: .failed,
+                agentType: .codex,
+                startedAt: now.addingTimeInterval(-10 * 86_400),
+                metrics: SessionMetrics(totalTokens: 500_000, cost: 9),
+                projectPath: "/repo/api"
+            ),
+            Session(
+                name: "Write docs",
+                status: .running,
+                agentType: .claudeCode,
+                startedAt: now.addingTimeInterval(-30 * 60),
+                metrics: SessionMetrics(totalTokens: 40_000, cost: 0.4),
+                projectPath: "/repo/site"
+            )
+        ]
+    }
+
+    private func names(_ text: String) throws -> [String] {
+        try SessionQuery(text).filter(sessions, now: now).map(\.name)
+    }
+
+    func testCombinesComparisonsWithSuffixedNumbersAndAges() throws {
+        XCTAssertEqual(try names("status=failed AND agent=codex AND tokens>100k AND started>7d"), ["Fix login"])
+        XCTAssertEqual(try names("cost>=$4.5 started<7d"), ["Old codex run"])
+        XCTAssertEqual(try names("duration>20m AND duration<2h"), ["Fix login", "Write docs"])
+        XCTAssertEqual(try names("ended<2026-03-10"), ["Fix login"])
+    }
+
+    func testTextFieldsIgnoreCaseAndMatchAlternateForms() throws {
+        XCTAssertEqual(try names("agent=claude"), ["Write docs"])
+        XCTAssertEqual(try names("project=site"), ["Write docs"])
+        XCTAssertEqual(try names("name~LOGIN OR branch~eng"), ["Fix login"])
+        XCTAssertEqual(try names("label=red AND model~codex"), ["Fix login"])
+        XCTAssertEqual(try names("branch!=eng-142-login"), ["Old codex run", "Write docs"])
+        XCTAssertEqual(try names("name=\"write docs\""), ["Write docs"])
+    }
+
+    func testAndBindsTighterThanOrAndNotNegates() throws {
+        XCTAssertEqual(try names("status=running OR agent=codex AND tokens>300k"), ["Old codex run", "Write docs"])
+        XCTAssertEqual(try names("(status=running OR agent=codex) AND tokens<300k"), ["Fix login", "Write docs"])
+        XCTAssertEqual(try names("NOT status=failed"), ["Write docs"])
+    }
+
+    func testRejectsMalformedQueries() {
+        XCTAssertThrowsError(try SessionQuery("colour=red")) { XCTAssertEqual($0 as? SessionQueryError, .unknownField("colour")) }
+        XCTAssertThrowsError(try SessionQuery("tokens>lots")) { XCTAssertEqual($0 as? SessionQueryError, .invalidValue("lots", field: "tokens")) }
+        XCTAssertThrowsError(try SessionQuery("status>failed")) { XCTAssertEqual($0 as? SessionQueryError, .unsupportedOperator(">", field: "status")) }
+        XCTAssertThrowsError(try SessionQuery("started=7d")) { XCTAssertEqual($0 as? SessionQueryError, .unsupportedOperator("=", field: "started")) }
+        XCTAssertThrowsError(try SessionQuery("(status=failed")) { XCTAssertEqual($0 as? SessionQueryError, .unexpectedEnd) }
+        XCTAssertThrowsError(try SessionQuery("status=failed)")) { XCTAssertEqual($0 as? SessionQueryError, .unexpectedToken(")")) }
+    }
+}
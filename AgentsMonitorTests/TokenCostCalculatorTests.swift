@@ -52,6 +52,18 @@ final class TokenCostCalculatorCodexTests: XCTestCase {
         XCTAssertEqual(summary.outputTokens, 1000)
         XCTAssertEqual(summary.cacheReadTokens, 500)
         XCTAssertEqual(summary.apiCalls, 2)
+        XCTAssertEqual(summary.modelIds, ["gpt-5-codex"])
+    }
+
+    func testCalculateCodexReadsContextWindow() {
+        let path = writeTempJSONL([
+            #"{"type":"turn_context","payload":{"model":"gpt-5-codex"}}"#,
+            #"{"type":"event_msg","payload":{"type":"token_count","info":{"total_token_usage":{"input_tokens":9000,"cached_input_tokens":0,"output_tokens":900},"last_token_usage":{"input_tokens":6000,"cached_input_tokens":5000,"output_tokens":400},"model_context_window":272000}}}"#
+        ])
+
+        let summary = TokenCostCalculator.calculateCodex(jsonlPath: path)?.tokenSummary
+        XCTAssertEqual(summary?.contextTokens, 6400)
+        XCTAssertEqual(summary?.contextWindow, 272000)
     }
 
     // MARK: - Rate Limits
@@ -188,3 +200,141 @@ final class TokenCostCalculatorCodexTests: XCTestCase {
         XCTAssertEqual(decoded.summary.modelName, entry.summary.modelName)
     }
 }
+
+// MARK: - Token Estimates
+
+final class TokenEstimatorTests: XCTestCase {
+
+    func testCountsWordsAndPunctuationLikeABPETokenizer() {
+        XCTAssertEqual(TokenEstimator.estimate(""), 0)
+        XCTAssertEqual(TokenEstimator.estimate("Hello world"), 2)
+        XCTAssertEqual(TokenEstimator.estimate("let x = 42"), 5)
+        XCTAssertEqual(TokenEstimator.estimate("日本語"), 3)
+    }
+
+    func testLongTextIsRoughlyFourCharactersPerToken() {
+        let prose = String(repeating: "The quick brown fox jumps over the lazy dog. ", count: 20)
+        let estimate = TokenEstimator.estimate(prose)
+        XCTAssertGreaterThan(estimate, prose.count / 6)
+        XCTAssertLessThan(estimate, prose.count / 3)
+    }
+
+    func testSummarySplitsInputAndOutput() {
+        let content = [
+            #"{"type":"user","message":{"content":"Count the files in this folder please"}}"#,
+            #"{"type":"assistant","message":{"content":[{"type":"text","text":"Sure."},{"type":"tool_use","id":"t1","name":"Bash","input":{"command":"ls | wc -l"}}]}}"#,
+            #"{"type":"user","message":{"content":[{"type":"tool_result","tool_use_id":"t1","content":"12"}]}}"#
+        ].joined(separator: "\n")
+        let transcript = TranscriptReader.parse(content, agentType: .claudeCode)
+        let summary = TokenEstimator.summary(for: transcript)
+
+        let prompt = TokenEstimator.estimate("Count the files in this folder please")
+        XCTAssertEqual(summary.inputTokens, prompt + TokenEstimator.estimate("12"))
+        XCTAssertEqual(summary.outputTokens, TokenEstimator.estimate("Sure.") + TokenEstimator.estimate(#"{"command":"ls | wc -l"}"#))
+        XCTAssertEqual(summary.isEstimated, true)
+        XCTAssertEqual(transcript.messages.first?.estimatedTokens, prompt)
+    }
+
+    func testEstimatesWhenUsageIsMissingOrZero() {
+        // A Codex session without token_count events reports no usage at all.
+        let content = #"{"type":"response_item","timestamp":"2026-01-01T10:00:00Z","payload":{"type":"message","role":"user","content":[{"type":"input_text","text":"Rename the config loader"}]}}"#
+        let transcript = TranscriptReader.parse(content, agentType: .codex)
+
+        let estimated = TokenEstimator.summary(reported: nil) { transcript }
+        XCTAssertEqual(estimated?.inputTokens, TokenEstimator.estimate("Rename the config loader"))
+        XCTAssertEqual(estimated?.isEstimated, true)
+        XCTAssertNil(TokenEstimator.summary(reported: nil) { Transcript() })
+
+        let reported = TokenEstimator.summary(for: transcript)
+        let kept = TokenEstimator.summary(reported: reported) { XCTFail("read the transcript"); return nil }
+        XCTAssertEqual(kept?.inputTokens, reported.inputTokens)
+    }
+
+    func testSummaryPricesAtTheTranscriptModel() {
+        let content = #"{"type":"assistant","message":{"model":"claude-sonnet-4-5","content":[{"type":"text","text":"Done."}]}}"#
+        let transcript = TranscriptReader.parse(content, agentType: .claudeCode)
+        let summary = TokenEstimator.summary(for: transcript)
+
+        XCTAssertEqual(transcript.models, ["claude-sonnet-4-5"])
+        XCTAssertEqual(summary.modelName, "Sonnet 4")
+        XCTAssertGreaterThan(summary.cost, 0)
+    }
+}
+
+// MARK: - MCP Server Tests
+
+final class MCPServersTests: XCTestCase {
+
+    private func writeTempFile(_ content: String, at relativePath: String, in directory: URL) {
+        let url = directory.appendingPathComponent(relativePath)
+        try! FileManager.default.createDirectory(at: url.deletingLastPathComponent(), withIntermediateDirectories: true)
+        try! content.write(to: url, atomically: true, encoding: .utf8)
+    }
+
+    private func makeTempDirectory() -> URL {
+        let url = URL(fileURLWithPath: NSTemporaryDirectory()).appendingPathComponent(UUID().uuidString)
+        try! FileManager.default.createDirectory(at: url, withIntermediateDirectories: true)
+        addTeardownBlock { try? FileManager.default.removeItem(at: url) }
+        return url
+    }
+
+    func testParsesQualifiedToolNames() {
+        let claude = MCPServers.ToolUse(toolName: "mcp__github__create_issue", agentType: .claudeCode)
+        XCTAssertEqual(claude?.server, "github")
+        XCTAssertEqual(claude?.tool, "create_issue")
+        XCTAssertNil(MCPServers.ToolUse(toolName: "Bash", agentType: .claudeCode))
+        XCTAssertNil(MCPServers.ToolUse(toolName: "linear__list_issues", agentType: .claudeCode))
+        XCTAssertEqual(MCPServers.ToolUse(toolName: "linear__list_issues", agentType: .codex)?.server, "linear")
+        XCTAssertNil(MCPServers.ToolUse(toolName: "shell", agentType: .codex))
+    }
+
+    func testClaudeCalculationRecordsMCPToolsInOrder() {
+        let path = NSTemporaryDirectory() + UUID().uuidString + ".jsonl"
+        try! [
+            #"{"type":"assistant","message":{"model":"claude-sonnet-4-5","usage":{"input_tokens":10,"output_tokens":5},"content":[{"type":"tool_use","id":"t1","name":"mcp__linear__list_issues","input":{}}]}}"#,
+            #"{"type":"assistant","message":{"model":"claude-sonnet-4-5","usage":{"input_tokens":10,"output_tokens":5},"content":[{"type":"tool_use","id":"t2","name":"Read","input":{}},{"type":"tool_use","id":"t3","name":"mcp__github__get_pr","input":{}},{"type":"tool_use","id":"t4","name":"mcp__linear__list_issues","input":{}}]}}"#
+        ].joined(separator: "\n").write(toFile: path, atomically: true, encoding: .utf8)
+        addTeardownBlock { try? FileManager.default.removeItem(atPath: path) }
+
+        let summary = TokenCostCalculator.calculate(jsonlPath: path)
+        XCTAssertEqual(summary?.mcpTools, ["mcp__linear__list_issues", "mcp__github__get_pr"])
+
+        var session = Session(name: "s", agentType: .claudeCode)
+        session.mcpToolsUsed = summary?.mcpTools ?? []
+        XCTAssertEqual(session.usedMCPServers, ["linear", "github"])
+    }
+
+    func testClaudeConfiguredServersMergeGlobalProjectAndSharedFile() {
+        let home = makeTempDirectory()
+        let project = makeTempDirectory()
+        writeTempFile(
+            #"{"mcpServers":{"github":{}},"projects":{"\#(project.path)":{"mcpServers":{"sentry":{}},"disabledMcpjsonServers":["figma"]}}}"#,
+            at: ".claude.json", in: home
+        )
+        writeTempFile(#"{"mcpServers":{"linear":{},"figma":{}}}"#, at: ".mcp.json", in: project)
+
+        XCTAssertEqual(
+            MCPServers.configured(agentType: .claudeCode, projectPath: project.path, home: home),
+            ["github", "linear", "sentry"]
+        )
+        XCTAssertEqual(MCPServers.configured(agentType: .claudeCode, projectPath: nil, home: home), ["github"])
+    }
+
+    func testCodexConfiguredServersComeFromConfigTables() {
+        let home = makeTempDirectory()
+        writeTempFile("""
+            model = "gpt-5-codex"
+
+            [mcp_servers.linear]
+            command = "npx"
+
+            [mcp_servers.linear.env]
+            TOKEN = "x"
+
+            [mcp_servers."docs.search"]
+            url = "https://example.com"
+            """, at: ".codex/config.toml", in: home)
+
+        XCTAssertEqual(MCPServers.configured(agentType: .codex, projectPath: nil, home: home), ["docs.search", "linear"])
+    }
+}
@@ -0,0 +1,762 @@
+import XCTest
+import SQLite3
+@testable import AgentsMonitor
+
+final class TranscriptReaderTests: XCTestCase {
+
+    private let claudeTranscript = [
+        #"{"type":"user","timestamp":"2026-01-01T10:00:00.000Z","message":{"role":"user","content":"Fix the failing login test"}}"#,
+        #"{"type":"assistant","timestamp":"2026-01-01T10:00:05.000Z","message":{"content":[{"type":"text","text":"Running the tests first."},{"type":"tool_use","id":"toolu_1","name":"Bash","input":{"command":"swift test --filter LoginTests"}}]}}"#,
+        #"{"type":"user","timestamp":"2026-01-01T10:00:20.000Z","message":{"content":[{"type":"tool_result","tool_use_id":"toolu_1","content":"LoginTests.testExpiredToken failed","is_error":true}]}}"#,
+        #"{"type":"assistant","timestamp":"2026-01-01T10:00:30.000Z","message":{"content":[{"type":"text","text":"The expired token check is inverted."}]}}"#
+    ].joined(separator: "\n")
+
+    func testParsesClaudeMessagesAndToolCalls() {
+        let transcript = TranscriptReader.parse(claudeTranscript, agentType: .claudeCode)
+
+        let texts = transcript.messages.filter { $0.role != .tool }.map(\.content)
+        XCTAssertEqual(texts, ["Fix the failing login test", "Running the tests first.", "The expired token check is inverted."])
+
+        XCTAssertEqual(transcript.toolCalls.count, 1)
+        let call = transcript.toolCalls[0]
+        XCTAssertEqual(call.name, "Bash")
+        XCTAssertEqual(call.input, #"{"command":"swift test --filter LoginTests"}"#)
+        XCTAssertEqual(call.status, .failed)
+        XCTAssertEqual(call.output, "LoginTests.testExpiredToken failed")
+        XCTAssertEqual(call.duration ?? 0, 15, accuracy: 0.01)
+    }
+
+    func testAssistantRepliesCarryCodeBlocks() {
+        let content = """
+        {"type":"user","message":{"content":"```bash\\nls\\n```"}}
+        {"type":"assistant","message":{"content":[{"type":"text","text":"Run:\\n```bash\\nls -la\\n```"}]}}
+        """
+        let transcript = TranscriptReader.parse(content, agentType: .claudeCode)
+
+        XCTAssertEqual(transcript.messages.count, 2)
+        XCTAssertTrue(transcript.messages[0].codeBlocks.isEmpty)
+        XCTAssertEqual(transcript.messages[1].codeBlocks, [CodeBlock(language: "bash", content: "ls -la")])
+    }
+
+    func testParsesCodexMessagesAndFunctionCalls() {
+        let content = [
+            #"{"timestamp":"2026-01-01T10:00:00Z","type":"response_item","payload":{"type":"message","role":"user","content":[{"type":"input_text","text":"List files"}]}}"#,
+            #"{"timestamp":"2026-01-01T10:00:02Z","type":"response_item","payload":{"type":"function_call","name":"shell","arguments":"{\"command\":[\"ls\"]}","call_id":"call_1"}}"#,
+            #"{"timestamp":"2026-01-01T10:00:03Z","type":"response_item","payload":{"type":"function_call_output","call_id":"call_1","output":"README.md"}}"#
+        ].joined(separator: "\n")
+
+        let transcript = TranscriptReader.parse(content, agentType: .codex)
+        XCTAssertEqual(transcript.messages.first?.content, "List files")
+        XCTAssertEqual(transcript.toolCalls.first?.name, "shell")
+        XCTAssertEqual(transcript.toolCalls.first?.output, "README.md")
+        XCTAssertEqual(transcript.toolCalls.first?.status, .completed)
+    }
+
+    // MARK: - Search
+
+    func testSearchFindsMatchesInMessagesAndToolOutput() {
+        let transcript = TranscriptReader.parse(claudeTranscript, agentType: .claudeCode)
+        let matches = TranscriptSearch.search("expired", in: transcript)
+
+        XCTAssertEqual(matches.map(\.sourceLabel), ["Bash output", "Assistant"])
+        XCTAssertEqual(matches.last?.offset, 4)
+    }
+
+    func testSearchIsCaseInsensitiveAndTrimsQuery() {
+        let transcript = TranscriptReader.parse(claudeTranscript, agentType: .claudeCode)
+        XCTAssertEqual(TranscriptSearch.search("  LOGIN ", in: transcript).count, 3)
+        XCTAssertTrue(TranscriptSearch.search("   ", in: transcript).isEmpty)
+    }
+
+    func testSearchLimitKeepsEarliestMatchesAcrossMessagesAndTools() {
+        let start = Date(timeIntervalSince1970: 1_767_261_600)
+        var transcript = Transcript()
+        transcript.messages = [
+            Message(role: .user, content: "Why does the build fail?", timestamp: start),
+            Message(role: .assistant, content: "The build fails on a timeout.", timestamp: start.addingTimeInterval(30)),
+            Message(role: .assistant, content: "Another timeout, retrying.", timestamp: start.addingTimeInterval(60))
+        ]
+        transcript.toolCalls = [
+            ToolCall(name: "Bash", input: "{}", output: "error: timeout after 10s", startedAt: start.addingTimeInterval(10), completedAt: start.addingTimeInterval(20), status: .failed)
+        ]
+
+        let matches = TranscriptSearch.search("timeout", in: transcript, limit: 2)
+
+        XCTAssertEqual(matches.map(\.sourceLabel), ["Bash output", "Assistant"])
+        XCTAssertEqual(matches.map(\.timestamp), [start.addingTimeInterval(20), start.addingTimeInterval(30)])
+    }
+
+    func testLinksAreFoundOnceInFirstMentionOrder() {
+        let content = [
+            #"{"type":"user","timestamp":"2026-01-01T10:00:00.000Z","message":{"role":"user","content":"See https://github.com/acme/api/issues/42 and mail me"}}"#,
+            #"{"type":"assistant","timestamp":"2026-01-01T10:00:05.000Z","message":{"content":[{"type":"tool_use","id":"toolu_1","name":"Bash","input":{"command":"gh pr create"}}]}}"#,
+            #"{"type":"user","timestamp":"2026-01-01T10:00:20.000Z","message":{"content":[{"type":"tool_result","tool_use_id":"toolu_1","content":"\u001b]8;;https://github.com/acme/api/pull/7\u001b\\#7\u001b]8;;\u001b\\ opened, ftp://example.com skipped"}]}}"#,
+            #"{"type":"assistant","timestamp":"2026-01-01T10:00:30.000Z","message":{"content":[{"type":"text","text":"Opened https://github.com/acme/api/pull/7 for https://github.com/acme/api/issues/42 after editing README.md, main.rs and setup.py."}]}}"#
+        ].joined(separator: "\n")
+        let transcript = TranscriptReader.parse(content, agentType: .claudeCode)
+        let links = TranscriptSearch.links(in: transcript)
+
+        XCTAssertEqual(links.map(\.url.absoluteString), [
+            "https://github.com/acme/api/issues/42",
+            "https://github.com/acme/api/pull/7"
+        ])
+        XCTAssertEqual(links.map(\.source.label), ["User", "Bash output"])
+        XCTAssertTrue(links[0].snippet.contains("See https://github.com/acme/api/issues/42"))
+    }
+
+    func testSnippetAddsEllipsesWhenTrimmed() {
+        let text = String(repeating: "a", count: 100) + "needle" + String(repeating: "b", count: 100)
+        let range = text.range(of: "needle")!
+        let snippet = TranscriptSearch.snippet(of: text, around: range)
+        XCTAssertTrue(snippet.hasPrefix("…"))
+        XCTAssertTrue(snippet.hasSuffix("…"))
+        XCTAssertTrue(snippet.contains("needle"))
+    }
+
+    func testRecordsSkippedLinesButNotTrailingPartialLine() {
+        let content = [
+            #"{"type":"user","timestamp":"2026-01-01T10:00:00.000Z","message":{"content":"Hi"}}"#,
+            #"{"type":"user","timestamp":"2026-01-01T10:00:01"#,
+            #"{"type":"assistant","timestamp":"2026-01-01T10:00:02.000Z"}"#,
+            #"{"type":"future-entry","timestamp":"2026-01-01T10:00:03.000Z"}"#,
+            #"{"type":"assistant","timestamp":"2026-01-01T10:00:04.000Z","message":{"content":[{"type":"text",""#
+        ].joined(separator: "\n")
+        let transcript = TranscriptReader.parse(content, agentType: .claudeCode)
+
+        XCTAssertEqual(transcript.messages.count, 1)
+        XCTAssertTrue(transcript.isPartial)
+        XCTAssertEqual(transcript.issues.map(\.line), [2, 3])
+        XCTAssertEqual(transcript.issues.first?.description, "Line 2: Not a JSON object")
+        XCTAssertFalse(TranscriptReader.parse(content.components(separatedBy: "\n")[0] + "\n", agentType: .claudeCode).isPartial)
+    }
+}
+
+// MARK: - Threading Tests
+
+final class TranscriptThreadingTests: XCTestCase {
+
+    private let root = "11111111-1111-1111-1111-111111111111"
+    private let toolLine = "22222222-2222-2222-2222-222222222222"
+    private let result = "33333333-3333-3333-3333-333333333333"
+    private let answer = "44444444-4444-4444-4444-444444444444"
+    private let retry = "55555555-5555-5555-5555-555555555555"
+
+    private var content: String {
+        [
+            #"{"type":"user","uuid":"\#(root)","parentUuid":null,"message":{"content":"Rename the type"}}"#,
+            #"{"type":"assistant","uuid":"\#(toolLine)","parentUuid":"\#(root)","message":{"content":[{"type":"text","text":"Looking."},{"type":"tool_use","id":"t1","name":"Grep","input":{"pattern":"Foo"}}]}}"#,
+            #"{"type":"user","uuid":"\#(result)","parentUuid":"\#(toolLine)","message":{"content":[{"type":"tool_result","tool_use_id":"t1","content":"3 files"}]}}"#,
+            #"{"type":"assistant","uuid":"\#(answer)","parentUuid":"\#(result)","message":{"content":[{"type":"text","text":"Renamed in 3 files."}]}}"#,
+            #"{"type":"assistant","uuid":"\#(retry)","parentUuid":"\#(root)","message":{"content":[{"type":"text","text":"Retrying."}]}}"#
+        ].joined(separator: "\n")
+    }
+
+    func testThreadFollowsParentsThroughToolResults() {
+        let transcript = TranscriptReader.parse(content, agentType: .claudeCode)
+        let thread = transcript.thread(endingAt: UUID(uuidString: answer)!)
+        XCTAssertEqual(thread.map(\.content), ["Rename the type", "Looking.", "Grep", "Renamed in 3 files."])
+    }
+
+    func testRetryBranchesFromTheRoot() {
+        let transcript = TranscriptReader.parse(content, agentType: .claudeCode)
+        let rootId = UUID(uuidString: root)!
+
+        XCTAssertEqual(transcript.replies(to: rootId).map(\.content), ["Looking.", "Retrying."])
+        XCTAssertEqual(transcript.branchPoints.map(\.id), [rootId])
+        XCTAssertEqual(transcript.thread(endingAt: UUID(uuidString: retry)!).map(\.content), ["Rename the type", "Retrying."])
+    }
+
+    func testTranscriptsWithoutParentsUseFileOrder() {
+        let content = [
+            #"{"type":"response_item","payload":{"type":"message","role":"user","content":[{"type":"input_text","text":"one"}]}}"#,
+            #"{"type":"response_item","payload":{"type":"message","role":"assistant","content":[{"type":"output_text","text":"two"}]}}"#
+        ].joined(separator: "\n")
+        let transcript = TranscriptReader.parse(content, agentType: .codex)
+        let last = transcript.messages[1]
+        XCTAssertEqual(transcript.thread(endingAt: last.id).map(\.content), ["one", "two"])
+    }
+}
+
+// MARK: - Attachment Tests
+
+final class TranscriptAttachmentTests: XCTestCase {
+
+    private let pngBase64 = Data([0x89, 0x50, 0x4E, 0x47]).base64EncodedString()
+
+    func testClaudeImageBlocksAttachToPrompt() {
+        let content = #"{"type":"user","message":{"content":[{"type":"text","text":"What is wrong here?"},{"type":"image","source":{"type":"base64","media_type":"image/png","data":"\#(pngBase64)"}}]}}"#
+        let transcript = TranscriptReader.parse(content, agentType: .claudeCode)
+
+        XCTAssertEqual(transcript.messages.count, 1)
+        let attachment = transcript.messages[0].attachments.first
+        XCTAssertEqual(attachment?.kind, .image)
+        XCTAssertEqual(attachment?.mediaType, "image/png")
+        XCTAssertEqual(attachment?.data, Data([0x89, 0x50, 0x4E, 0x47]))
+    }
+
+    func testImageOnlyPromptGetsItsOwnMessage() {
+        let content = #"{"type":"user","message":{"content":[{"type":"image","source":{"type":"base64","media_type":"image/jpeg","data":"\#(pngBase64)"}}]}}"#
+        let transcript = TranscriptReader.parse(content, agentType: .claudeCode)
+        XCTAssertEqual(transcript.messages.count, 1)
+        XCTAssertEqual(transcript.messages[0].attachments.first?.fileExtension, "jpg")
+    }
+
+    func testFileToolsReferenceTheirPath() {
+        let content = #"{"type":"assistant","message":{"content":[{"type":"tool_use","id":"t1","name":"Read","input":{"file_path":"/tmp/app/main.swift"}}]}}"#
+        let transcript = TranscriptReader.parse(content, agentType: .claudeCode)
+        let attachment = transcript.messages.first?.attachments.first
+        XCTAssertEqual(attachment?.kind, .file)
+        XCTAssertEqual(attachment?.displayName, "main.swift")
+    }
+
+    func testCodexDataURLImages() {
+        let attachment = TranscriptReader.imageAttachment(fromDataURL: "data:image/gif;base64,\(pngBase64)")
+        XCTAssertEqual(attachment?.mediaType, "image/gif")
+        XCTAssertNil(TranscriptReader.imageAttachment(fromDataURL: "https://example.com/a.png"))
+    }
+
+    func testInlineImagesAreWrittenOncePerSession() throws {
+        let base = FileManager.default.temporaryDirectory.appendingPathComponent("attachments_\(UUID().uuidString)")
+        defer { try? FileManager.default.removeItem(at: base) }
+        let sessionId = UUID()
+        let image = MessageAttachment(kind: .image, mediaType: "image/png", data: Data([1, 2, 3]))
+        let sameBytes = MessageAttachment(kind: .image, mediaType: "image/png", data: Data([1, 2, 3]))
+
+        let first = try AttachmentStore.fileURL(for: image, sessionId: sessionId, base: base)
+        let second = try AttachmentStore.fileURL(for: sameBytes, sessionId: sessionId, base: base)
+        XCTAssertEqual(first, second)
+        XCTAssertEqual(try Data(contentsOf: first), Data([1, 2, 3]))
+        XCTAssertEqual(first.deletingLastPathComponent(), AttachmentStore.folder(for: sessionId, base: base))
+    }
+}
+
+// MARK: - File Diff Tests
+
+final class FileDiffTests: XCTestCase {
+
+    func testEditBecomesUnifiedDiff() {
+        let diff = FileDiff(toolName: "Edit", input: [
+            "file_path": "/tmp/app/Login.swift",
+            "old_string": "guard token.isExpired else {\n    return false\n}",
+            "new_string": "guard !token.isExpired else {\n    return false\n}"
+        ])
+
+        XCTAssertEqual(diff?.unified, """
+        --- /tmp/app/Login.swift
+        +++ /tmp/app/Login.swift
+        @@ -1,3 +1,3 @@
+        -guard token.isExpired else {
+        +guard !token.isExpired else {
+             return false
+         }
+        """)
+        XCTAssertEqual(diff?.additions, 1)
+        XCTAssertEqual(diff?.deletions, 1)
+    }
+
+    func testDistantChangesGetSeparateHunks() {
+        let old = (1...20).map { "line \($0)" }.joined(separator: "\n")
+        let new = old.replacingOccurrences(of: "line 2\n", with: "line two\n")
+            .replacingOccurrences(of: "line 18\n", with: "line eighteen\n")
+        let diff = FileDiff(filePath: "notes.txt", old: old, new: new)
+
+        XCTAssertEqual(diff.hunks.count, 2)
+        XCTAssertEqual(diff.hunks[0].oldStart, 1)
+        XCTAssertEqual(diff.hunks[0].oldCount, 5)
+        XCTAssertEqual(diff.hunks[1].oldStart, 15)
+        XCTAssertEqual(diff.hunks[1].newCount, 6)
+    }
+
+    func testWriteIsAllAdditions() {
+        let diff = FileDiff(toolName: "Write", input: ["file_path": "README.md", "content": "# Title\nBody\n"])
+        XCTAssertEqual(diff?.isNewFile, true)
+        XCTAssertEqual(diff?.unified, "--- /dev/null\n+++ b/README.md\n@@ -0,0 +1,2 @@\n+# Title\n+Body")
+    }
+
+    func testOtherToolsHaveNoDiff() {
+        XCTAssertNil(FileDiff(toolName: "Read", input: #"{"file_path":"/tmp/a"}"#))
+        XCTAssertNil(FileDiff(toolName: "Edit", input: #"{"file_path":"/tmp/a"}"#))
+    }
+
+    func testTranscriptAttachesDiffToToolCall() {
+        let content = #"{"type":"assistant","message":{"content":[{"type":"tool_use","id":"t1","name":"MultiEdit","input":{"file_path":"a.py","edits":[{"old_string":"x = 1","new_string":"x = 2"},{"old_string":"y = 1","new_string":"y = 2"}]}}]}}"#
+        let transcript = TranscriptReader.parse(content, agentType: .claudeCode)
+        let call = transcript.toolCalls[0]
+
+        let diff = transcript.diff(forToolCall: call.id)
+        XCTAssertEqual(diff?.hunks.map(\.oldStart), [1, 2])
+        XCTAssertEqual(diff?.additions, 2)
+        XCTAssertEqual(transcript.fileChanges.count, 1)
+    }
+}
+
+// MARK: - Claude JSONL Export Tests
+
+final class ClaudeTranscriptExporterTests: XCTestCase {
+
+    private let codexRollout = [
+        #"{"timestamp":"2026-01-01T10:00:00Z","type":"response_item","payload":{"type":"message","role":"user","content":[{"type":"input_text","text":"List files"}]}}"#,
+        #"{"timestamp":"2026-01-01T10:00:02Z","type":"response_item","payload":{"type":"function_call","name":"shell","arguments":"{\"command\":[\"ls\"]}","call_id":"call_1"}}"#,
+        #"{"timestamp":"2026-01-01T10:00:03Z","type":"response_item","payload":{"type":"function_call_output","call_id":"call_1","output":"README.md"}}"#,
+        #"{"timestamp":"2026-01-01T10:00:04Z","type":"response_item","payload":{"type":"message","role":"assistant","content":[{"type":"output_text","text":"There is one file."}]}}"#
+    ].joined(separator: "\n")
+
+    func testCodexSessionRoundTripsThroughClaudeFormat() {
+        let original = TranscriptReader.parse(codexRollout, agentType: .codex)
+        let session = Session(name: "List files", agentType: .codex, projectPath: "/tmp/repo", gitBranch: "main")
+
+        let jsonl = ClaudeTranscriptExporter.jsonl(for: original, session: session)
+        let reread = TranscriptReader.parse(jsonl, agentType: .claudeCode)
+
+        XCTAssertEqual(reread.messages.filter { $0.role != .tool }.map(\.content), ["List files", "There is one file."])
+        XCTAssertEqual(reread.toolCalls.map(\.name), ["shell"])
+        XCTAssertEqual(reread.toolCalls.first?.input, #"{"command":["ls"]}"#)
+        XCTAssertEqual(reread.toolCalls.first?.output, "README.md")
+        XCTAssertEqual(reread.toolCalls.first?.status, .completed)
+    }
+
+    func testLinesChainAndCarrySessionContext() throws {
+        let original = TranscriptReader.parse(codexRollout, agentType: .codex)
+        let session = Session(name: "List files", agentType: .codex, projectPath: "/tmp/repo", gitBranch: "main")
+        let lines = ClaudeTranscriptExporter.jsonl(for: original, session: session)
+            .split(separator: "\n")
+            .map { try? JSONSerialization.jsonObject(with: Data($0.utf8)) as? [String: Any] }
+
+        XCTAssertEqual(lines.count, 4)
+        XCTAssertTrue(lines[0]?["parentUuid"] is NSNull)
+        for (previous, line) in zip(lines, lines.dropFirst()) {
+            XCTAssertEqual(line?["parentUuid"] as? String, previous?["uuid"] as? String)
+        }
+        XCTAssertEqual(lines.compactMap { $0?["type"] as? String }, ["user", "assistant", "user", "assistant"])
+        XCTAssertEqual(lines[0]?["cwd"] as? String, "/tmp/repo")
+        XCTAssertEqual(lines[0]?["gitBranch"] as? String, "main")
+        XCTAssertEqual(lines[0]?["sessionId"] as? String, session.id.uuidString.lowercased())
+    }
+
+    func testEmptyTranscriptExportsNothing() {
+        XCTAssertEqual(ClaudeTranscriptExporter.jsonl(for: Transcript(), session: Session(name: "Empty")), "")
+    }
+}
+
+// MARK: - OTLP Export Tests
+
+final class OTLPExporterTests: XCTestCase {
+
+    func testTracesURLAddsReceiverPath() {
+        XCTAssertEqual(OTLPExporter.tracesURL(for: " http://localhost:4318 ")?.absoluteString, "http://localhost:4318/v1/traces")
+        XCTAssertEqual(OTLPExporter.tracesURL(for: "https://otel.example.com/v1/traces")?.absoluteString, "https://otel.example.com/v1/traces")
+        XCTAssertNil(OTLPExporter.tracesURL(for: ""))
+        XCTAssertNil(OTLPExporter.tracesURL(for: "localhost:4318"))
+    }
+
+    func testHeadersParseKeyValuePairsOrBareToken() {
+        XCTAssertEqual(OTLPExporter.headers(from: "x-honeycomb-team=abc, x-dataset = agents"), ["x-honeycomb-team": "abc", "x-dataset": "agents"])
+        XCTAssertEqual(OTLPExporter.headers(from: "Authorization=Basic%20dXNlcg=="), ["Authorization": "Basic dXNlcg=="])
+        XCTAssertEqual(OTLPExporter.headers(from: " secret-token "), ["Authorization": "Bearer secret-token"])
+        XCTAssertEqual(OTLPExporter.headers(from: ""), [:])
+    }
+
+    func testBatchesKeepEachSessionWholeUnderSpanLimit() {
+        func item(calls: Int) -> (session: Session, transcript: Transcript) {
+            var transcript = Transcript()
+            transcript.toolCalls = (0..<calls).map { _ in ToolCall(name: "Read", input: "{}", startedAt: Date(), completedAt: nil, status: .completed) }
+            return (Session(name: "s", status: .completed, startedAt: Date()), transcript)
+        }
+        let sessions = [item(calls: 3), item(calls: 4), item(calls: 20), item(calls: 1)]
+
+        let batches = OTLPExporter.batches(of: sessions, maxSpans: 10)
+
+        XCTAssertEqual(batches.map { $0.map(\.transcript.toolCalls.count) }, [[3, 4], [20], [1]])
+    }
+
+    func testSessionBecomesTraceWithNestedToolSpans() throws {
+        let start = Date(timeIntervalSince1970: 1_767_261_600)
+        let task = ToolCall(name: "Task", input: "{}", startedAt: start, completedAt: start.addingTimeInterval(30), status: .completed)
+        let grep = ToolCall(name: "Grep", input: "{}", startedAt: start.addingTimeInterval(5), completedAt: start.addingTimeInterval(6), status: .failed)
+        var transcript = Transcript()
+        transcript.toolCalls = [task, grep]
+        transcript.subagentToolCallIds = [task.id: [grep.id]]
+        var session = Session(name: "Find config", status: .completed, startedAt: start, endedAt: start.addingTimeInterval(60))
+        session.metrics.inputTokens = 1200
+        session.metrics.cost = 0.25
+
+        let payload = OTLPExporter.payload(for: [(session, transcript)])
+        let resource = try XCTUnwrap((payload["resourceSpans"] as? [[String: Any]])?.first)
+        let scope = try XCTUnwrap((resource["scopeSpans"] as? [[String: Any]])?.first)
+        let spans = try XCTUnwrap(scope["spans"] as? [[String: Any]])
+
+        XCTAssertEqual(spans.map { $0["name"] as? String }, ["Find config", "Task", "Grep"])
+        let traceId = session.id.uuidString.replacingOccurrences(of: "-", with: "").lowercased()
+        XCTAssertTrue(spans.allSatisfy { $0["traceId"] as? String == traceId })
+        XCTAssertNil(spans[0]["parentSpanId"])
+        XCTAssertEqual(spans[1]["parentSpanId"] as? String, spans[0]["spanId"] as? String)
+        XCTAssertEqual(spans[2]["parentSpanId"] as? String, spans[1]["spanId"] as? String)
+        XCTAssertEqual((spans[2]["status"] as? [String: Any])?["code"] as? Int, 2)
+        XCTAssertEqual(spans[0]["startTimeUnixNano"] as? String, "1767261600000000000")
+
+        let attributes = try XCTUnwrap(spans[0]["attributes"] as? [[String: Any]])
+        let tokens = attributes.first { $0["key"] as? String == "gen_ai.usage.input_tokens" }?["value"] as? [String: Any]
+        XCTAssertEqual(tokens?["intValue"] as? String, "1200")
+        let cost = attributes.first { $0["key"] as? String == "gen_ai.usage.cost_usd" }?["value"] as? [String: Any]
+        XCTAssertEqual(cost?["doubleValue"] as? Double, 0.25)
+
+        let again = OTLPExporter.payload(for: [(session, transcript)])
+        let againSpans = ((again["resourceSpans"] as? [[String: Any]])?.first?["scopeSpans"] as? [[String: Any]])?.first?["spans"] as? [[String: Any]]
+        XCTAssertEqual(againSpans?.map { $0["spanId"] as? String }, spans.map { $0["spanId"] as? String })
+    }
+}
+
+// MARK: - Analytics Database Export Tests
+
+final class AnalyticsDatabaseExporterTests: XCTestCase {
+
+    private var directory: URL!
+
+    override func setUpWithError() throws {
+        directory = FileManager.default.temporaryDirectory.appendingPathComponent(UUID().uuidString)
+        try FileManager.default.createDirectory(at: directory, withIntermediateDirectories: true)
+    }
+
+    override func tearDownWithError() throws {
+        try? FileManager.default.removeItem(at: directory)
+    }
+
+    private func query(_ sql: String, in url: URL) -> [[String?]] {
+        var db: OpaquePointer?
+        guard sqlite3_open_v2(url.path, &db, SQLITE_OPEN_READONLY, nil) == SQLITE_OK else { return [] }
+        defer { sqlite3_close(db) }
+        var statement: OpaquePointer?
+        guard sqlite3_prepare_v2(db, sql, -1, &statement, nil) == SQLITE_OK else { return [] }
+        defer { sqlite3_finalize(statement) }
+        var rows: [[String?]] = []
+        while sqlite3_step(statement) == SQLITE_ROW {
+            rows.append((0..<sqlite3_column_count(statement)).map { column in
+                sqlite3_column_text(statement, column).map { String(cString: $0) }
+            })
+        }
+        return rows
+    }
+
+    func testExportsSessionsMetricsMessagesAndToolCalls() throws {
+        let transcript = TranscriptReader.parse([
+            #"{"type":"user","timestamp":"2026-01-01T10:00:00.000Z","message":{"role":"user","content":"Fix the failing login test"}}"#,
+            #"{"type":"assistant","timestamp":"2026-01-01T10:00:05.000Z","message":{"content":[{"type":"text","text":"Running the tests first."},{"type":"tool_use","id":"toolu_1","name":"Bash","input":{"command":"swift test"}}]}}"#,
+            #"{"type":"user","timestamp":"2026-01-01T10:00:20.000Z","message":{"content":[{"type":"tool_result","tool_use_id":"toolu_1","content":"1 failure","is_error":true}]}}"#
+        ].joined(separator: "\n"), agentType: .claudeCode)
+        var session = Session(name: "Login", status: .failed, projectPath: "/tmp/api")
+        session.metrics.inputTokens = 1200
+        session.metrics.cost = 0.5
+        let url = directory.appendingPathComponent("analytics.sqlite")
+
+        try AnalyticsDatabaseExporter.export([(session, transcript)], to: url)
+
+        XCTAssertEqual(query("SELECT name, status, project_path FROM sessions", in: url), [["Login", "Failed", "/tmp/api"]])
+        XCTAssertEqual(query("SELECT input_tokens, cost FROM metrics", in: url), [["1200", "0.5"]])
+        XCTAssertEqual(query("SELECT files_changed, tokens_per_file, retries FROM metrics", in: url), [["0", nil, "0"]])
+        XCTAssertEqual(query("SELECT role FROM messages WHERE role != 'Tool' ORDER BY position", in: url), [["User"], ["Assistant"]])
+        XCTAssertEqual(query("SELECT name, status, output, duration FROM tool_calls", in: url), [["Bash", "Failed", "1 failure", "15.0"]])
+        XCTAssertEqual(query("SELECT count(*) FROM messages m JOIN sessions s ON s.id = m.session_id", in: url).first?.first, "\(transcript.messages.count)")
+    }
+
+    func testExportReplacesExistingFile() throws {
+        let url = directory.appendingPathComponent("analytics.sqlite")
+        try AnalyticsDatabaseExporter.export([(Session(name: "One"), Transcript()), (Session(name: "Two"), Transcript())], to: url)
+        try AnalyticsDatabaseExporter.export([(Session(name: "Three"), Transcript())], to: url)
+
+        XCTAssertEqual(query("SELECT name FROM sessions", in: url), [["Three"]])
+        XCTAssertEqual(try FileManager.default.contentsOfDirectory(atPath: directory.path), ["analytics.sqlite"])
+    }
+}
+
+// MARK: - Markdown Note Tests
+
+final class MarkdownNoteExporterTests: XCTestCase {
+
+    private let transcript = TranscriptReader.parse([
+        #"{"type":"user","timestamp":"2026-01-01T10:00:00.000Z","message":{"role":"user","content":"Fix the failing login test"}}"#,
+        #"{"type":"assistant","timestamp":"2026-01-01T10:00:05.000Z","message":{"content":[{"type":"text","text":"Fixing it."},{"type":"tool_use","id":"toolu_1","name":"Edit","input":{"file_path":"/tmp/api/Login.swift","old_string":"a < b","new_string":"a > b"}}]}}"#,
+        #"{"type":"user","timestamp":"2026-01-01T10:00:20.000Z","message":{"content":[{"type":"tool_result","tool_use_id":"toolu_1","content":"ok"}]}}"#
+    ].joined(separator: "\n"), agentType: .claudeCode)
+
+    private func session() -> Session {
+        var session = Session(
+            name: "Fix \"login\" tests",
+            status: .completed,
+            startedAt: ISO8601DateFormatter().date(from: "2026-01-01T12:00:00Z")!,
+            endedAt: ISO8601DateFormatter().date(from: "2026-01-01T12:01:00Z")!,
+            projectPath: "/tmp/api",
+            gitBranch: "fix/login"
+        )
+        session.metrics.cost = 0.125
+        return session
+    }
+
+    func testNoteHasFrontmatterAndSections() {
+        let note = MarkdownNoteExporter.note(for: session(), transcript: transcript)
+
+        XCTAssertTrue(note.hasPrefix("---\ntitle: \"Fix \\\"login\\\" tests\"\n"))
+        XCTAssertTrue(note.contains("repo: \"api\"\n"))
+        XCTAssertTrue(note.contains("branch: \"fix/login\"\n"))
+        XCTAssertTrue(note.contains("duration_seconds: 60\n"))
+        XCTAssertTrue(note.contains("cost: 0.1250\n"))
+        XCTAssertTrue(note.contains("  - agent/claude-code\n  - status/completed\n  - repo/api\n"))
+        XCTAssertTrue(note.contains("- `Login.swift` +1 −1"))
+        XCTAssertTrue(note.contains("| Edit | 1 | 0 |"))
+        XCTAssertTrue(note.contains("Fix the failing login test"))
+    }
+
+    func testFileNameIsStablePerSessionAndSafe() {
+        let session = session()
+        let name = MarkdownNoteExporter.fileName(for: session)
+
+        XCTAssertEqual(name, MarkdownNoteExporter.fileName(for: session))
+        XCTAssertTrue(name.hasPrefix("2026-01-01 Fix login tests ("))
+        XCTAssertTrue(name.hasSuffix("\(session.id.uuidString.prefix(8).lowercased())).md"))
+        XCTAssertFalse(name.dropLast(3).contains("/"))
+    }
+
+    func testWriteUpdatesExistingNoteAfterRenames() throws {
+        let folder = FileManager.default.temporaryDirectory.appendingPathComponent(UUID().uuidString)
+        defer { try? FileManager.default.removeItem(at: folder) }
+        var session = session()
+        let first = try MarkdownNoteExporter.write(session, transcript: transcript, toFolder: folder)
+
+        session.name = "Login tests, take two"
+        XCTAssertEqual(try MarkdownNoteExporter.write(session, transcript: transcript, toFolder: folder).lastPathComponent, first.lastPathComponent)
+
+        let renamed = folder.appendingPathComponent("Login fix.md")
+        try FileManager.default.moveItem(at: first, to: renamed)
+        XCTAssertEqual(try MarkdownNoteExporter.write(session, transcript: transcript, toFolder: folder).lastPathComponent, "Login fix.md")
+        XCTAssertEqual(try FileManager.default.contentsOfDirectory(atPath: folder.path), ["Login fix.md"])
+        XCTAssertTrue(try String(contentsOf: renamed).contains("# Login tests, take two"))
+    }
+}
+
+// MARK: - Model Tests
+
+final class TranscriptModelTests: XCTestCase {
+
+    func testCollectsCodexTurnModels() {
+        let content = [
+            #"{"type":"turn_context","payload":{"model":"gpt-5-codex"}}"#,
+            #"{"type":"turn_context","payload":{"model":"gpt-5.1-codex-mini"}}"#,
+            #"{"type":"turn_context","payload":{"model":"gpt-5-codex"}}"#
+        ].joined(separator: "\n")
+        XCTAssertEqual(TranscriptReader.parse(content, agentType: .codex).models, ["gpt-5-codex", "gpt-5.1-codex-mini"])
+    }
+
+    func testSkipsSyntheticClaudeMessages() {
+        let content = #"{"type":"assistant","message":{"model":"<synthetic>","content":[{"type":"text","text":"No response requested."}]}}"#
+        XCTAssertTrue(TranscriptReader.parse(content, agentType: .claudeCode).models.isEmpty)
+    }
+}
+
+// MARK: - Compaction Tests
+
+final class TranscriptCompactionTests: XCTestCase {
+
+    func testClaudeCompactBoundaryIsRecorded() {
+        let content = [
+            #"{"type":"user","timestamp":"2026-01-01T10:00:00Z","message":{"content":"Refactor the parser"}}"#,
+            #"{"type":"system","subtype":"compact_boundary","timestamp":"2026-01-01T11:00:00Z","content":"Conversation compacted","compactMetadata":{"trigger":"auto","preTokens":167000}}"#,
+            #"{"type":"user","isCompactSummary":true,"timestamp":"2026-01-01T11:00:01Z","message":{"content":"This session is being continued from a previous conversation."}}"#
+        ].joined(separator: "\n")
+        let transcript = TranscriptReader.parse(content, agentType: .claudeCode)
+
+        XCTAssertEqual(transcript.compactions, [
+            Compaction(timestamp: ISO8601DateFormatter().date(from: "2026-01-01T11:00:00Z")!, trigger: "auto", tokensBefore: 167000)
+        ])
+        XCTAssertEqual(transcript.messages.map(\.role), [.user, .system])
+    }
+
+    func testCodexCompactedLineIsRecorded() {
+        let content = #"{"timestamp":"2026-01-01T10:00:00Z","type":"compacted","payload":{"message":"Summary of earlier work"}}"#
+        let transcript = TranscriptReader.parse(content, agentType: .codex)
+
+        XCTAssertEqual(transcript.compactions.count, 1)
+        XCTAssertNil(transcript.compactions.first?.trigger)
+        XCTAssertEqual(transcript.messages.first?.content, "Summary of earlier work")
+    }
+}
+
+// MARK: - Sub-agent Tests
+
+final class TranscriptSubagentTests: XCTestCase {
+
+    func testSidechainToolCallsBelongToTheirTaskCall() {
+        let content = [
+            #"{"type":"assistant","uuid":"00000000-0000-0000-0000-000000000001","timestamp":"2026-01-01T10:00:00Z","message":{"content":[{"type":"tool_use","id":"toolu_task","name":"Task","input":{"description":"Find config loaders","subagent_type":"Explore","prompt":"Find every place config is loaded"}}]}}"#,
+            #"{"type":"user","isSidechain":true,"uuid":"00000000-0000-0000-0000-000000000002","parentUuid":null,"timestamp":"2026-01-01T10:00:01Z","message":{"content":"Find every place config is loaded"}}"#,
+            #"{"type":"assistant","isSidechain":true,"uuid":"00000000-0000-0000-0000-000000000003","parentUuid":"00000000-0000-0000-0000-000000000002","timestamp":"2026-01-01T10:00:02Z","message":{"content":[{"type":"tool_use","id":"toolu_grep","name":"Grep","input":{"pattern":"loadConfig"}}]}}"#,
+            #"{"type":"user","isSidechain":true,"uuid":"00000000-0000-0000-0000-000000000004","parentUuid":"00000000-0000-0000-0000-000000000003","timestamp":"2026-01-01T10:00:03Z","message":{"content":[{"type":"tool_result","tool_use_id":"toolu_grep","content":"Config.swift"}]}}"#,
+            #"{"type":"user","uuid":"00000000-0000-0000-0000-000000000005","parentUuid":"00000000-0000-0000-0000-000000000001","timestamp":"2026-01-01T10:00:40Z","message":{"content":[{"type":"tool_result","tool_use_id":"toolu_task","content":"Config is loaded in Config.swift"}]}}"#,
+            #"{"type":"assistant","uuid":"00000000-0000-0000-0000-000000000006","parentUuid":"00000000-0000-0000-0000-000000000005","timestamp":"2026-01-01T10:00:41Z","message":{"content":[{"type":"tool_use","id":"toolu_read","name":"Read","input":{"file_path":"Config.swift"}}]}}"#
+        ].joined(separator: "\n")
+        let transcript = TranscriptReader.parse(content, agentType: .claudeCode)
+
+        XCTAssertEqual(transcript.subagents.count, 1)
+        let run = transcript.subagents[0]
+        XCTAssertEqual(run.description, "Find config loaders")
+        XCTAssertEqual(run.agentType, "Explore")
+        XCTAssertEqual(run.status, .completed)
+        XCTAssertEqual(run.duration, 40)
+        XCTAssertEqual(run.toolCalls.map(\.name), ["Grep"])
+    }
+
+    func testParallelSidechainsAreCreditedByPrompt() {
+        let content = [
+            #"{"type":"assistant","uuid":"00000000-0000-0000-0000-000000000001","timestamp":"2026-01-01T10:00:00Z","message":{"content":[{"type":"tool_use","id":"toolu_a","name":"Task","input":{"description":"A","prompt":"Search the API"}},{"type":"tool_use","id":"toolu_b","name":"Task","input":{"description":"B","prompt":"Search the UI"}}]}}"#,
+            #"{"type":"user","isSidechain":true,"uuid":"00000000-0000-0000-0000-000000000002","parentUuid":null,"timestamp":"2026-01-01T10:00:01Z","message":{"content":"Search the API"}}"#,
+            #"{"type":"user","isSidechain":true,"uuid":"00000000-0000-0000-0000-000000000003","parentUuid":null,"timestamp":"2026-01-01T10:00:01Z","message":{"content":"Search the UI"}}"#,
+            #"{"type":"assistant","isSidechain":true,"uuid":"00000000-0000-0000-0000-000000000004","parentUuid":"00000000-0000-0000-0000-000000000002","timestamp":"2026-01-01T10:00:02Z","message":{"content":[{"type":"tool_use","id":"toolu_api","name":"Grep","input":{"pattern":"api"}}]}}"#,
+            #"{"type":"assistant","isSidechain":true,"uuid":"00000000-0000-0000-0000-000000000005","parentUuid":"00000000-0000-0000-0000-000000000003","timestamp":"2026-01-01T10:00:02Z","message":{"content":[{"type":"tool_use","id":"toolu_ui","name":"Glob","input":{"pattern":"*.tsx"}}]}}"#
+        ].joined(separator: "\n")
+        let runs = TranscriptReader.parse(content, agentType: .claudeCode).subagents
+
+        XCTAssertEqual(runs.map(\.description), ["A", "B"])
+        XCTAssertEqual(runs.map { $0.toolCalls.map(\.name) }, [["Grep"], ["Glob"]])
+    }
+
+    func testSubagentFilesAreLinkedToTheirTaskCall() throws {
+        let folder = FileManager.default.temporaryDirectory.appendingPathComponent("subagents-\(UUID().uuidString)")
+        defer { try? FileManager.default.removeItem(at: folder) }
+        let sessionId = "11111111-1111-1111-1111-111111111111"
+        try FileManager.default.createDirectory(at: folder.appendingPathComponent("\(sessionId)/subagents"), withIntermediateDirectories: true)
+        let parent = folder.appendingPathComponent("\(sessionId).jsonl")
+        try #"{"type":"assistant","uuid":"00000000-0000-0000-0000-000000000001","timestamp":"2026-01-01T10:00:00Z","message":{"content":[{"type":"tool_use","id":"toolu_task","name":"Task","input":{"description":"Find config loaders","prompt":"Find every place config is loaded"}}]}}"#
+            .write(to: parent, atomically: true, encoding: .utf8)
+        try [
+            #"{"type":"user","isSidechain":true,"sessionId":"11111111-1111-1111-1111-111111111111","timestamp":"2026-01-01T10:00:01Z","message":{"content":"Find every place config is loaded"}}"#,
+            #"{"type":"assistant","isSidechain":true,"sessionId":"11111111-1111-1111-1111-111111111111","timestamp":"2026-01-01T10:00:02Z","message":{"content":[{"type":"tool_use","id":"toolu_grep","name":"Grep","input":{"pattern":"loadConfig"}}]}}"#
+        ].joined(separator: "\n").write(to: folder.appendingPathComponent("\(sessionId)/subagents/agent-a1b2.jsonl"), atomically: true, encoding: .utf8)
+        let sibling = folder.appendingPathComponent("22222222-2222-2222-2222-222222222222.jsonl")
+        try #"{"type":"user","isSidechain":true,"sessionId":"11111111-1111-1111-1111-111111111111","timestamp":"2026-01-01T10:00:01Z","message":{"content":"Unrelated"}}"#
+            .write(to: sibling, atomically: true, encoding: .utf8)
+
+        XCTAssertEqual(TranscriptReader.sidechainParent(ofFileAt: sibling.path), sessionId)
+        XCTAssertEqual(TranscriptReader.sidechainFiles(forSessionAt: parent.path).count, 2)
+        let transcript = try XCTUnwrap(TranscriptReader.read(jsonlPath: parent.path, agentType: .claudeCode))
+        XCTAssertEqual(transcript.subagents.first?.toolCalls.map(\.name), ["Grep"])
+    }
+
+    func testSessionWithoutTaskCallsHasNoSubagents() {
+        let content = #"{"type":"assistant","timestamp":"2026-01-01T10:00:00Z","message":{"content":[{"type":"tool_use","id":"toolu_1","name":"Bash","input":{"command":"ls"}}]}}"#
+        let transcript = TranscriptReader.parse(content, agentType: .claudeCode)
+
+        XCTAssertTrue(transcript.subagents.isEmpty)
+    }
+}
+
+// MARK: - Session Timeline Tests
+
+final class SessionTimelineTests: XCTestCase {
+
+    func testSplitsSessionIntoPhases() throws {
+        let transcript = TranscriptReader.parse([
+            #"{"type":"user","timestamp":"2026-01-01T10:00:00.000Z","message":{"content":"Fix the build"}}"#,
+            #"{"type":"assistant","timestamp":"2026-01-01T10:00:10.000Z","message":{"content":[{"type":"tool_use","id":"toolu_1","name":"Bash","input":{"command":"swift build"}}]}}"#,
+            #"{"type":"user","timestamp":"2026-01-01T10:00:40.000Z","message":{"content":[{"type":"tool_result","tool_use_id":"toolu_1","content":"ok"}]}}"#,
+            #"{"type":"assistant","timestamp":"2026-01-01T10:01:00.000Z","message":{"content":[{"type":"text","text":"Fixed."}]}}"#,
+            #"{"type":"user","timestamp":"2026-01-01T10:03:00.000Z","message":{"content":"Thanks, now the tests"}}"#,
+            #"{"type":"assistant","timestamp":"2026-01-01T10:03:30.000Z","message":{"content":[{"type":"text","text":"They pass."}]}}"#,
+            #"{"type":"user","timestamp":"2026-01-01T10:20:00.000Z","message":{"content":"Great"}}"#
+        ].joined(separator: "\n"), agentType: .claudeCode)
+
+        let segments = SessionTimeline.segments(for: transcript)
+        XCTAssertEqual(segments.map(\.phase), [.thinking, .toolExecution, .thinking, .waitingForUser, .thinking, .waitingForUser, .idle])
+        let totals = Dictionary(uniqueKeysWithValues: SessionTimeline.totals(segments).map { ($0.phase, $0.duration) })
+        XCTAssertEqual(totals, [.thinking: 60, .toolExecution: 30, .waitingForUser: 420, .idle: 690])
+
+        let now = try XCTUnwrap(ISO8601DateFormatter().date(from: "2026-01-01T10:21:00Z"))
+        let live = SessionTimeline.segments(for: transcript, now: now)
+        XCTAssertEqual(live.last?.phase, .thinking)
+        XCTAssertEqual(live.last?.end, now)
+    }
+
+    func testOverlappingCallsCountAsOneToolStretch() {
+        let start = Date(timeIntervalSince1970: 1_767_261_600)
+        var transcript = Transcript()
+        transcript.messages = [
+            Message(role: .user, content: "Check both", timestamp: start),
+            Message(role: .assistant, content: "Done", timestamp: start.addingTimeInterval(60))
+        ]
+        // Listed out of start order, as sub-agent calls are.
+        transcript.toolCalls = [
+            ToolCall(name: "Grep", input: "{}", startedAt: start.addingTimeInterval(20), completedAt: start.addingTimeInterval(40), status: .completed),
+            ToolCall(name: "Task", input: "{}", startedAt: start.addingTimeInterval(10), completedAt: start.addingTimeInterval(30), status: .completed)
+        ]
+
+        let segments = SessionTimeline.segments(for: transcript)
+
+        XCTAssertEqual(segments.map(\.phase), [.thinking, .toolExecution, .thinking])
+        XCTAssertEqual(segments.map(\.duration), [10, 30, 20])
+    }
+}
+
+// MARK: - Transcript Cache Tests
+
+final class TranscriptCacheTests: XCTestCase {
+
+    func testReparsesOnlyWhenTheFileChanges() async throws {
+        let file = FileManager.default.temporaryDirectory.appendingPathComponent("cache-\(UUID().uuidString).jsonl")
+        defer { try? FileManager.default.removeItem(at: file) }
+        try #"{"type":"user","timestamp":"2026-01-01T10:00:00Z","message":{"content":"first"}}"#.write(to: file, atomically: true, encoding: .utf8)
+        let cache = TranscriptCache()
+
+        let first = await cache.transcript(at: file.path, agentType: .claudeCode, redactionPatterns: [])
+        let again = await cache.transcript(at: file.path, agentType: .claudeCode, redactionPatterns: [])
+        XCTAssertEqual(first?.messages.map(\.id), again?.messages.map(\.id))
+
+        try #"{"type":"user","timestamp":"2026-01-01T10:00:00Z","message":{"content":"second"}}"#.write(to: file, atomically: true, encoding: .utf8)
+        try FileManager.default.setAttributes([.modificationDate: Date().addingTimeInterval(60)], ofItemAtPath: file.path)
+        let changed = await cache.transcript(at: file.path, agentType: .claudeCode, redactionPatterns: [])
+        XCTAssertEqual(changed?.messages.map(\.content), ["second"])
+    }
+
+    func testBatchReadsEachFileOnceWithoutEvictingCachedEntries() async throws {
+        let folder = FileManager.default.temporaryDirectory.appendingPathComponent("cache-\(UUID().uuidString)")
+        try FileManager.default.createDirectory(at: folder, withIntermediateDirectories: true)
+        defer { try? FileManager.default.removeItem(at: folder) }
+        let sessions = try (0..<3).map { index -> Session in
+            let file = folder.appendingPathComponent("\(UUID().uuidString.lowercased()).jsonl")
+            try #"{"type":"user","timestamp":"2026-01-01T10:00:00Z","message":{"content":"prompt \#(index)"}}"#.write(to: file, atomically: true, encoding: .utf8)
+            return Session(name: "s\(index)", status: .completed, startedAt: Date(), jsonlPath: file.path)
+        }
+        let cache = TranscriptCache(capacity: 1)
+        let open = await cache.transcript(at: try XCTUnwrap(sessions[0].jsonlPath), agentType: .claudeCode, redactionPatterns: [])
+
+        let batch = TranscriptBatch(cache: cache, redactionPatterns: [])
+        let first = await batch.transcripts(for: sessions)
+        let again = await batch.transcripts(for: Array(sessions.suffix(2)))
+
+        XCTAssertEqual(first.map(\.transcript.messages.first?.content), ["prompt 0", "prompt 1", "prompt 2"])
+        XCTAssertEqual(first[0].transcript.messages.map(\.id), open?.messages.map(\.id))
+        XCTAssertEqual(again.map(\.transcript.messages.first?.id), first.suffix(2).map(\.transcript.messages.first?.id))
+        let stillOpen = await cache.transcript(at: try XCTUnwrap(sessions[0].jsonlPath), agentType: .claudeCode, redactionPatterns: [])
+        XCTAssertEqual(stillOpen?.messages.map(\.id), open?.messages.map(\.id))
+    }
+
+    func testReparsesWhenASiblingSidechainFileChanges() async throws {
+        let folder = FileManager.default.temporaryDirectory.appendingPathComponent("cache-\(UUID().uuidString)")
+        try FileManager.default.createDirectory(at: folder, withIntermediateDirectories: true)
+        defer { try? FileManager.default.removeItem(at: folder) }
+        let sessionId = UUID().uuidString.lowercased()
+        let parent = folder.appendingPathComponent("\(sessionId).jsonl")
+        try #"{"type":"user","timestamp":"2026-01-01T10:00:00Z","message":{"content":"first"}}"#.write(to: parent, atomically: true, encoding: .utf8)
+        let cache = TranscriptCache()
+        let first = await cache.transcript(at: parent.path, agentType: .claudeCode, redactionPatterns: [])
+
+        let sidechain = folder.appendingPathComponent("agent-1.jsonl")
+        try #"{"type":"user","isSidechain":true,"sessionId":"\#(sessionId)","timestamp":"2026-01-01T10:00:05Z","message":{"content":"look around"}}"#
+            .write(to: sidechain, atomically: true, encoding: .utf8)
+        let withSidechain = await cache.transcript(at: parent.path, agentType: .claudeCode, redactionPatterns: [])
+        XCTAssertNotEqual(first?.messages.map(\.id), withSidechain?.messages.map(\.id))
+
+        let cached = await cache.transcript(at: parent.path, agentType: .claudeCode, redactionPatterns: [])
+        XCTAssertEqual(withSidechain?.messages.map(\.id), cached?.messages.map(\.id))
+
+        try FileManager.default.setAttributes([.modificationDate: Date().addingTimeInterval(60)], ofItemAtPath: sidechain.path)
+        let grown = await cache.transcript(at: parent.path, agentType: .claudeCode, redactionPatterns: [])
+        XCTAssertNotEqual(cached?.messages.map(\.id), grown?.messages.map(\.id))
+    }
+}
@@ -0,0 +1,107 @@
+import XCTest
+@testable import AgentsMonitor
+
+final class UsageAnalyticsTests: XCTestCase {
+
+    private func makeSession(status: SessionStatus, agentType: AgentType = .claudeCode, cost: Double = 0, ended: Bool = true) -> Session {
+        let start = Date(timeIntervalSince1970: 1_700_000_000)
+        return Session(
+            name: "Session",
+            status: status,
+            agentType: agentType,
+            startedAt: start,
+            endedAt: ended ? start.addingTimeInterval(600) : nil,
+            metrics: SessionMetrics(totalTokens: 1000, cost: cost)
+        )
+    }
+
+    func testFeatureCountsAccumulate() async {
+        let analytics = UsageAnalytics(fileURL: nil)
+        await analytics.record(.manualRefresh)
+        await analytics.record(.manualRefresh)
+        await analytics.record(.settingsOpened)
+
+        let insights = await analytics.insights()
+        XCTAssertEqual(insights.featureCounts[.manualRefresh], 2)
+        XCTAssertEqual(insights.featureCounts[.settingsOpened], 1)
+        XCTAssertNotNil(insights.trackingSince)
+    }
+
+    func testOutcomesOnlyIncludeEndedSessions() async {
+        let analytics = UsageAnalytics(fileURL: nil)
+        await analytics.recordOutcomes(of: [
+            makeSession(status: .completed, cost: 1.5),
+            makeSession(status: .failed, agentType: .codex, cost: 0.5),
+            makeSession(status: .running, ended: false)
+        ])
+
+        let insights = await analytics.insights()
+        XCTAssertEqual(insights.sessionCount, 2)
+        XCTAssertEqual(insights.failedCount, 1)
+        XCTAssertEqual(insights.failureRate, 0.5, accuracy: 0.0001)
+        XCTAssertEqual(insights.totalCost, 2.0, accuracy: 0.0001)
+        XCTAssertEqual(insights.averageDuration, 600, accuracy: 0.1)
+        XCTAssertEqual(insights.sessionsByAgent[.codex], 1)
+    }
+
+    func testRecordingSameSessionTwiceKeepsLatestOutcome() async {
+        let analytics = UsageAnalytics(fileURL: nil)
+        var session = makeSession(status: .completed, cost: 1.0)
+        await analytics.recordOutcomes(of: [session])
+        session.metrics.cost = 3.0
+        await analytics.recordOutcomes(of: [session])
+
+        let insights = await analytics.insights()
+        XCTAssertEqual(insights.sessionCount, 1)
+        XCTAssertEqual(insights.totalCost, 3.0, accuracy: 0.0001)
+    }
+
+    func testPersistsAcrossInstances() async {
+        let url = FileManager.default.temporaryDirectory.appendingPathComponent("analytics_\(UUID().uuidString).json")
+        defer { try? FileManager.default.removeItem(at: url) }
+
+        let writer = UsageAnalytics(fileURL: url)
+        await writer.record(.popoverOpened)
+        await writer.flush()
+        let insights = await UsageAnalytics(fileURL: url).insights()
+        XCTAssertEqual(insights.featureCounts[.popoverOpened], 1)
+    }
+
+    func testMovesLegacyFileOnFirstRead() async throws {
+        let directory = FileManager.default.temporaryDirectory.appendingPathComponent(UUID().uuidString)
+        defer { try? FileManager.default.removeItem(at: directory) }
+        let legacy = directory.appendingPathComponent("legacy.json")
+        let url = directory.appendingPathComponent("Application Support/usage-analytics.json")
+
+        let writer = UsageAnalytics(fileURL: legacy)
+        await writer.record(.settingsOpened)
+        await writer.flush()
+
+        let insights = await UsageAnalytics(fileURL: url, legacyFileURL: legacy).insights()
+        XCTAssertEqual(insights.featureCounts[.settingsOpened], 1)
+        XCTAssertTrue(FileManager.default.fileExists(atPath: url.path))
+        XCTAssertFalse(FileManager.default.fileExists(atPath: legacy.path))
+    }
+
+    func testWritesAreCoalescedUntilDelayElapses() async throws {
+        let url = FileManager.default.temporaryDirectory.appendingPathComponent("analytics_\(UUID().uuidString).json")
+        defer { try? FileManager.default.removeItem(at: url) }
+
+        let analytics = UsageAnalytics(fileURL: url, saveDelay: .milliseconds(100))
+        await analytics.record(.manualRefresh)
+        await analytics.record(.manualRefresh)
+        XCTAssertFalse(FileManager.default.fileExists(atPath: url.path))
+
+        try await Task.sleep(for: .milliseconds(300))
+        let insights = await UsageAnalytics(fileURL: url).insights()
+        XCTAssertEqual(insights.featureCounts[.manualRefresh], 2)
+    }
+
+    func testResetClearsEverything() async {
+        let analytics = UsageAnalytics(fileURL: nil)
+        await analytics.record(.popoverOpened)
+        await analytics.reset()
+        let insights = await analytics.insights()
+        XCTAssertEqual(insights, UsageAnalytics.Insights())
+    }
+}